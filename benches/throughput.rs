@@ -0,0 +1,113 @@
+//! Measures how many requests per second Goose makes against a minimal local HTTP
+//! server: on the single-threaded basic scheduler earlier versions of Goose ran on, with
+//! `--threads 1`, and on the multi-threaded scheduler with two worker threads and with
+//! one worker thread per CPU core.
+//!
+//! Run with `cargo bench --bench throughput`.
+
+use gumdrop::Options;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+
+/// How many users make requests at the same time.
+const USERS: usize = 32;
+
+/// How many seconds each load test runs once all users are launched. Users are launched
+/// in one second, which is included in the measured throughput.
+const RUN_TIME: usize = 10;
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get("/").await?;
+    Ok(())
+}
+
+/// Reply to every request on a keep-alive connection with a tiny page.
+fn serve_connection(mut stream: TcpStream) {
+    let mut request = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => return,
+            Ok(read) => request.extend_from_slice(&buffer[..read]),
+        }
+        // Requests made by the load test are GETs without a body.
+        while let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            request.drain(..end + 4);
+            if stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Start the HTTP server on a free port, returning its URL.
+fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || serve_connection(stream));
+        }
+    });
+    url
+}
+
+/// Run a load test on the given number of worker threads, returning the requests made
+/// per second.
+fn requests_per_second(host: &str, threads: Option<usize>) -> f32 {
+    let mut configuration = GooseConfiguration::parse_args_default(&[
+        "--host",
+        host,
+        "--users",
+        &USERS.to_string(),
+        "--hatch-rate",
+        &USERS.to_string(),
+        "--run-time",
+        &RUN_TIME.to_string(),
+        "--no-task-metrics",
+        "--no-reset-metrics",
+    ])
+    .unwrap();
+    configuration.threads = threads;
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    let requests: usize = goose_metrics
+        .requests
+        .values()
+        .map(|request| request.success_count + request.fail_count)
+        .sum();
+    requests as f32 / goose_metrics.duration.max(1) as f32
+}
+
+fn main() {
+    let host = start_server();
+    println!(
+        "{} users for {} seconds on {} CPU cores:",
+        USERS,
+        RUN_TIME,
+        num_cpus::get()
+    );
+    for (runtime, threads) in &[
+        ("basic", Some(1)),
+        ("2 workers", Some(2)),
+        ("1 per core", None),
+    ] {
+        println!(
+            " {:<16} {:>10.0} requests/second",
+            runtime,
+            requests_per_second(&host, *threads)
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::util;
+use crate::{GooseControl, GooseController};
+
+/// Commands understood by the controller, displayed by the `help` command.
+const CONTROLLER_HELP: &str = "goose controller commands:
+ users VALUE          adjust running users at the hatch rate
+ hatch-rate VALUE     set per-second user hatch rate
+ throttle VALUE       set maximum requests per second
+ metrics              display running metrics
+ reset-metrics        reset metrics
+ start                start a load test waiting for the web UI
+ pause                pause all users
+ resume               resume all users
+ stop                 stop the load test
+ help                 display this help
+ exit                 close the connection
+";
+
+/// Parse one line received from a controller client into a command for the running
+/// load test. Returns a message for the client if the line isn't a valid command.
+fn parse_command(line: &str) -> Result<ControllerCommand, String> {
+    let mut words = line.split_whitespace();
+    let command = match words.next() {
+        Some(command) => command.to_lowercase(),
+        None => return Ok(ControllerCommand::None),
+    };
+    let value = match words.next() {
+        Some(value) => match value.parse::<usize>() {
+            Ok(value) => Some(value),
+            Err(_) => return Err(format!("invalid value: {}", value)),
+        },
+        None => None,
+    };
+    if words.next().is_some() {
+        return Err(format!("too many arguments for command: {}", command));
+    }
+    parse_control(&command, value)
+}
+
+/// Parse a command and its optional value, shared by the TCP and WebSocket controllers.
+pub(crate) fn parse_control(
+    command: &str,
+    value: Option<usize>,
+) -> Result<ControllerCommand, String> {
+    // Commands either require a value, or don't accept one.
+    let required = || value.ok_or(format!("missing value for command: {}", command));
+    let no_value = || match value {
+        Some(_) => Err(format!("unexpected value for command: {}", command)),
+        None => Ok(()),
+    };
+
+    let control = match command {
+        "users" => GooseControl::SetUsers(required()?),
+        "hatch-rate" => match required()? {
+            0 => return Err("hatch-rate must be at least 1".to_string()),
+            hatch_rate => GooseControl::SetHatchRate(hatch_rate),
+        },
+        "throttle" => GooseControl::SetThrottleRequests(required()?),
+        "reset-metrics" => {
+            no_value()?;
+            GooseControl::ResetMetrics
+        }
+        // A load test waiting for the web UI starts paused.
+        "start" => {
+            no_value()?;
+            GooseControl::Resume
+        }
+        "pause" => {
+            no_value()?;
+            GooseControl::Pause
+        }
+        "resume" => {
+            no_value()?;
+            GooseControl::Resume
+        }
+        "stop" => {
+            no_value()?;
+            GooseControl::Stop
+        }
+        "metrics" => {
+            no_value()?;
+            return Ok(ControllerCommand::Metrics);
+        }
+        "help" | "?" => {
+            no_value()?;
+            return Ok(ControllerCommand::Help);
+        }
+        "exit" | "quit" => {
+            no_value()?;
+            return Ok(ControllerCommand::Exit);
+        }
+        _ => return Err(format!("unrecognized command: {}", command)),
+    };
+    Ok(ControllerCommand::Control(control))
+}
+
+/// A command received from a controller client.
+#[derive(Debug)]
+pub(crate) enum ControllerCommand {
+    /// Forward a command to the running load test.
+    Control(GooseControl),
+    /// Display the running metrics.
+    Metrics,
+    /// Display the available commands.
+    Help,
+    /// Close the connection.
+    Exit,
+    /// Empty line, do nothing.
+    None,
+}
+
+/// Handle commands from one controller client until it disconnects.
+async fn controller_connection(socket: TcpStream, controller: GooseController) {
+    let (reader, mut writer) = tokio::io::split(socket);
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        if writer.write_all(b"goose> ").await.is_err() {
+            break;
+        }
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            // The client disconnected.
+            Ok(None) | Err(_) => break,
+        };
+        let (reply, exit) = match parse_command(&line) {
+            Ok(ControllerCommand::Control(command)) => {
+                debug!("controller command: {:?}", command);
+                if controller.sender.send(command).is_ok() {
+                    ("ok\n".to_string(), false)
+                } else {
+                    ("load test is no longer running\n".to_string(), true)
+                }
+            }
+            Ok(ControllerCommand::Metrics) => {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let _ = controller.sender.send(GooseControl::Metrics(reply_tx));
+                match reply_rx.await {
+                    Ok(metrics) => {
+                        let metrics = metrics.to_string();
+                        if metrics.is_empty() {
+                            ("no metrics to display\n".to_string(), false)
+                        } else {
+                            (metrics, false)
+                        }
+                    }
+                    Err(_) => ("load test is no longer running\n".to_string(), true),
+                }
+            }
+            Ok(ControllerCommand::Help) => (CONTROLLER_HELP.to_string(), false),
+            Ok(ControllerCommand::Exit) => ("goodbye!\n".to_string(), true),
+            Ok(ControllerCommand::None) => (String::new(), false),
+            Err(e) => (format!("error: {}\n", e), false),
+        };
+        if writer.write_all(reply.as_bytes()).await.is_err() || exit {
+            break;
+        }
+    }
+}
+
+/// Start the TCP controller on `address`, forwarding commands to the load test.
+pub async fn spawn_controller(
+    address: &str,
+    sender: mpsc::UnboundedSender<GooseControl>,
+) -> Result<oneshot::Sender<()>, std::io::Error> {
+    let controller = GooseController { sender };
+    let (address, shutdown) = util::spawn_server(address, "controller", move |socket| {
+        controller_connection(socket, controller.clone())
+    })
+    .await?;
+    info!("controller listening on {}", address);
+    Ok(shutdown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_controller_commands() {
+        match parse_command("users 50") {
+            Ok(ControllerCommand::Control(GooseControl::SetUsers(50))) => (),
+            other => panic!("unexpected: {:?}", other),
+        }
+        match parse_command(" Hatch-Rate 5 ") {
+            Ok(ControllerCommand::Control(GooseControl::SetHatchRate(5))) => (),
+            other => panic!("unexpected: {:?}", other),
+        }
+        match parse_command("throttle 100") {
+            Ok(ControllerCommand::Control(GooseControl::SetThrottleRequests(100))) => (),
+            other => panic!("unexpected: {:?}", other),
+        }
+        match parse_command("stop") {
+            Ok(ControllerCommand::Control(GooseControl::Stop)) => (),
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(matches!(
+            parse_command("metrics"),
+            Ok(ControllerCommand::Metrics)
+        ));
+        assert!(matches!(parse_command(""), Ok(ControllerCommand::None)));
+        assert!(matches!(parse_command("quit"), Ok(ControllerCommand::Exit)));
+
+        // Invalid commands and values.
+        assert!(parse_command("users").is_err());
+        assert!(parse_command("users ten").is_err());
+        assert!(parse_command("users 10 20").is_err());
+        assert!(parse_command("hatch-rate 0").is_err());
+        assert!(parse_command("pause 5").is_err());
+        assert!(parse_command("launch").is_err());
+    }
+}
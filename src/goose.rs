@@ -53,17 +53,41 @@
 //!
 //! ### Task Set Wait Time
 //!
-//! Wait time is specified as a low-high integer range. Each time a task completes in
-//! the task set, the user will pause for a random number of seconds inclusively between
+//! Wait time is specified as a low-high `Duration` range. Each time a task completes in
+//! the task set, the user will pause for a random amount of time inclusively between
 //! the low and high wait times. In the following example, users loading `foo` tasks will
 //! sleep 0 to 3 seconds after each task completes, and users loading `bar` tasks will
-//! sleep 5 to 10 seconds after each task completes.
+//! sleep 100 to 500 milliseconds after each task completes.
 //!
 //! ```rust
 //!     use goose::prelude::*;
+//!     use std::time::Duration;
+//!
+//!     let mut foo_tasks = taskset!("FooTasks")
+//!         .set_wait_time(Duration::from_secs(0), Duration::from_secs(3))
+//!         .unwrap();
+//!     let mut bar_tasks = taskset!("BarTasks")
+//!         .set_wait_time(Duration::from_millis(100), Duration::from_millis(500))
+//!         .unwrap();
+//! ```
+//!
+//! Other wait time strategies can be configured with a
+//! [`GooseWaitTime`](./enum.GooseWaitTime.html): a constant pause, exponentially or
+//! normally distributed random pauses, constant pacing (starting a task at a fixed
+//! interval regardless of how long tasks take to run), or a custom function. In the
+//! following example, users loading `foo` tasks start a new task every 2 seconds, and
+//! users loading `bar` tasks pause an average of 5 seconds after each task completes.
 //!
-//!     let mut foo_tasks = taskset!("FooTasks").set_wait_time(0, 3).unwrap();
-//!     let mut bar_tasks = taskset!("BarTasks").set_wait_time(5, 10).unwrap();
+//! ```rust
+//!     use goose::prelude::*;
+//!     use std::time::Duration;
+//!
+//!     let mut foo_tasks = taskset!("FooTasks")
+//!         .set_wait_strategy(GooseWaitTime::ConstantPacing(Duration::from_secs(2)))
+//!         .unwrap();
+//!     let mut bar_tasks = taskset!("BarTasks")
+//!         .set_wait_strategy(GooseWaitTime::Exponential(Duration::from_secs(5)))
+//!         .unwrap();
 //! ```
 //! ## Creating Tasks
 //!
@@ -284,19 +308,22 @@
 
 use http::method::Method;
 use http::StatusCode;
+use rand::Rng;
+use rand_distr::{Exp1, StandardNormal};
 use reqwest::{header, Client, ClientBuilder, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{self, AtomicUsize};
 use std::sync::Arc;
-use std::{future::Future, pin::Pin, time::Instant};
+use std::{future::Future, pin::Pin, time::Duration, time::Instant};
 use tokio::sync::{mpsc, Mutex, RwLock};
 use url::Url;
 
-use crate::metrics::GooseMetric;
+use crate::metrics::{GooseHistogram, GooseMetric};
+use crate::throttle::{self, ThrottlePermit, MAX_THROTTLE_REQUESTS};
 use crate::{GooseConfiguration, GooseError};
 
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
@@ -340,6 +367,8 @@ pub enum GooseTaskError {
     RequestCanceled {
         source: mpsc::error::SendError<bool>,
     },
+    /// The request was canceled because the `--max-requests` limit was reached.
+    MaxRequestsReached,
     /// There was an error sending the metrics for a request to the parent thread.
     /// The `GooseRawRequest` that was not recorded can be extracted from the error
     /// chain, available inside `.source`.
@@ -366,6 +395,9 @@ impl GooseTaskError {
             GooseTaskError::RequestCanceled { .. } => {
                 "request canceled because throttled load test ended"
             }
+            GooseTaskError::MaxRequestsReached => {
+                "request canceled because max_requests was reached"
+            }
             GooseTaskError::MetricsFailed { .. } => "failed to send metrics to parent thread",
             GooseTaskError::LoggerFailed { .. } => "failed to send log message to logger thread",
             GooseTaskError::InvalidMethod { .. } => "unrecognized HTTP request method",
@@ -448,6 +480,129 @@ impl From<mpsc::error::SendError<Option<GooseDebug>>> for GooseTaskError {
     }
 }
 
+/// The function type of a custom wait time. It is passed how long the task that just
+/// completed took to run, and returns how long the user should pause.
+pub type GooseWaitTimeFunction = Arc<dyn Fn(Duration) -> Duration + Send + Sync>;
+
+/// How long a user pauses after running each task, configured per task set with
+/// `GooseTaskSet::set_wait_strategy()`.
+#[derive(Clone)]
+pub enum GooseWaitTime {
+    /// Always pause for the same amount of time. By default users don't pause.
+    Constant(Duration),
+    /// Pause for a random amount of time from the first value to the second value,
+    /// inclusively.
+    Uniform(Duration, Duration),
+    /// Pause for an exponentially distributed random amount of time averaging the
+    /// specified value, modeling users that arrive independently of each other.
+    Exponential(Duration),
+    /// Pause for a normally distributed random amount of time with the specified mean
+    /// and standard deviation. Negative values are treated as no pause.
+    Normal(Duration, Duration),
+    /// Start a task every specified period, pausing for the period minus how long the
+    /// task took to run. If the task took longer than the period, don't pause.
+    ConstantPacing(Duration),
+    /// Pause for the amount of time returned by a custom function.
+    Custom(GooseWaitTimeFunction),
+}
+impl GooseWaitTime {
+    /// Returns how long to pause after a task that took `task_run_time` to run.
+    pub fn wait_time(&self, task_run_time: Duration) -> Duration {
+        match self {
+            GooseWaitTime::Constant(wait) => *wait,
+            GooseWaitTime::Uniform(min, max) => {
+                if min >= max {
+                    *min
+                } else {
+                    let nanos = rand::thread_rng()
+                        .gen_range(min.as_nanos() as u64, max.as_nanos() as u64 + 1);
+                    Duration::from_nanos(nanos)
+                }
+            }
+            GooseWaitTime::Exponential(mean) => {
+                let sample: f64 = rand::thread_rng().sample(Exp1);
+                mean.mul_f64(sample)
+            }
+            GooseWaitTime::Normal(mean, std_dev) => {
+                let sample: f64 = rand::thread_rng().sample(StandardNormal);
+                let wait = mean.as_secs_f64() + sample * std_dev.as_secs_f64();
+                if wait > 0.0 {
+                    Duration::from_secs_f64(wait)
+                } else {
+                    Duration::from_secs(0)
+                }
+            }
+            GooseWaitTime::ConstantPacing(period) => {
+                period.checked_sub(task_run_time).unwrap_or_default()
+            }
+            GooseWaitTime::Custom(function) => function(task_run_time),
+        }
+    }
+}
+impl Default for GooseWaitTime {
+    fn default() -> Self {
+        GooseWaitTime::Constant(Duration::from_secs(0))
+    }
+}
+impl fmt::Debug for GooseWaitTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GooseWaitTime::Constant(wait) => write!(f, "Constant({:?})", wait),
+            GooseWaitTime::Uniform(min, max) => write!(f, "Uniform({:?}, {:?})", min, max),
+            GooseWaitTime::Exponential(mean) => write!(f, "Exponential({:?})", mean),
+            GooseWaitTime::Normal(mean, std_dev) => {
+                write!(f, "Normal({:?}, {:?})", mean, std_dev)
+            }
+            GooseWaitTime::ConstantPacing(period) => write!(f, "ConstantPacing({:?})", period),
+            GooseWaitTime::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+/// Custom functions are only equal to themselves.
+impl PartialEq for GooseWaitTime {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (GooseWaitTime::Constant(a), GooseWaitTime::Constant(b)) => a == b,
+            (GooseWaitTime::Uniform(a, b), GooseWaitTime::Uniform(c, d)) => a == c && b == d,
+            (GooseWaitTime::Exponential(a), GooseWaitTime::Exponential(b)) => a == b,
+            (GooseWaitTime::Normal(a, b), GooseWaitTime::Normal(c, d)) => a == c && b == d,
+            (GooseWaitTime::ConstantPacing(a), GooseWaitTime::ConstantPacing(b)) => a == b,
+            (GooseWaitTime::Custom(a), GooseWaitTime::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+/// The wait time is part of the load test hash, but custom functions can't be hashed.
+impl Hash for GooseWaitTime {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            GooseWaitTime::Constant(wait) => {
+                0.hash(state);
+                wait.hash(state);
+            }
+            GooseWaitTime::Uniform(min, max) => {
+                1.hash(state);
+                min.hash(state);
+                max.hash(state);
+            }
+            GooseWaitTime::Exponential(mean) => {
+                2.hash(state);
+                mean.hash(state);
+            }
+            GooseWaitTime::Normal(mean, std_dev) => {
+                3.hash(state);
+                mean.hash(state);
+                std_dev.hash(state);
+            }
+            GooseWaitTime::ConstantPacing(period) => {
+                4.hash(state);
+                period.hash(state);
+            }
+            GooseWaitTime::Custom(_) => 5.hash(state),
+        }
+    }
+}
+
 /// An individual task set.
 #[derive(Clone, Hash)]
 pub struct GooseTaskSet {
@@ -457,10 +612,8 @@ pub struct GooseTaskSet {
     pub task_sets_index: usize,
     /// An integer value that controls the frequency that this task set will be assigned to a user.
     pub weight: usize,
-    /// An integer value indicating the minimum number of seconds a user will sleep after running a task.
-    pub min_wait: usize,
-    /// An integer value indicating the maximum number of seconds a user will sleep after running a task.
-    pub max_wait: usize,
+    /// How long a user pauses after running each task.
+    pub wait_time: GooseWaitTime,
     /// A vector containing one copy of each GooseTask that will run by users running this task set.
     pub tasks: Vec<GooseTask>,
     /// A vector of vectors of integers, controlling the sequence and order GooseTasks are run.
@@ -471,6 +624,10 @@ pub struct GooseTaskSet {
     pub weighted_on_stop_tasks: Vec<Vec<usize>>,
     /// An optional default host to run this TaskSet against.
     pub host: Option<String>,
+    /// Optional maximum requests per second made by all users running this task set.
+    pub throttle_requests: Option<usize>,
+    /// Optional maximum requests per second made by each user running this task set.
+    pub user_throttle_requests: Option<usize>,
 }
 impl GooseTaskSet {
     /// Creates a new GooseTaskSet. Once created, GooseTasks must be assigned to it, and finally it must be
@@ -488,13 +645,14 @@ impl GooseTaskSet {
             name: name.to_string(),
             task_sets_index: usize::max_value(),
             weight: 1,
-            min_wait: 0,
-            max_wait: 0,
+            wait_time: GooseWaitTime::default(),
             tasks: Vec::new(),
             weighted_tasks: Vec::new(),
             weighted_on_start_tasks: Vec::new(),
             weighted_on_stop_tasks: Vec::new(),
             host: None,
+            throttle_requests: None,
+            user_throttle_requests: None,
         }
     }
 
@@ -568,23 +726,77 @@ impl GooseTaskSet {
         self
     }
 
+    /// Limit the combined number of requests per second made by all users running this
+    /// task set, in addition to any global `--throttle-requests` limit. For example,
+    /// this can model an API client with a contractual rate limit, or keep a heavy task
+    /// set from using up the global throttle. When running in Gaggle mode, each Worker
+    /// applies this limit separately.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     // All AnonymousUsers together make at most 100 requests per second.
+    ///     taskset!("AnonymousUser").set_throttle_requests(100)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_throttle_requests(mut self, requests: usize) -> Result<Self, GooseError> {
+        trace!("{} set_throttle_requests: {}", self.name, requests);
+        validate_throttle_requests(requests)?;
+        self.throttle_requests = Some(requests);
+
+        Ok(self)
+    }
+
+    /// Limit the number of requests per second made by each user running this task
+    /// set. Requests made by a user are spaced evenly, so a limit of 2 requests per
+    /// second allows each user one request every half second.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     // Each ApiClient makes at most 2 requests per second.
+    ///     taskset!("ApiClient").set_user_throttle_requests(2)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_user_throttle_requests(mut self, requests: usize) -> Result<Self, GooseError> {
+        trace!("{} set_user_throttle_requests: {}", self.name, requests);
+        validate_throttle_requests(requests)?;
+        self.user_throttle_requests = Some(requests);
+
+        Ok(self)
+    }
+
     /// Configure a task_set to to pause after running each task. The length of the pause will be randomly
-    /// selected from `min_weight` to `max_wait` inclusively.  For example, if `min_wait` is `0` and
-    /// `max_weight` is `2`, the user will randomly sleep for 0, 1 or 2 seconds after each task completes.
+    /// selected from `min_wait` to `max_wait` inclusively. For example, if `min_wait` is `0` and
+    /// `max_wait` is `2` seconds, the user will randomly sleep between 0 and 2 seconds after each task
+    /// completes. Wait times have millisecond resolution.
     ///
     /// # Example
     /// ```rust
     /// use goose::prelude::*;
+    /// use std::time::Duration;
     ///
     /// fn main() -> Result<(), GooseError> {
-    ///     taskset!("ExampleTasks").set_wait_time(0, 1)?;
+    ///     taskset!("ExampleTasks").set_wait_time(Duration::from_millis(100), Duration::from_millis(500))?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn set_wait_time(mut self, min_wait: usize, max_wait: usize) -> Result<Self, GooseError> {
+    pub fn set_wait_time(
+        mut self,
+        min_wait: Duration,
+        max_wait: Duration,
+    ) -> Result<Self, GooseError> {
         trace!(
-            "{} set_wait time: min: {} max: {}",
+            "{} set_wait time: min: {:?} max: {:?}",
             self.name,
             min_wait,
             max_wait
@@ -598,11 +810,66 @@ impl GooseTaskSet {
                         .to_string(),
             });
         }
-        self.min_wait = min_wait;
-        self.max_wait = max_wait;
+        self.wait_time = GooseWaitTime::Uniform(min_wait, max_wait);
 
         Ok(self)
     }
+
+    /// Configure how long users running this task set pause after running each task,
+    /// with any `GooseWaitTime` strategy. For example, constant pacing starts a task
+    /// every 5 seconds, regardless of how long the tasks take to run (as long as they
+    /// take less than 5 seconds).
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     taskset!("PacedTasks")
+    ///         .set_wait_strategy(GooseWaitTime::ConstantPacing(Duration::from_secs(5)))?;
+    ///
+    ///     taskset!("ThinkingTasks")
+    ///         .set_wait_strategy(GooseWaitTime::Normal(
+    ///             Duration::from_secs(3),
+    ///             Duration::from_millis(500),
+    ///         ))?;
+    ///
+    ///     // Pause twice as long as the task took to run.
+    ///     taskset!("CustomTasks")
+    ///         .set_wait_strategy(GooseWaitTime::Custom(std::sync::Arc::new(|task_run_time| {
+    ///             task_run_time * 2
+    ///         })))?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_wait_strategy(mut self, wait_time: GooseWaitTime) -> Result<Self, GooseError> {
+        trace!("{} set_wait_strategy: {:?}", self.name, wait_time);
+        if let GooseWaitTime::Uniform(min_wait, max_wait) = wait_time {
+            if min_wait > max_wait {
+                return Err(GooseError::InvalidWaitTime {
+                    min_wait,
+                    max_wait,
+                    detail: "The minimum wait time can not be set to a larger value than the maximum wait time.".to_string(),
+                });
+            }
+        }
+        self.wait_time = wait_time;
+
+        Ok(self)
+    }
+}
+
+// Throttles must allow from 1 to 1,000,000 requests per second.
+fn validate_throttle_requests(requests: usize) -> Result<(), GooseError> {
+    if requests == 0 || requests > MAX_THROTTLE_REQUESTS {
+        return Err(GooseError::InvalidThrottle {
+            throttle: requests,
+            detail: "The throttle must be set from 1 to 1,000,000 requests per second.".to_string(),
+        });
+    }
+    Ok(())
 }
 
 /// Commands sent between the parent and user threads, and between manager and
@@ -615,10 +882,14 @@ pub enum GooseUserCommand {
     RUN,
     /// Tell user thread to exit.
     EXIT,
+    /// Tell user thread to stop running tasks until resumed, keeping its session.
+    PAUSE,
+    /// Tell paused user thread to continue running tasks.
+    RESUME,
 }
 
 /// Supported HTTP methods.
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Ord, PartialOrd)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, Ord, PartialOrd)]
 pub enum GooseMethod {
     DELETE,
     GET,
@@ -642,6 +913,28 @@ fn goose_method_from_method(method: Method) -> Result<GooseMethod, GooseTaskErro
     })
 }
 
+/// Describe the kind of error that prevented a request from getting a response, to
+/// group failures in the metrics.
+fn request_error(error: &reqwest::Error) -> &'static str {
+    if error.is_timeout() {
+        "timeout error"
+    } else if error.is_connect() {
+        "connect error"
+    } else if error.is_redirect() {
+        "redirect error"
+    } else if error.is_body() {
+        "body error"
+    } else if error.is_decode() {
+        "decode error"
+    } else if error.is_builder() {
+        "builder error"
+    } else if error.is_request() {
+        "request error"
+    } else {
+        "unknown error"
+    }
+}
+
 /// The request that Goose is making. User threads send this data to the parent thread
 /// when metrics are enabled. This request object must be provided to calls to
 /// [`set_success`](https://docs.rs/goose/*/goose/goose/struct.GooseUser.html#method.set_success)
@@ -652,6 +945,8 @@ fn goose_method_from_method(method: Method) -> Result<GooseMethod, GooseTaskErro
 pub struct GooseRawRequest {
     /// How many milliseconds the load test has been running.
     pub elapsed: u64,
+    /// When the request was made, in nanoseconds since the Unix epoch.
+    pub timestamp: u64,
     /// The method being used (ie, GET, POST, etc).
     pub method: GooseMethod,
     /// The optional name of the request.
@@ -672,11 +967,20 @@ pub struct GooseRawRequest {
     pub update: bool,
     /// Which GooseUser thread processed the request.
     pub user: usize,
+    /// How many milliseconds the request waited on throttles before it was made.
+    pub throttle_wait: u64,
+    /// Why the request failed: the HTTP status, the kind of error making the request,
+    /// or the tag passed to `set_failure`. Empty if the request succeeded.
+    pub error: String,
 }
 impl GooseRawRequest {
     pub fn new(method: GooseMethod, name: &str, url: &str, elapsed: u128, user: usize) -> Self {
         GooseRawRequest {
             elapsed: elapsed as u64,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|now| now.as_nanos() as u64)
+                .unwrap_or(0),
             method,
             name: name.to_string(),
             url: url.to_string(),
@@ -687,6 +991,8 @@ impl GooseRawRequest {
             success: true,
             update: false,
             user,
+            throttle_wait: 0,
+            error: "".to_string(),
         }
     }
 
@@ -735,6 +1041,9 @@ pub struct GooseRequest {
     pub fail_count: usize,
     /// Load test hash.
     pub load_test_hash: u64,
+    /// Optional HDR histogram of exact response times, enabled with `--histogram-digits`.
+    #[serde(default)]
+    pub histogram: Option<GooseHistogram>,
 }
 impl GooseRequest {
     /// Create a new GooseRequest object.
@@ -752,6 +1061,7 @@ impl GooseRequest {
             success_count: 0,
             fail_count: 0,
             load_test_hash,
+            histogram: None,
         }
     }
 
@@ -811,6 +1121,11 @@ impl GooseRequest {
         };
         self.response_times.insert(rounded_response_time, counter);
         debug!("incremented {} counter: {}", rounded_response_time, counter);
+
+        // Also record the exact response time if HDR histograms are enabled.
+        if let Some(histogram) = self.histogram.as_mut() {
+            histogram.record(response_time);
+        }
     }
 
     /// Increment counter for status code, creating new counter if first time seeing status code.
@@ -901,18 +1216,22 @@ pub struct GooseUser {
     pub weighted_bucket_position: Arc<AtomicUsize>,
     /// The base URL to prepend to all relative paths.
     pub base_url: Arc<RwLock<Url>>,
-    /// Minimum amount of time to sleep after running a task.
-    pub min_wait: usize,
-    /// Maximum amount of time to sleep after running a task.
-    pub max_wait: usize,
     /// A local copy of the global GooseConfiguration.
     pub config: GooseConfiguration,
     /// Channel to logger.
     pub logger: Option<mpsc::UnboundedSender<Option<GooseDebug>>>,
     /// Channel to throttle.
-    pub throttle: Option<mpsc::Sender<bool>>,
+    pub throttle: Option<mpsc::Sender<ThrottlePermit>>,
+    /// Channel to the throttle of the task set this user is running, if enabled.
+    pub task_set_throttle: Option<mpsc::Sender<ThrottlePermit>>,
+    /// Minimum time between requests made by this user, if throttled per user.
+    pub request_interval: Option<Duration>,
+    /// When this user is next allowed to make a request, if throttled per user.
+    pub next_request: Arc<Mutex<Instant>>,
     /// Normal tasks are optionally throttled, test_start and test_stop tasks are not.
     pub is_throttled: bool,
+    /// Counts requests made by all users, if limited with `--max-requests`.
+    pub request_counter: Option<Arc<AtomicUsize>>,
     /// Channel to parent.
     pub channel_to_parent: Option<mpsc::UnboundedSender<GooseMetric>>,
     /// An index into the internal `GooseTest.weighted_users, indicating which weighted GooseTaskSet is running.
@@ -935,8 +1254,6 @@ impl GooseUser {
     pub fn new(
         task_sets_index: usize,
         base_url: Url,
-        min_wait: usize,
-        max_wait: usize,
         configuration: &GooseConfiguration,
         load_test_hash: u64,
     ) -> Result<Self, GooseError> {
@@ -953,12 +1270,14 @@ impl GooseUser {
             weighted_bucket: Arc::new(AtomicUsize::new(0)),
             weighted_bucket_position: Arc::new(AtomicUsize::new(0)),
             base_url: Arc::new(RwLock::new(base_url)),
-            min_wait,
-            max_wait,
             config: configuration.clone(),
             logger: None,
             throttle: None,
+            task_set_throttle: None,
+            request_interval: None,
+            next_request: Arc::new(Mutex::new(Instant::now())),
             is_throttled: true,
+            request_counter: None,
             channel_to_parent: None,
             // A value of max_value() indicates this user isn't fully initialized yet.
             weighted_users_index: usize::max_value(),
@@ -973,7 +1292,7 @@ impl GooseUser {
 
     /// Create a new single-use user.
     pub fn single(base_url: Url, configuration: &GooseConfiguration) -> Result<Self, GooseError> {
-        let mut single_user = GooseUser::new(0, base_url, 0, 0, configuration, 0)?;
+        let mut single_user = GooseUser::new(0, base_url, configuration, 0)?;
         // Only one user, so index is 0.
         single_user.weighted_users_index = 0;
         // Do not throttle test_start (setup) and test_stop (teardown) tasks.
@@ -1458,13 +1777,43 @@ impl GooseUser {
         request_builder: RequestBuilder,
         request_name: Option<&str>,
     ) -> Result<GooseResponse, GooseTaskError> {
-        // If throttle-requests is enabled...
-        if self.is_throttled && self.config.throttle_requests.is_some() {
-            // ...wait until there's room to add a token to the throttle channel before proceeding.
-            debug!("GooseUser: waiting on throttle");
-            // Will result in GooseTaskError::RequestCanceled if this fails.
-            self.throttle.clone().unwrap().send(true).await?;
-        };
+        // If --max-requests is enabled, don't make any more requests once it's reached.
+        if let Some(request_counter) = self.request_counter.as_ref() {
+            if let Some(max_requests) = self.config.max_requests {
+                if request_counter.fetch_add(1, atomic::Ordering::SeqCst) >= max_requests {
+                    return Err(GooseTaskError::MaxRequestsReached);
+                }
+            }
+        }
+
+        let throttle_started = Instant::now();
+        if self.is_throttled {
+            // If this user is throttled, wait until it's allowed to make another request.
+            if let Some(request_interval) = self.request_interval {
+                let mut next_request = self.next_request.lock().await;
+                if *next_request > Instant::now() {
+                    debug!("GooseUser: waiting on user throttle");
+                    tokio::time::delay_until((*next_request).into()).await;
+                }
+                *next_request = std::cmp::max(*next_request, Instant::now()) + request_interval;
+            }
+
+            // If this task set is throttled, wait until its throttle grants a permit
+            // before proceeding.
+            if let Some(task_set_throttle) = self.task_set_throttle.as_ref() {
+                debug!("GooseUser: waiting on task set throttle");
+                // Will result in GooseTaskError::RequestCanceled if this fails.
+                throttle::acquire(task_set_throttle).await?;
+            }
+
+            // If throttle-requests is enabled, wait until the throttle grants a permit
+            // before proceeding.
+            if let Some(throttle) = self.throttle.as_ref() {
+                debug!("GooseUser: waiting on throttle");
+                // Will result in GooseTaskError::RequestCanceled if this fails.
+                throttle::acquire(throttle).await?;
+            }
+        }
 
         let started = Instant::now();
         let request = request_builder.build()?;
@@ -1488,9 +1837,12 @@ impl GooseUser {
             self.started.elapsed().as_millis(),
             self.weighted_users_index,
         );
+        raw_request.throttle_wait = started.duration_since(throttle_started).as_millis() as u64;
 
-        // Make the actual request.
-        let response = self.client.lock().await.execute(request).await;
+        // Make the actual request. The client is cheaply cloned so the lock isn't held
+        // while waiting for the response.
+        let client = self.client.lock().await.clone();
+        let response = client.execute(request).await;
         raw_request.set_response_time(started.elapsed().as_millis());
 
         match &response {
@@ -1500,6 +1852,7 @@ impl GooseUser {
                 // @TODO: match/handle all is_foo() https://docs.rs/http/0.2.1/http/status/struct.StatusCode.html
                 if !status_code.is_success() {
                     raw_request.success = false;
+                    raw_request.error = status_code.to_string();
                 }
                 raw_request.set_status_code(Some(status_code));
                 raw_request.set_final_url(r.url().as_str());
@@ -1523,9 +1876,9 @@ impl GooseUser {
                 }
             }
             Err(e) => {
-                // @TODO: what can we learn from a reqwest error?
                 warn!("{:?}: {}", &path, e);
                 raw_request.success = false;
+                raw_request.error = request_error(e).to_string();
                 raw_request.set_status_code(None);
             }
         };
@@ -1595,7 +1948,9 @@ impl GooseUser {
         if !request.success {
             request.success = true;
             request.update = true;
+            // The update includes the error, so the parent can forget the failure.
             self.send_to_parent(GooseMetric::Request(request.clone()))?;
+            request.error.clear();
         }
 
         Ok(())
@@ -1609,12 +1964,12 @@ impl GooseUser {
     /// mutable copy must be included when setting a request as a failure.
     ///
     /// Calls to `set_failure` must include four parameters. The first, `tag`, is an
-    /// arbitrary string identifying the reason for the failure, used when logging. The
-    /// second, `request`, is a mutable reference to the `GooseRawRequest` object of the
-    /// request being identified as a failure (the contained `success` field will be set
-    /// to `false`, and the `update` field will be set to `true`). The last two
-    /// parameters, `header` and `body`, are optional and used to provide more detail in
-    /// logs.
+    /// arbitrary string identifying the reason for the failure, used when logging and
+    /// to group failures in the metrics. The second, `request`, is a mutable reference
+    /// to the `GooseRawRequest` object of the request being identified as a failure (the
+    /// contained `success` field will be set to `false`, and the `update` field will be
+    /// set to `true`). The last two parameters, `header` and `body`, are optional and
+    /// used to provide more detail in logs.
     ///
     /// This also calls
     /// [`log_debug`](https://docs.rs/goose/*/goose/goose/struct.GooseUser.html#method.log_debug).
@@ -1662,6 +2017,7 @@ impl GooseUser {
         if request.success {
             request.success = false;
             request.update = true;
+            request.error = tag.to_string();
             self.send_to_parent(GooseMetric::Request(request.clone()))?;
         }
         // Write failure to log, converting `&mut request` to `&request` as needed by `log_debug()`.
@@ -1861,11 +2217,12 @@ impl GooseUser {
     /// # Example
     /// ```rust,no_run
     /// use goose::prelude::*;
+    /// use std::time::Duration;
     ///
     /// fn main() -> Result<(), GooseError> {
     ///     let _goose_metrics = GooseAttack::initialize()?
     ///         .register_taskset(taskset!("LoadtestTasks").set_host("http//foo.example.com/")
-    ///             .set_wait_time(0, 3)?
+    ///             .set_wait_time(Duration::from_secs(0), Duration::from_secs(3))?
     ///             .register_task(task!(task_foo).set_weight(10)?)
     ///             .register_task(task!(task_bar))
     ///         )
@@ -2235,9 +2592,10 @@ mod tests {
         assert_eq!(task_set.name, "foo");
         assert_eq!(task_set.task_sets_index, usize::max_value());
         assert_eq!(task_set.weight, 1);
-        assert_eq!(task_set.min_wait, 0);
-        assert_eq!(task_set.max_wait, 0);
+        assert_eq!(task_set.wait_time, GooseWaitTime::default());
         assert_eq!(task_set.host, None);
+        assert_eq!(task_set.throttle_requests, None);
+        assert_eq!(task_set.user_throttle_requests, None);
         assert_eq!(task_set.tasks.len(), 0);
         assert_eq!(task_set.weighted_tasks.len(), 0);
         assert_eq!(task_set.weighted_on_start_tasks.len(), 0);
@@ -2249,8 +2607,7 @@ mod tests {
         assert_eq!(task_set.weighted_tasks.len(), 0);
         assert_eq!(task_set.task_sets_index, usize::max_value());
         assert_eq!(task_set.weight, 1);
-        assert_eq!(task_set.min_wait, 0);
-        assert_eq!(task_set.max_wait, 0);
+        assert_eq!(task_set.wait_time, GooseWaitTime::default());
         assert_eq!(task_set.host, None);
 
         // Different task can be registered.
@@ -2259,8 +2616,7 @@ mod tests {
         assert_eq!(task_set.weighted_tasks.len(), 0);
         assert_eq!(task_set.task_sets_index, usize::max_value());
         assert_eq!(task_set.weight, 1);
-        assert_eq!(task_set.min_wait, 0);
-        assert_eq!(task_set.max_wait, 0);
+        assert_eq!(task_set.wait_time, GooseWaitTime::default());
         assert_eq!(task_set.host, None);
 
         // Same task can be registered again.
@@ -2269,8 +2625,7 @@ mod tests {
         assert_eq!(task_set.weighted_tasks.len(), 0);
         assert_eq!(task_set.task_sets_index, usize::max_value());
         assert_eq!(task_set.weight, 1);
-        assert_eq!(task_set.min_wait, 0);
-        assert_eq!(task_set.max_wait, 0);
+        assert_eq!(task_set.wait_time, GooseWaitTime::default());
         assert_eq!(task_set.host, None);
 
         // Setting weight only affects weight field.
@@ -2279,8 +2634,7 @@ mod tests {
         assert_eq!(task_set.tasks.len(), 3);
         assert_eq!(task_set.weighted_tasks.len(), 0);
         assert_eq!(task_set.task_sets_index, usize::max_value());
-        assert_eq!(task_set.min_wait, 0);
-        assert_eq!(task_set.max_wait, 0);
+        assert_eq!(task_set.wait_time, GooseWaitTime::default());
         assert_eq!(task_set.host, None);
 
         // Weight can be changed.
@@ -2294,17 +2648,20 @@ mod tests {
         assert_eq!(task_set.tasks.len(), 3);
         assert_eq!(task_set.weighted_tasks.len(), 0);
         assert_eq!(task_set.task_sets_index, usize::max_value());
-        assert_eq!(task_set.min_wait, 0);
-        assert_eq!(task_set.max_wait, 0);
+        assert_eq!(task_set.wait_time, GooseWaitTime::default());
 
         // Host field can be changed.
         task_set = task_set.set_host("https://bar.example.com/");
         assert_eq!(task_set.host, Some("https://bar.example.com/".to_string()));
 
         // Wait time only affects wait time fields.
-        task_set = task_set.set_wait_time(1, 10).unwrap();
-        assert_eq!(task_set.min_wait, 1);
-        assert_eq!(task_set.max_wait, 10);
+        task_set = task_set
+            .set_wait_time(Duration::from_secs(1), Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(
+            task_set.wait_time,
+            GooseWaitTime::Uniform(Duration::from_secs(1), Duration::from_secs(10))
+        );
         assert_eq!(task_set.host, Some("https://bar.example.com/".to_string()));
         assert_eq!(task_set.weight, 5);
         assert_eq!(task_set.tasks.len(), 3);
@@ -2312,9 +2669,39 @@ mod tests {
         assert_eq!(task_set.task_sets_index, usize::max_value());
 
         // Wait time can be changed.
-        task_set = task_set.set_wait_time(3, 9).unwrap();
-        assert_eq!(task_set.min_wait, 3);
-        assert_eq!(task_set.max_wait, 9);
+        task_set = task_set
+            .set_wait_time(Duration::from_millis(300), Duration::from_millis(900))
+            .unwrap();
+        assert_eq!(
+            task_set.wait_time,
+            GooseWaitTime::Uniform(Duration::from_millis(300), Duration::from_millis(900))
+        );
+
+        // Any wait time strategy can be configured.
+        task_set = task_set
+            .set_wait_strategy(GooseWaitTime::ConstantPacing(Duration::from_secs(2)))
+            .unwrap();
+        assert_eq!(
+            task_set.wait_time,
+            GooseWaitTime::ConstantPacing(Duration::from_secs(2))
+        );
+
+        // Throttles only affect throttle fields.
+        task_set = task_set.set_throttle_requests(100).unwrap();
+        assert_eq!(task_set.throttle_requests, Some(100));
+        assert_eq!(task_set.user_throttle_requests, None);
+        task_set = task_set.set_user_throttle_requests(2).unwrap();
+        assert_eq!(task_set.throttle_requests, Some(100));
+        assert_eq!(task_set.user_throttle_requests, Some(2));
+        assert_eq!(task_set.weight, 5);
+
+        // The minimum uniform wait time can't be larger than the maximum.
+        assert!(task_set
+            .set_wait_strategy(GooseWaitTime::Uniform(
+                Duration::from_secs(2),
+                Duration::from_secs(1)
+            ))
+            .is_err());
     }
 
     #[test]
@@ -2397,6 +2784,61 @@ mod tests {
         assert_eq!(task.sequence, 8);
     }
 
+    #[test]
+    fn goose_wait_time() {
+        let task_run_time = Duration::from_millis(300);
+
+        let wait_time = GooseWaitTime::default();
+        assert_eq!(wait_time.wait_time(task_run_time), Duration::from_secs(0));
+
+        let wait_time = GooseWaitTime::Constant(Duration::from_millis(250));
+        assert_eq!(
+            wait_time.wait_time(task_run_time),
+            Duration::from_millis(250)
+        );
+
+        let min = Duration::from_millis(100);
+        let max = Duration::from_millis(200);
+        let wait_time = GooseWaitTime::Uniform(min, max);
+        for _ in 0..100 {
+            let wait = wait_time.wait_time(task_run_time);
+            assert!(wait >= min && wait <= max);
+        }
+        let wait_time = GooseWaitTime::Uniform(max, max);
+        assert_eq!(wait_time.wait_time(task_run_time), max);
+
+        // Random distributions never return a negative wait time.
+        let wait_time = GooseWaitTime::Exponential(Duration::from_millis(100));
+        let wait_time_normal =
+            GooseWaitTime::Normal(Duration::from_millis(100), Duration::from_secs(1));
+        for _ in 0..100 {
+            assert!(wait_time.wait_time(task_run_time) < Duration::from_secs(60));
+            assert!(wait_time_normal.wait_time(task_run_time) < Duration::from_secs(60));
+        }
+
+        // Constant pacing subtracts the task run time from the period.
+        let wait_time = GooseWaitTime::ConstantPacing(Duration::from_secs(1));
+        assert_eq!(
+            wait_time.wait_time(task_run_time),
+            Duration::from_millis(700)
+        );
+        assert_eq!(
+            wait_time.wait_time(Duration::from_secs(2)),
+            Duration::from_secs(0)
+        );
+
+        let wait_time = GooseWaitTime::Custom(Arc::new(|task_run_time| task_run_time * 2));
+        assert_eq!(
+            wait_time.wait_time(task_run_time),
+            Duration::from_millis(600)
+        );
+        assert_eq!(wait_time, wait_time.clone());
+        assert_ne!(
+            wait_time,
+            GooseWaitTime::Custom(Arc::new(|task_run_time| task_run_time * 2))
+        );
+    }
+
     #[test]
     fn goose_raw_request() {
         const PATH: &str = "http://127.0.0.1/";
@@ -2641,10 +3083,8 @@ mod tests {
         const HOST: &str = "http://example.com/";
         let configuration = GooseConfiguration::default();
         let base_url = get_base_url(Some(HOST.to_string()), None, None).unwrap();
-        let user = GooseUser::new(0, base_url, 0, 0, &configuration, 0).unwrap();
+        let user = GooseUser::new(0, base_url, &configuration, 0).unwrap();
         assert_eq!(user.task_sets_index, 0);
-        assert_eq!(user.min_wait, 0);
-        assert_eq!(user.max_wait, 0);
         assert_eq!(user.weighted_users_index, usize::max_value());
         assert_eq!(user.weighted_on_start_tasks.len(), 0);
         assert_eq!(user.weighted_tasks.len(), 0);
@@ -2677,9 +3117,7 @@ mod tests {
             Some("http://www.example.com/".to_string()),
         )
         .unwrap();
-        let user2 = GooseUser::new(0, base_url, 1, 3, &configuration, 0).unwrap();
-        assert_eq!(user2.min_wait, 1);
-        assert_eq!(user2.max_wait, 3);
+        let user2 = GooseUser::new(0, base_url, &configuration, 0).unwrap();
 
         // Confirm the URLs are correctly built using the task_set_host.
         let url = user2.build_url("/foo").await.unwrap();
@@ -0,0 +1,318 @@
+use std::fmt::Write;
+use std::time;
+
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::goose::GooseRawRequest;
+use crate::metrics::GooseMetricsSummary;
+
+/// How often buffered points are posted to an InfluxDB endpoint.
+const POST_EVERY: time::Duration = time::Duration::from_secs(1);
+
+/// How long connecting to an InfluxDB endpoint may take before giving up.
+const CONNECT_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+/// How long posting points to an InfluxDB endpoint may take before giving up.
+const POST_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+/// How many batches of points may wait to be posted before new batches are dropped.
+const POST_QUEUE: usize = 16;
+
+/// Writes metrics as InfluxDB line protocol, to a file and/or an HTTP endpoint.
+#[derive(Debug, Default)]
+pub struct InfluxDbOutput {
+    /// A buffered file the line protocol is written to, if enabled.
+    file: Option<(String, BufWriter<File>)>,
+    /// Sends batches of points to the task posting them to an endpoint, if enabled,
+    /// so a slow endpoint never blocks the load test.
+    endpoint: Option<(mpsc::Sender<String>, JoinHandle<()>)>,
+    /// Points waiting to be written.
+    buffer: String,
+    /// When buffered points were last posted to the endpoint.
+    posted: Option<time::Instant>,
+}
+impl InfluxDbOutput {
+    /// Write line protocol to the named file.
+    pub async fn file(&mut self, name: &str) -> Result<(), std::io::Error> {
+        info!("writing influxdb line protocol to {}", name);
+        let file = File::create(name).await?;
+        self.file = Some((name.to_string(), BufWriter::new(file)));
+        Ok(())
+    }
+
+    /// Post line protocol to an InfluxDB-compatible write endpoint, such as
+    /// `http://127.0.0.1:8086/write?db=goose`.
+    pub fn endpoint(&mut self, url: &str) -> Result<(), reqwest::Error> {
+        info!("posting influxdb line protocol to {}", url);
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(POST_TIMEOUT)
+            .build()?;
+        let (sender, receiver) = mpsc::channel(POST_QUEUE);
+        let poster = tokio::spawn(post_points(client, url.to_string(), receiver));
+        self.endpoint = Some((sender, poster));
+        Ok(())
+    }
+
+    /// Record a point for a request, tagged with its method, name, status code and user.
+    pub fn record_request(&mut self, raw_request: &GooseRawRequest) {
+        let method = format!("{:?}", raw_request.method);
+        let status_code = raw_request.status_code.to_string();
+        let user = raw_request.user.to_string();
+        let fields = [
+            ("elapsed", format!("{}i", raw_request.elapsed)),
+            ("response_time", format!("{}i", raw_request.response_time)),
+            ("success", raw_request.success.to_string()),
+            ("update", raw_request.update.to_string()),
+            ("redirected", raw_request.redirected.to_string()),
+            ("throttle_wait", format!("{}i", raw_request.throttle_wait)),
+            ("url", string_field(&raw_request.url)),
+        ];
+        push_point(
+            &mut self.buffer,
+            "goose_request",
+            &[
+                ("method", &method),
+                ("name", &raw_request.name),
+                ("status_code", &status_code),
+                ("user", &user),
+            ],
+            &fields,
+            raw_request.timestamp,
+        );
+    }
+
+    /// Record a point for each request, task and failure in the final metrics summary.
+    pub fn record_summary(&mut self, summary: &GooseMetricsSummary) {
+        let timestamp = now();
+        for request in &summary.requests {
+            let method = format!("{:?}", request.method);
+            let mut fields = vec![
+                ("requests", format!("{}i", request.requests)),
+                ("fails", format!("{}i", request.fails)),
+                (
+                    "requests_per_second",
+                    request.requests_per_second.to_string(),
+                ),
+                ("fails_per_second", request.fails_per_second.to_string()),
+                (
+                    "min_response_time",
+                    format!("{}i", request.min_response_time),
+                ),
+                ("mean_response_time", request.mean_response_time.to_string()),
+                (
+                    "median_response_time",
+                    format!("{}i", request.median_response_time),
+                ),
+                (
+                    "max_response_time",
+                    format!("{}i", request.max_response_time),
+                ),
+            ];
+            let percentiles: Vec<(String, String)> = request
+                .percentiles
+                .iter()
+                .map(|(percentile, time)| (format!("p{}", percentile), format!("{}i", time)))
+                .collect();
+            for (percentile, time) in &percentiles {
+                fields.push((percentile, time.to_string()));
+            }
+            push_point(
+                &mut self.buffer,
+                "goose_request_summary",
+                &[("method", &method), ("name", &request.name)],
+                &fields,
+                timestamp,
+            );
+        }
+        for task in &summary.tasks {
+            let task_index = task.task_index.to_string();
+            let fields = [
+                ("runs", format!("{}i", task.runs)),
+                ("fails", format!("{}i", task.fails)),
+                ("runs_per_second", task.runs_per_second.to_string()),
+                ("fails_per_second", task.fails_per_second.to_string()),
+                ("min_time", format!("{}i", task.min_time)),
+                ("mean_time", task.mean_time.to_string()),
+                ("median_time", format!("{}i", task.median_time)),
+                ("max_time", format!("{}i", task.max_time)),
+            ];
+            push_point(
+                &mut self.buffer,
+                "goose_task_summary",
+                &[
+                    ("taskset", &task.taskset_name),
+                    ("task_index", &task_index),
+                    ("task", &task.task_name),
+                ],
+                &fields,
+                timestamp,
+            );
+        }
+        for failure in &summary.failures {
+            let method = format!("{:?}", failure.method);
+            push_point(
+                &mut self.buffer,
+                "goose_failure_summary",
+                &[
+                    ("method", &method),
+                    ("name", &failure.name),
+                    ("error", &failure.error),
+                ],
+                &[("occurrences", format!("{}i", failure.occurrences))],
+                timestamp,
+            );
+        }
+    }
+
+    /// Write buffered points. Points are posted to an endpoint at most once a second,
+    /// unless `force` is set when the load test ends, which also waits for all points
+    /// to be posted.
+    pub async fn flush(&mut self, force: bool) {
+        if force {
+            if let Some((mut sender, poster)) = self.endpoint.take() {
+                if !self.buffer.is_empty() {
+                    let _ = sender.send(self.buffer.clone()).await;
+                }
+                drop(sender);
+                if tokio::time::timeout(POST_TIMEOUT, poster).await.is_err() {
+                    warn!("gave up posting influxdb line protocol");
+                }
+            }
+        }
+        if self.buffer.is_empty() {
+            return;
+        }
+        if let Some((sender, _)) = self.endpoint.as_mut() {
+            if matches!(self.posted, Some(posted) if posted.elapsed() < POST_EVERY) {
+                return;
+            }
+            self.posted = Some(time::Instant::now());
+            if sender.try_send(self.buffer.clone()).is_err() {
+                warn!("dropped influxdb line protocol, the endpoint isn't keeping up");
+            }
+        }
+        if let Some((name, file)) = self.file.as_mut() {
+            if let Err(e) = file.write_all(self.buffer.as_bytes()).await {
+                warn!("failed to write influxdb line protocol to {}: {}", name, e);
+            }
+            if force {
+                let _ = file.flush().await;
+            }
+        }
+        self.buffer.clear();
+    }
+}
+
+/// Post batches of points to an endpoint until the load test ends.
+async fn post_points(client: reqwest::Client, url: String, mut receiver: mpsc::Receiver<String>) {
+    while let Some(points) = receiver.recv().await {
+        match client.post(&url).body(points).send().await {
+            Ok(response) if !response.status().is_success() => {
+                warn!(
+                    "failed to post influxdb line protocol to {}: {}",
+                    url,
+                    response.status()
+                );
+            }
+            Ok(_) => (),
+            Err(e) => warn!("failed to post influxdb line protocol to {}: {}", url, e),
+        }
+    }
+    debug!("influxdb poster exiting");
+}
+
+/// Nanoseconds since the Unix epoch.
+fn now() -> u64 {
+    time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|now| now.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Escape commas, equals signs and spaces in a measurement, tag key or tag value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ',' | '=' | ' ' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            // Line protocol doesn't allow newlines, even escaped.
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quote a string field value.
+fn string_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Append one point in line protocol, for example
+/// `goose_request,method=GET,name=/ response_time=12i 1600000000000000000`. Empty tag
+/// values aren't allowed, so they're left out.
+fn push_point(
+    buffer: &mut String,
+    measurement: &str,
+    tags: &[(&str, &str)],
+    fields: &[(&str, String)],
+    timestamp: u64,
+) {
+    buffer.push_str(&escape(measurement));
+    for (tag, value) in tags {
+        if !value.is_empty() {
+            let _ = write!(buffer, ",{}={}", escape(tag), escape(value));
+        }
+    }
+    for (index, (field, value)) in fields.iter().enumerate() {
+        let separator = if index == 0 { ' ' } else { ',' };
+        let _ = write!(buffer, "{}{}={}", separator, escape(field), value);
+    }
+    let _ = writeln!(buffer, " {}", timestamp);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goose::GooseMethod;
+
+    #[test]
+    fn line_protocol() {
+        let mut raw_request =
+            GooseRawRequest::new(GooseMethod::GET, "/a page,1", "http://localhost/", 5, 2);
+        raw_request.response_time = 12;
+        raw_request.status_code = 200;
+        raw_request.timestamp = 1_600_000_000_123_456_789;
+        let mut output = InfluxDbOutput::default();
+        output.record_request(&raw_request);
+        assert_eq!(
+            output.buffer,
+            "goose_request,method=GET,name=/a\\ page\\,1,status_code=200,user=2 \
+             elapsed=5i,response_time=12i,success=true,update=false,redirected=false,\
+             throttle_wait=0i,url=\"http://localhost/\" 1600000000123456789\n"
+        );
+
+        let mut buffer = String::new();
+        push_point(
+            &mut buffer,
+            "goose_task_summary",
+            &[("taskset", "LoadTest"), ("task", "")],
+            &[
+                ("runs", "3i".to_string()),
+                ("label", string_field("a \"b\"")),
+            ],
+            0,
+        );
+        assert_eq!(
+            buffer,
+            "goose_task_summary,taskset=LoadTest runs=3i,label=\"a \\\"b\\\"\" 0\n"
+        );
+    }
+}
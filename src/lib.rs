@@ -91,11 +91,12 @@
 //!
 //! ```rust,no_run
 //! use goose::prelude::*;
+//! use std::time::Duration;
 //!
 //! fn main() -> Result<(), GooseError> {
 //!     let _goose_metrics = GooseAttack::initialize()?
 //!         .register_taskset(taskset!("LoadtestTasks")
-//!             .set_wait_time(0, 3)?
+//!             .set_wait_time(Duration::from_secs(0), Duration::from_secs(3))?
 //!             // Register the foo task, assigning it a weight of 10.
 //!             .register_task(task!(loadtest_foo).set_weight(10)?)
 //!             // Register the bar task, assigning it a weight of 2 (so it
@@ -303,15 +304,26 @@
 #[macro_use]
 extern crate log;
 
+mod controller;
 pub mod goose;
+mod influxdb;
 pub mod logger;
 #[cfg(feature = "gaggle")]
 mod manager;
 pub mod metrics;
 pub mod prelude;
+mod prometheus;
+mod report;
+pub mod sink;
+mod stream;
+mod test_plan;
+pub mod threshold;
 mod throttle;
 mod user;
 mod util;
+mod websocket;
+#[cfg(feature = "webui")]
+mod webui;
 #[cfg(feature = "gaggle")]
 mod worker;
 
@@ -320,36 +332,57 @@ use lazy_static::lazy_static;
 #[cfg(feature = "gaggle")]
 use nng::Socket;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use simplelog::*;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::{
     atomic::{AtomicBool, AtomicUsize, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 use std::{f32, fmt, io, time};
-use tokio::fs::File;
-use tokio::io::BufWriter;
-use tokio::prelude::*;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use url::Url;
 
-use crate::goose::{
-    GooseDebug, GooseRawRequest, GooseRequest, GooseTask, GooseTaskSet, GooseUser, GooseUserCommand,
-};
-use crate::metrics::{GooseMetric, GooseMetrics};
+use crate::goose::{GooseDebug, GooseTask, GooseTaskSet, GooseUser, GooseUserCommand};
+use crate::influxdb::InfluxDbOutput;
+use crate::metrics::{GooseHistorySnapshot, GooseMetric, GooseMetrics, GooseRequestMetrics};
+use crate::prometheus::SharedPrometheusMetrics;
+use crate::sink::{CsvMetricsSink, GooseMetricsSink, JsonMetricsSink, RawMetricsSink};
+use crate::stream::MetricsStream;
+use crate::test_plan::{TestPlan, TestPlanStep};
+use crate::threshold::{GooseThreshold, GooseThresholdVerdict};
+use crate::throttle::{ThrottleCommand, ThrottlePermit, ThrottleStep};
 #[cfg(feature = "gaggle")]
 use crate::worker::GaggleMetrics;
 
 /// Constant defining how often metrics should be displayed while load test is running.
 const RUNNING_METRICS_EVERY: usize = 15;
 
+/// Constant defining how long, in milliseconds, the parent loop spends receiving
+/// metrics before it handles controller commands and other housekeeping.
+const RECEIVE_METRICS_FOR: u64 = 250;
+
 /// Constant defining Goose's default port when running a Gaggle.
 const DEFAULT_PORT: &str = "5115";
 
+/// Constant defining the host the controller listens on by default.
+const DEFAULT_CONTROLLER_HOST: &str = "127.0.0.1";
+
+/// Constant defining the port the controller listens on by default.
+const DEFAULT_CONTROLLER_PORT: u16 = 5116;
+
+/// Constant defining the port the WebSocket controller listens on by default.
+const DEFAULT_WEBSOCKET_PORT: u16 = 5117;
+
+/// Constant defining the port the web UI listens on by default.
+const DEFAULT_WEBUI_PORT: u16 = 5118;
+
+/// Constant defining the port the Prometheus endpoint listens on by default.
+const DEFAULT_PROMETHEUS_PORT: u16 = 5119;
+
 // WORKER_ID is only used when running a gaggle (a distributed load test).
 lazy_static! {
     static ref WORKER_ID: AtomicUsize = AtomicUsize::new(0);
@@ -394,12 +427,15 @@ pub enum GooseError {
         value: String,
         detail: String,
     },
+    /// Invalid throttle specified. The invalid number of requests per second is found in
+    /// `.throttle`. An optional explanation providing context may be found in `.detail`.
+    InvalidThrottle { throttle: usize, detail: String },
     /// Invalid wait time specified. The minimum wait time and maximum wait time are found in
     /// `.min_wait` and `.max_wait` respectively. An optional explanation providing context may
     /// be found in `.detail`.
     InvalidWaitTime {
-        min_wait: usize,
-        max_wait: usize,
+        min_wait: time::Duration,
+        max_wait: time::Duration,
         detail: String,
     },
     /// Invalid weight specified. The invalid weight value is found in `.weight`. An optional
@@ -408,6 +444,9 @@ pub enum GooseError {
     /// `GooseAttack` has no `GooseTaskSet` defined. An optional explanation may be found in
     /// `.detail`.
     NoTaskSets { detail: String },
+    /// One or more thresholds failed. Whether each threshold passed is found in
+    /// `.verdict`.
+    ThresholdsFailed { verdict: GooseThresholdVerdict },
 }
 impl GooseError {
     fn describe(&self) -> &str {
@@ -417,9 +456,11 @@ impl GooseError {
             GooseError::FeatureNotEnabled { .. } => "required compile-time feature not enabled",
             GooseError::InvalidHost { .. } => "failed to parse hostname",
             GooseError::InvalidOption { .. } => "invalid option or value specified",
+            GooseError::InvalidThrottle { .. } => "invalid throttle specified",
             GooseError::InvalidWaitTime { .. } => "invalid wait_time specified",
             GooseError::InvalidWeight { .. } => "invalid weight specified",
             GooseError::NoTaskSets { .. } => "no task sets defined",
+            GooseError::ThresholdsFailed { .. } => "one or more thresholds failed",
         }
     }
 }
@@ -435,6 +476,17 @@ impl fmt::Display for GooseError {
             GooseError::InvalidHost {
                 ref parse_error, ..
             } => write!(f, "GooseError: {} ({})", self.describe(), parse_error),
+            GooseError::ThresholdsFailed { ref verdict } => write!(
+                f,
+                "GooseError: {} ({})",
+                self.describe(),
+                verdict
+                    .failures()
+                    .iter()
+                    .map(|result| result.threshold.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
             _ => write!(f, "GooseError: {}", self.describe()),
         }
     }
@@ -468,6 +520,325 @@ impl From<io::Error> for GooseError {
     }
 }
 
+/// Channels to the throttle thread, if enabled.
+type ThrottleChannels = (
+    // A channel used by GooseClients to throttle requests.
+    Option<mpsc::Sender<ThrottlePermit>>,
+    // A channel used by parent to control the throttle, and to tell it the load test
+    // is complete.
+    Option<mpsc::UnboundedSender<ThrottleCommand>>,
+);
+
+/// Internal state tracked while a load test is running.
+struct GooseAttackRunState {
+    /// A handle to later rejoin the logger thread, if enabled.
+    logger_thread: Option<tokio::task::JoinHandle<()>>,
+    /// A channel used by GooseUsers to send debug logs, if enabled.
+    all_threads_logger: Option<mpsc::UnboundedSender<Option<GooseDebug>>>,
+    /// A channel used by GooseUsers to throttle requests, if enabled.
+    all_threads_throttle: Option<mpsc::Sender<ThrottlePermit>>,
+    /// A channel used by parent to control the throttle and tell it the load test is
+    /// complete, if enabled.
+    parent_to_throttle_tx: Option<mpsc::UnboundedSender<ThrottleCommand>>,
+    /// A channel per task set used by GooseUsers to throttle requests, if enabled.
+    task_set_throttles: Vec<Option<mpsc::Sender<ThrottlePermit>>>,
+    /// Channels used by parent to tell task set throttles the load test is complete.
+    parent_to_task_set_throttles: Vec<mpsc::UnboundedSender<ThrottleCommand>>,
+    /// A channel used by GooseUsers to sync metrics back to the parent.
+    all_threads_sender: mpsc::UnboundedSender<GooseMetric>,
+    /// The parent receives metrics from GooseUsers on this channel.
+    metric_receiver: mpsc::UnboundedReceiver<GooseMetric>,
+    /// Handles of all launched user threads, to wait for them to exit.
+    users: Vec<tokio::task::JoinHandle<()>>,
+    /// Counts users that finished running, because they completed all --iterations or
+    /// reached --max-requests.
+    users_finished: Arc<AtomicUsize>,
+    /// Counts requests made by all users, if limited with --max-requests.
+    request_counter: Arc<AtomicUsize>,
+    /// Channels used to control running user threads, in the order they were launched.
+    user_channels: Vec<mpsc::UnboundedSender<GooseUserCommand>>,
+    /// The test plan being followed.
+    test_plan: TestPlan,
+    /// The test plan step currently running.
+    step: usize,
+    /// When the current test plan step started.
+    step_started: time::Instant,
+    /// How many users were running when the current test plan step started.
+    step_users: usize,
+    /// Whether the next test plan step holds the users set by `set_users` for the
+    /// rest of the step it overrode.
+    step_held: bool,
+    /// Whether users are being stopped gradually at the end of the load test.
+    ramping_down: bool,
+    /// Receives commands from GooseControllers, if not already taken by an earlier load test.
+    control_receiver: Option<mpsc::UnboundedReceiver<GooseControl>>,
+    /// A channel used by GooseUsers to report they're ready to start an iteration, if
+    /// running at an arrival rate.
+    all_threads_ready: Option<mpsc::UnboundedSender<oneshot::Sender<()>>>,
+    /// The parent receives ready GooseUsers on this channel, if running at an arrival rate.
+    ready_receiver: Option<mpsc::UnboundedReceiver<oneshot::Sender<()>>>,
+    /// GooseUsers waiting to start an iteration, in the order they became ready.
+    ready_users: VecDeque<oneshot::Sender<()>>,
+    /// When iterations started being scheduled at the arrival rate.
+    arrivals_started: time::Instant,
+    /// How many iterations have been scheduled at the arrival rate.
+    arrivals: usize,
+    /// When the load test was paused, if it is currently paused.
+    paused: Option<time::Instant>,
+    /// Set when the load test is canceled with ctrl-c or told to stop by a controller.
+    canceled: Arc<AtomicBool>,
+    /// Channels used to shut down the TCP and WebSocket controllers, the web UI and
+    /// the Prometheus endpoint, if enabled.
+    controller_shutdowns: Vec<oneshot::Sender<()>>,
+    /// How often to record a snapshot of the metrics history, if enabled.
+    history_interval: Option<time::Duration>,
+    /// When the current metrics history interval started.
+    history_started: time::Instant,
+    /// Requests made during the current metrics history interval, if enabled.
+    history_requests: Option<GooseRequestMetrics>,
+    /// Metrics served to Prometheus, if enabled.
+    prometheus: Option<SharedPrometheusMetrics>,
+    /// Streams metrics to StatsD or Graphite, if enabled.
+    metrics_stream: Option<MetricsStream>,
+    /// Writes metrics as InfluxDB line protocol, if enabled.
+    influxdb: Option<InfluxDbOutput>,
+    /// Outputs receiving metrics, including the --metrics-file if enabled.
+    metrics_sinks: Vec<Box<dyn GooseMetricsSink>>,
+    /// When each `abort_on_fail` threshold started being breached, if it currently is.
+    threshold_breaches: Vec<Option<time::Instant>>,
+    /// When `abort_on_fail` thresholds were last evaluated.
+    thresholds_checked: time::Instant,
+    /// Requests made since `abort_on_fail` thresholds were last evaluated, if any are
+    /// set.
+    threshold_requests: Option<GooseRequestMetrics>,
+}
+impl GooseAttackRunState {
+    /// How many users are running, excluding users that finished on their own.
+    fn running_users(&self) -> usize {
+        self.user_channels
+            .len()
+            .saturating_sub(self.users_finished.load(Ordering::SeqCst))
+    }
+
+    /// Override the current test plan step, adjusting to `users` running users over
+    /// `duration`. If the current step had more time remaining, the new number of
+    /// users is held for the rest of that time before continuing with the test plan.
+    fn set_users(&mut self, users: usize, duration: time::Duration) {
+        let now = time::Instant::now();
+        let mut steps = vec![TestPlanStep { users, duration }];
+        // Replace the hold left by an earlier override along with the current step.
+        let end = (self.step + 1 + self.step_held as usize).min(self.test_plan.steps.len());
+        let overridden: time::Duration = self.test_plan.steps[self.step..end]
+            .iter()
+            .map(|step| step.duration)
+            .sum();
+        let remaining = (self.step_started + overridden).saturating_duration_since(now);
+        self.step_held = remaining > duration;
+        if self.step_held {
+            steps.push(TestPlanStep {
+                users,
+                duration: remaining - duration,
+            });
+        }
+        self.test_plan.steps.splice(self.step..end, steps);
+        self.step_started = now;
+        self.step_users = self.user_channels.len();
+    }
+}
+
+/// Commands that can be sent to a running load test with a `GooseController`.
+#[derive(Debug)]
+pub enum GooseControl {
+    /// Stop the specified number of running users. Each user runs its on_stop tasks
+    /// before exiting.
+    StopUsers(usize),
+    /// Change the maximum number of requests per second allowed by the throttle.
+    SetThrottleRequests(usize),
+    /// Pause all running users between tasks, keeping their sessions.
+    Pause,
+    /// Resume all paused users.
+    Resume,
+    /// Launch or stop users at the hatch rate until the specified number of users
+    /// are running.
+    SetUsers(usize),
+    /// Change the per-second rate users are launched and stopped at.
+    SetHatchRate(usize),
+    /// Reset all metrics, and restart the metrics timer.
+    ResetMetrics,
+    /// Reply with a snapshot of the current metrics.
+    Metrics(oneshot::Sender<GooseMetrics>),
+    /// Stop the load test, as if canceled with ctrl-c.
+    Stop,
+}
+
+/// A handle for controlling a running load test from another thread, returned by
+/// `GooseAttack::controller()`.
+#[derive(Clone, Debug)]
+pub struct GooseController {
+    sender: mpsc::UnboundedSender<GooseControl>,
+}
+impl GooseController {
+    /// Stop the most recently launched `users` while the load test keeps running.
+    /// Each stopped user runs its on_stop tasks before exiting. Returns false if
+    /// the load test is no longer running.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    ///     use goose::prelude::*;
+    ///     use std::{thread, time};
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     let goose_attack = GooseAttack::initialize()?
+    ///         .register_taskset(taskset!("ExampleTasks")
+    ///             .register_task(task!(example_task))
+    ///         );
+    ///
+    ///     // Stop 10 users every minute.
+    ///     let controller = goose_attack.controller();
+    ///     thread::spawn(move || loop {
+    ///         thread::sleep(time::Duration::from_secs(60));
+    ///         if !controller.stop_users(10) {
+    ///             break;
+    ///         }
+    ///     });
+    ///
+    ///     goose_attack.execute()?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn example_task(user: &GooseUser) -> GooseTaskResult {
+    ///     let _goose = user.get("/foo").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn stop_users(&self, users: usize) -> bool {
+        self.sender.send(GooseControl::StopUsers(users)).is_ok()
+    }
+
+    /// Change the maximum number of requests per second allowed by the throttle while
+    /// the load test keeps running, replacing the rest of any `--throttle-schedule`.
+    /// The throttle must have been enabled with `--throttle-requests` or
+    /// `--throttle-schedule`. Returns false if the load test is no longer running.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    ///     use goose::prelude::*;
+    ///     use std::{thread, time};
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     let goose_attack = GooseAttack::initialize()?
+    ///         .register_taskset(taskset!("ExampleTasks")
+    ///             .register_task(task!(example_task))
+    ///         );
+    ///
+    ///     // Allow 10 more requests per second every minute.
+    ///     let controller = goose_attack.controller();
+    ///     thread::spawn(move || {
+    ///         let mut requests = 10;
+    ///         loop {
+    ///             thread::sleep(time::Duration::from_secs(60));
+    ///             requests += 10;
+    ///             if !controller.set_throttle_requests(requests) {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     });
+    ///
+    ///     goose_attack.execute()?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn example_task(user: &GooseUser) -> GooseTaskResult {
+    ///     let _goose = user.get("/foo").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_throttle_requests(&self, requests: usize) -> bool {
+        self.sender
+            .send(GooseControl::SetThrottleRequests(requests))
+            .is_ok()
+    }
+
+    /// Pause the load test. Each running user finishes its current task and then
+    /// stops making requests, keeping its session and cookies until resumed. No
+    /// users are launched or stopped while paused, and the paused time isn't
+    /// included in the load test's run time or metrics duration. Returns false if
+    /// the load test is no longer running.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    ///     use goose::prelude::*;
+    ///     use std::{thread, time};
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     let goose_attack = GooseAttack::initialize()?
+    ///         .register_taskset(taskset!("ExampleTasks")
+    ///             .register_task(task!(example_task))
+    ///         );
+    ///
+    ///     // Pause the load test for 30 seconds after it has run for 1 minute.
+    ///     let controller = goose_attack.controller();
+    ///     thread::spawn(move || {
+    ///         thread::sleep(time::Duration::from_secs(60));
+    ///         controller.pause();
+    ///         thread::sleep(time::Duration::from_secs(30));
+    ///         controller.resume();
+    ///     });
+    ///
+    ///     goose_attack.execute()?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn example_task(user: &GooseUser) -> GooseTaskResult {
+    ///     let _goose = user.get("/foo").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn pause(&self) -> bool {
+        self.sender.send(GooseControl::Pause).is_ok()
+    }
+
+    /// Resume a load test paused with `pause()`. Returns false if the load test is
+    /// no longer running.
+    pub fn resume(&self) -> bool {
+        self.sender.send(GooseControl::Resume).is_ok()
+    }
+
+    /// Launch or stop users at the hatch rate until `users` users are running. Any
+    /// remaining test plan continues afterward. Returns false if the load test is no
+    /// longer running.
+    pub fn set_users(&self, users: usize) -> bool {
+        self.sender.send(GooseControl::SetUsers(users)).is_ok()
+    }
+
+    /// Change the per-second rate users are launched and stopped at, including any
+    /// users currently being launched. Returns false if the load test is no longer
+    /// running.
+    pub fn set_hatch_rate(&self, hatch_rate: usize) -> bool {
+        self.sender
+            .send(GooseControl::SetHatchRate(hatch_rate))
+            .is_ok()
+    }
+
+    /// Reset all metrics collected so far, and restart the metrics timer. Returns
+    /// false if the load test is no longer running.
+    pub fn reset_metrics(&self) -> bool {
+        self.sender.send(GooseControl::ResetMetrics).is_ok()
+    }
+
+    /// Stop the load test, as if canceled with ctrl-c. Returns false if the load test
+    /// is no longer running.
+    pub fn stop(&self) -> bool {
+        self.sender.send(GooseControl::Stop).is_ok()
+    }
+}
+
 /// Internal global state for load test.
 #[derive(Clone)]
 pub struct GooseAttack {
@@ -489,10 +860,22 @@ pub struct GooseAttack {
     run_time: usize,
     /// Track total number of users to run for this load test.
     users: usize,
+    /// An optional test plan, replacing the --users, --hatch-rate and --run-time options.
+    test_plan: Option<TestPlan>,
+    /// A channel used by GooseControllers to send commands to the running load test.
+    control_sender: mpsc::UnboundedSender<GooseControl>,
+    /// The receiving end of the control channel, taken when the load test starts.
+    control_receiver: Arc<Mutex<Option<mpsc::UnboundedReceiver<GooseControl>>>>,
     /// When the load test started.
     started: Option<time::Instant>,
     /// All metrics merged together.
     metrics: GooseMetrics,
+    /// Outputs receiving metrics, taken when the load test starts.
+    metrics_sinks: Arc<Mutex<Vec<Box<dyn GooseMetricsSink>>>>,
+    /// Pass/fail thresholds evaluated against the final metrics.
+    thresholds: Vec<GooseThreshold>,
+    /// The index of the `abort_on_fail` threshold that aborted the load test, and why.
+    threshold_aborted: Option<(usize, String)>,
 }
 /// Goose's internal global state.
 impl GooseAttack {
@@ -505,6 +888,7 @@ impl GooseAttack {
     ///     let mut goose_attack = GooseAttack::initialize();
     /// ```
     pub fn initialize() -> Result<GooseAttack, GooseError> {
+        let (control_sender, control_receiver) = mpsc::unbounded_channel();
         let goose_attack = GooseAttack {
             test_start_task: None,
             test_stop_task: None,
@@ -515,6 +899,12 @@ impl GooseAttack {
             number_of_cpus: num_cpus::get(),
             run_time: 0,
             users: 0,
+            test_plan: None,
+            control_sender,
+            control_receiver: Arc::new(Mutex::new(Some(control_receiver))),
+            metrics_sinks: Arc::new(Mutex::new(Vec::new())),
+            thresholds: Vec::new(),
+            threshold_aborted: None,
             started: None,
             metrics: GooseMetrics::default(),
         };
@@ -533,6 +923,7 @@ impl GooseAttack {
     ///     let mut goose_attack = GooseAttack::initialize_with_config(configuration);
     /// ```
     pub fn initialize_with_config(config: GooseConfiguration) -> GooseAttack {
+        let (control_sender, control_receiver) = mpsc::unbounded_channel();
         GooseAttack {
             test_start_task: None,
             test_stop_task: None,
@@ -543,6 +934,12 @@ impl GooseAttack {
             number_of_cpus: num_cpus::get(),
             run_time: 0,
             users: 0,
+            test_plan: None,
+            control_sender,
+            control_receiver: Arc::new(Mutex::new(Some(control_receiver))),
+            metrics_sinks: Arc::new(Mutex::new(Vec::new())),
+            thresholds: Vec::new(),
+            threshold_aborted: None,
             started: None,
             metrics: GooseMetrics::default(),
         }
@@ -650,7 +1047,7 @@ impl GooseAttack {
                 });
             }
 
-            // All of these options must be defined below, search for formatted_log.
+            // All of these options must be defined below, search for JsonMetricsSink.
             let options = vec!["json", "csv", "raw"];
             if !options.contains(&self.configuration.metrics_format.as_str()) {
                 return Err(GooseError::InvalidOption {
@@ -733,7 +1130,10 @@ impl GooseAttack {
             }
             None => {
                 let u = self.number_of_cpus;
-                if !self.configuration.manager && !self.configuration.worker {
+                if !self.configuration.manager
+                    && !self.configuration.worker
+                    && self.configuration.test_plan.is_empty()
+                {
                     info!("concurrent users defaulted to {} (number of CPUs)", u);
                 }
                 u
@@ -869,6 +1269,109 @@ impl GooseAttack {
         self
     }
 
+    /// Optionally define a test plan, a sequence of steps each made up of a number of
+    /// users and a timespan. During each step, Goose linearly launches or stops users
+    /// until the number of running users matches the step. The load test ends when
+    /// the last step completes. A test plan replaces the `--users`, `--hatch-rate`
+    /// and `--run-time` options, and is overridden by the `--test-plan` option.
+    ///
+    /// For example, the following test plan launches 10 users over 30 seconds, then
+    /// increases to 100 users over the next 5 minutes, holds 100 users for 10
+    /// minutes, and then stops all users over the final 30 seconds.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    ///     use goose::prelude::*;
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     GooseAttack::initialize()?
+    ///         .set_test_plan("10,30s;100,5m;100,10m;0,30s")?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_test_plan(mut self, test_plan: &str) -> Result<Self, GooseError> {
+        trace!("set_test_plan: {}", test_plan);
+        self.test_plan = Some(TestPlan::from_str(test_plan)?);
+        Ok(self)
+    }
+
+    /// Optionally configure which percentiles of response times are displayed when the
+    /// load test finishes, as a comma separated list of percentiles from 0 to 100. By
+    /// default Goose displays the 50th, 75th, 98th, 99th, 99.9th and 99.99th
+    /// percentiles. Overridden by the `--percentiles` option.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    ///     use goose::prelude::*;
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     GooseAttack::initialize()?
+    ///         .set_percentiles("50,90,95,99,99.9")?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_percentiles(mut self, percentiles: &str) -> Result<Self, GooseError> {
+        trace!("set_percentiles: {}", percentiles);
+        self.metrics.percentiles = metrics::parse_percentiles(percentiles)?;
+        Ok(self)
+    }
+
+    /// Optionally set a pass/fail threshold, evaluated against the final metrics when
+    /// the load test finishes. If any threshold fails, `execute()` prints the metrics
+    /// and returns `GooseError::ThresholdsFailed`, so a load test returning its result
+    /// from `main()` exits with a non-zero status. Thresholds set with the `--threshold`
+    /// option are added to these. See the `threshold` module for the available metrics.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    ///     use goose::prelude::*;
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     GooseAttack::initialize()?
+    ///         .set_threshold(r#"p95("GET /") < 300ms"#)?
+    ///         .set_threshold("error_rate < 1%")?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_threshold(mut self, threshold: &str) -> Result<Self, GooseError> {
+        trace!("set_threshold: {}", threshold);
+        self.thresholds.push(GooseThreshold::from_str(threshold)?);
+        Ok(self)
+    }
+
+    /// Optionally register a `GooseMetricsSink`, which receives each request and task
+    /// metric, periodic snapshots and the final metrics. Any number of sinks can be
+    /// registered. See the `sink` module for an example.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    ///     use goose::prelude::*;
+    ///     use goose::sink::CsvMetricsSink;
+    ///
+    /// fn main() -> Result<(), GooseError> {
+    ///     GooseAttack::initialize()?
+    ///         .register_metrics_sink(CsvMetricsSink::create("requests.csv")?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn register_metrics_sink<T: GooseMetricsSink + 'static>(self, sink: T) -> Self {
+        self.metrics_sinks.lock().unwrap().push(Box::new(sink));
+        self
+    }
+
+    /// Returns a `GooseController`, which can be moved to another thread and used
+    /// to control the load test while it is running. See `GooseController` for an
+    /// example.
+    pub fn controller(&self) -> GooseController {
+        GooseController {
+            sender: self.control_sender.clone(),
+        }
+    }
+
     /// Allocate a vector of weighted GooseUser.
     fn weight_task_set_users(&mut self) -> Result<Vec<GooseUser>, GooseError> {
         trace!("weight_task_set_users");
@@ -918,8 +1421,6 @@ impl GooseAttack {
                 weighted_users.push(GooseUser::new(
                     self.task_sets[*task_sets_index].task_sets_index,
                     base_url,
-                    self.task_sets[*task_sets_index].min_wait,
-                    self.task_sets[*task_sets_index].max_wait,
                     &self.configuration,
                     self.metrics.hash,
                 )?);
@@ -981,6 +1482,9 @@ impl GooseAttack {
             std::process::exit(0);
         }
 
+        // A test plan replaces the --users, --hatch-rate and --run-time options.
+        self.validate_test_plan()?;
+
         // Manager mode.
         if self.configuration.manager {
             // @TODO: support running in both manager and worker mode.
@@ -1025,28 +1529,27 @@ impl GooseAttack {
                     detail: "The --throttle-requests option can not be set together with the --manager flag.".to_string(),
                 });
             }
-        }
 
-        // Validate throttle_requests, which must be a value from 1 to 1,000,000.
-        match self.configuration.throttle_requests {
-            Some(throttle) if throttle == 0 => {
+            if !self.configuration.throttle_schedule.is_empty() {
                 return Err(GooseError::InvalidOption {
-                    option: "--throttle-requests".to_string(),
-                    value: throttle.to_string(),
-                    detail: "The --throttle-requests option must be set to at least 1 request per second.".to_string(),
-                });
-            }
-            Some(throttle) if throttle > 1_000_000 => {
-                return Err(GooseError::InvalidOption {
-                    option: "--throttle-requests".to_string(),
-                    value: throttle.to_string(),
-                    detail: "The --throttle-requests option can not be set to more than 1,000,000 requests per second.".to_string(),
+                    option: "--throttle-schedule".to_string(),
+                    value: self.configuration.throttle_schedule,
+                    detail: "The --throttle-schedule option can not be set together with the --manager flag.".to_string(),
                 });
             }
-            // Everything else is valid.
-            _ => (),
         }
 
+        // Validate optional features, each of which may conflict with other options.
+        self.validate_throttle()?;
+        self.validate_arrival_rate()?;
+        self.validate_threads()?;
+        self.validate_metrics()?;
+        self.validate_history()?;
+        self.validate_outputs()?;
+        self.validate_thresholds()?;
+        self.validate_controllers()?;
+        self.validate_iterations()?;
+
         // Worker mode.
         if self.configuration.worker {
             // @TODO: support running in both manager and worker mode.
@@ -1132,6 +1635,54 @@ impl GooseAttack {
                 });
             }
 
+            if let Some(histogram_digits) = self.configuration.histogram_digits {
+                return Err(GooseError::InvalidOption {
+                    option: "--histogram-digits".to_string(),
+                    value: histogram_digits.to_string(),
+                    detail: "The --histogram-digits option can not be set together with the --worker flag.".to_string(),
+                });
+            }
+
+            if !self.configuration.percentiles.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--percentiles".to_string(),
+                    value: self.configuration.percentiles,
+                    detail:
+                        "The --percentiles option can not be set together with the --worker flag."
+                            .to_string(),
+                });
+            }
+
+            if !self.configuration.report_file.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--report-file".to_string(),
+                    value: self.configuration.report_file,
+                    detail:
+                        "The --report-file option can not be set together with the --worker flag."
+                            .to_string(),
+                });
+            }
+
+            if !self.configuration.summary_file.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--summary-file".to_string(),
+                    value: self.configuration.summary_file,
+                    detail:
+                        "The --summary-file option can not be set together with the --worker flag."
+                            .to_string(),
+                });
+            }
+
+            if !self.configuration.threshold.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--threshold".to_string(),
+                    value: self.configuration.threshold.join(", "),
+                    detail:
+                        "The --threshold option can not be set together with the --worker flag."
+                            .to_string(),
+                });
+            }
+
             if self.configuration.no_reset_metrics {
                 return Err(GooseError::InvalidOption {
                     option: "--no-reset-metrics".to_string(),
@@ -1170,22 +1721,28 @@ impl GooseAttack {
         }
 
         // Configure number of user threads to launch per second, defaults to 1.
-        if self.configuration.hatch_rate == 0 {
-            return Err(GooseError::InvalidOption {
-                option: "--hatch-rate".to_string(),
-                value: self.configuration.hatch_rate.to_string(),
-                detail: "The --hatch-rate option must be set to at least 1.".to_string(),
-            });
-        }
-        if self.configuration.hatch_rate > 1 && self.configuration.worker {
-            return Err(GooseError::InvalidOption {
-                option: "--hatch-rate".to_string(),
-                value: self.configuration.hatch_rate.to_string(),
-                detail: "The --hatch-rate option can not be set together with the --worker flag."
-                    .to_string(),
-            });
+        if let Some(hatch_rate) = self.configuration.hatch_rate {
+            if hatch_rate == 0 {
+                return Err(GooseError::InvalidOption {
+                    option: "--hatch-rate".to_string(),
+                    value: hatch_rate.to_string(),
+                    detail: "The --hatch-rate option must be set to at least 1.".to_string(),
+                });
+            }
+            if self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--hatch-rate".to_string(),
+                    value: hatch_rate.to_string(),
+                    detail:
+                        "The --hatch-rate option can not be set together with the --worker flag."
+                            .to_string(),
+                });
+            }
         }
-        debug!("hatch_rate = {}", self.configuration.hatch_rate);
+        debug!("hatch_rate = {}", self.hatch_rate());
+
+        // Optionally stop users at ramp_down_rate per second when the load test ends.
+        self.validate_ramp_down()?;
 
         // Confirm there's either a global host, or each task set has a host defined.
         if self.configuration.host.is_empty() {
@@ -1253,14 +1810,24 @@ impl GooseAttack {
         // Our load test is officially starting.
         self.started = Some(time::Instant::now());
         // Hatch users at hatch_rate per second, or one every 1 / hatch_rate fraction of a second.
-        let sleep_float = 1.0 / self.configuration.hatch_rate as f32;
+        let sleep_float = 1.0 / self.hatch_rate() as f32;
         let sleep_duration = time::Duration::from_secs_f32(sleep_float);
+        // Without a test plan, launch all users at hatch_rate and then run for run_time.
+        let test_plan = match &self.test_plan {
+            Some(t) => t.clone(),
+            None => TestPlan::from_hatch_interval(
+                self.users,
+                sleep_duration,
+                self.run_time,
+                self.configuration.no_reset_metrics,
+            ),
+        };
 
         // Start goose in manager mode.
         if self.configuration.manager {
             #[cfg(feature = "gaggle")]
             {
-                let mut rt = tokio::runtime::Runtime::new().unwrap();
+                let mut rt = self.build_runtime()?;
                 self = rt.block_on(manager::manager_main(self));
             }
 
@@ -1275,7 +1842,7 @@ impl GooseAttack {
         else if self.configuration.worker {
             #[cfg(feature = "gaggle")]
             {
-                let mut rt = tokio::runtime::Runtime::new().unwrap();
+                let mut rt = self.build_runtime()?;
                 self = rt.block_on(worker::worker_main(&self));
             }
 
@@ -1289,74 +1856,755 @@ impl GooseAttack {
         }
         // Start goose in single-process mode.
         else {
-            let mut rt = tokio::runtime::Runtime::new().unwrap();
-            self = rt.block_on(self.launch_users(sleep_duration, None))?;
+            let mut rt = self.build_runtime()?;
+            self = rt.block_on(self.launch_users(test_plan, None))?;
         }
 
-        Ok(self.metrics)
-    }
+        // Evaluate thresholds against the final metrics. Workers inherit thresholds from
+        // the Manager, which evaluates them for the entire Gaggle.
+        if !self.thresholds.is_empty() && !self.configuration.worker {
+            let mut verdict = GooseThresholdVerdict::evaluate(&self.thresholds, &self.metrics);
+            if let Some((index, reason)) = self.threshold_aborted.as_ref() {
+                verdict.abort(*index, reason);
+            }
+            info!(
+                "thresholds {}",
+                if verdict.passed { "passed" } else { "failed" }
+            );
+            self.metrics.thresholds = Some(verdict);
+        }
 
-    /// Helper to wrap configured host in Option<> if set.
-    fn get_configuration_host(&self) -> Option<String> {
-        if self.configuration.host.is_empty() {
-            None
-        } else {
-            Some(self.configuration.host.to_string())
+        // Optionally write the final metrics to an HTML report. Workers inherit the
+        // option from the Manager, which writes the report for the entire Gaggle.
+        if !self.configuration.report_file.is_empty() && !self.configuration.worker {
+            info!("writing HTML report to {}", &self.configuration.report_file);
+            std::fs::write(
+                &self.configuration.report_file,
+                report::html_report(&self.metrics, &self.configuration),
+            )?;
         }
-    }
 
-    /// Helper to create CSV-formatted logs.
-    fn prepare_csv(raw_request: &GooseRawRequest, header: &mut bool) -> String {
-        let body = format!(
-            // Put quotes around name, url and final_url as they are strings.
-            "{},{:?},\"{}\",\"{}\",\"{}\",{},{},{},{},{},{}",
-            raw_request.elapsed,
-            raw_request.method,
-            raw_request.name,
-            raw_request.url,
-            raw_request.final_url,
-            raw_request.redirected,
-            raw_request.response_time,
-            raw_request.status_code,
-            raw_request.success,
-            raw_request.update,
-            raw_request.user
-        );
-        // Concatenate the header before the body one time.
-        if *header {
-            *header = false;
-            format!(
-                // No quotes needed in header.
-                "{},{},{},{},{},{},{},{},{},{},{}\n",
-                "elapsed",
-                "method",
-                "name",
-                "url",
-                "final_url",
-                "redirected",
-                "response_time",
-                "status_code",
-                "success",
-                "update",
-                "user"
-            ) + &body
-        } else {
-            body
+        // Optionally write a summary of the final metrics to a file.
+        if !self.configuration.summary_file.is_empty() && !self.configuration.worker {
+            info!(
+                "writing metrics summary to {}",
+                &self.configuration.summary_file
+            );
+            let summary = match self.configuration.summary_format.as_str() {
+                "csv" => self.metrics.summary_csv(),
+                _ => self.metrics.summary_json(),
+            };
+            std::fs::write(&self.configuration.summary_file, summary)?;
         }
-    }
 
-    // Helper to spawn a logger thread if configured.
-    fn setup_logger(
-        &self,
-    ) -> (
-        // A handle to later rejoin the logger thread.
-        Option<tokio::task::JoinHandle<()>>,
-        // A channel used by GooseClients to send logs.
-        Option<mpsc::UnboundedSender<Option<GooseDebug>>>,
-    ) {
-        // If the logger isn't configured, return immediately.
-        if self.configuration.debug_file.is_empty() {
-            return (None, None);
+        // If any threshold failed, return the verdict as an error. The metrics aren't
+        // returned, so print them first.
+        if let Some(verdict) = self.metrics.thresholds.as_ref() {
+            if !verdict.passed {
+                let verdict = verdict.clone();
+                self.metrics.print();
+                return Err(GooseError::ThresholdsFailed { verdict });
+            }
+        }
+
+        Ok(self.metrics)
+    }
+
+    /// Validate the optional test plan, which replaces the --users, --hatch-rate and
+    /// --run-time options.
+    fn validate_test_plan(&mut self) -> Result<(), GooseError> {
+        if !self.configuration.test_plan.is_empty() {
+            self.test_plan = Some(TestPlan::from_str(&self.configuration.test_plan)?);
+        }
+        if let Some(test_plan) = &self.test_plan {
+            // @TODO: support test plans in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--test-plan".to_string(),
+                    value: self.configuration.test_plan.clone(),
+                    detail: "The --test-plan option can not be set together with the --manager or --worker flags.".to_string(),
+                });
+            }
+
+            if let Some(users) = self.configuration.users {
+                return Err(GooseError::InvalidOption {
+                    option: "--users".to_string(),
+                    value: users.to_string(),
+                    detail:
+                        "The --users option can not be set together with the --test-plan option."
+                            .to_string(),
+                });
+            }
+
+            if let Some(hatch_rate) = self.configuration.hatch_rate {
+                return Err(GooseError::InvalidOption {
+                    option: "--hatch-rate".to_string(),
+                    value: hatch_rate.to_string(),
+                    detail:
+                        "The --hatch-rate option can not be set together with the --test-plan option."
+                            .to_string(),
+                });
+            }
+
+            if !self.configuration.run_time.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--run-time".to_string(),
+                    value: self.configuration.run_time.clone(),
+                    detail:
+                        "The --run-time option can not be set together with the --test-plan option."
+                            .to_string(),
+                });
+            }
+
+            // Allocate enough users for the busiest step of the test plan.
+            self.users = test_plan.max_users();
+            debug!("test_plan = {:?}", test_plan);
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional throttle, schedule and burst.
+    fn validate_throttle(&self) -> Result<(), GooseError> {
+        // Validate throttle_requests, which must be a value from 1 to 1,000,000.
+        match self.configuration.throttle_requests {
+            Some(throttle) if throttle == 0 => {
+                return Err(GooseError::InvalidOption {
+                    option: "--throttle-requests".to_string(),
+                    value: throttle.to_string(),
+                    detail: "The --throttle-requests option must be set to at least 1 request per second.".to_string(),
+                });
+            }
+            Some(throttle) if throttle > throttle::MAX_THROTTLE_REQUESTS => {
+                return Err(GooseError::InvalidOption {
+                    option: "--throttle-requests".to_string(),
+                    value: throttle.to_string(),
+                    detail: "The --throttle-requests option can not be set to more than 1,000,000 requests per second.".to_string(),
+                });
+            }
+            // Everything else is valid.
+            _ => (),
+        }
+
+        // A throttle schedule replaces the --throttle-requests option.
+        if !self.configuration.throttle_schedule.is_empty() {
+            if let Some(throttle) = self.configuration.throttle_requests {
+                return Err(GooseError::InvalidOption {
+                    option: "--throttle-requests".to_string(),
+                    value: throttle.to_string(),
+                    detail: "The --throttle-requests option can not be set together with the --throttle-schedule option.".to_string(),
+                });
+            }
+            let schedule =
+                throttle::parse_throttle_schedule(&self.configuration.throttle_schedule)?;
+            debug!("throttle_schedule = {:?}", schedule);
+        }
+
+        // Optionally allow bursts of throttled requests.
+        if let Some(burst) = self.configuration.throttle_burst {
+            if burst == 0 {
+                return Err(GooseError::InvalidOption {
+                    option: "--throttle-burst".to_string(),
+                    value: burst.to_string(),
+                    detail: "The --throttle-burst option must be set to at least 1 request."
+                        .to_string(),
+                });
+            }
+            if self.configuration.throttle_requests.is_none()
+                && self.configuration.throttle_schedule.is_empty()
+            {
+                return Err(GooseError::InvalidOption {
+                    option: "--throttle-burst".to_string(),
+                    value: burst.to_string(),
+                    detail: "The --throttle-burst option can not be set without also setting the --throttle-requests or --throttle-schedule option.".to_string(),
+                });
+            }
+            debug!("throttle_burst = {}", burst);
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional arrival rate.
+    fn validate_arrival_rate(&self) -> Result<(), GooseError> {
+        // Optionally start task set iterations at arrival_rate per second.
+        if let Some(arrival_rate) = self.configuration.arrival_rate {
+            if arrival_rate == 0 {
+                return Err(GooseError::InvalidOption {
+                    option: "--arrival-rate".to_string(),
+                    value: arrival_rate.to_string(),
+                    detail:
+                        "The --arrival-rate option must be set to at least 1 iteration per second."
+                            .to_string(),
+                });
+            }
+            // @TODO: support arrival rates in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--arrival-rate".to_string(),
+                    value: arrival_rate.to_string(),
+                    detail: "The --arrival-rate option can not be set together with the --manager or --worker flags.".to_string(),
+                });
+            }
+            debug!("arrival_rate = {}", arrival_rate);
+        }
+
+        Ok(())
+    }
+
+    /// Validate how many threads run GooseUsers.
+    fn validate_threads(&self) -> Result<(), GooseError> {
+        // Optionally configure how many threads run GooseUsers.
+        if let Some(threads) = self.configuration.threads {
+            if threads == 0 {
+                return Err(GooseError::InvalidOption {
+                    option: "--threads".to_string(),
+                    value: threads.to_string(),
+                    detail: "The --threads option must be set to at least 1.".to_string(),
+                });
+            }
+            debug!("threads = {}", threads);
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional percentiles and HDR histograms.
+    fn validate_metrics(&mut self) -> Result<(), GooseError> {
+        // Optionally configure which percentiles are displayed.
+        if !self.configuration.percentiles.is_empty() {
+            self.metrics.percentiles = metrics::parse_percentiles(&self.configuration.percentiles)?;
+            debug!("percentiles = {:?}", self.metrics.percentiles);
+        }
+
+        // Optionally record exact times in HDR histograms.
+        if let Some(histogram_digits) = self.configuration.histogram_digits {
+            if histogram_digits == 0 || histogram_digits > 5 {
+                return Err(GooseError::InvalidOption {
+                    option: "--histogram-digits".to_string(),
+                    value: histogram_digits.to_string(),
+                    detail: "The --histogram-digits option must be set to a value between 1 and 5."
+                        .to_string(),
+                });
+            }
+            if self.configuration.no_metrics {
+                return Err(GooseError::InvalidOption {
+                    option: "--histogram-digits".to_string(),
+                    value: histogram_digits.to_string(),
+                    detail: "The --histogram-digits option can not be set together with the --no-metrics flag.".to_string(),
+                });
+            }
+            debug!("histogram_digits = {}", histogram_digits);
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional metrics history.
+    fn validate_history(&self) -> Result<(), GooseError> {
+        // Optionally record a history of metrics at a regular interval.
+        if !self.configuration.history_interval.is_empty() {
+            if util::parse_timespan(&self.configuration.history_interval) == 0 {
+                return Err(GooseError::InvalidOption {
+                    option: "--history-interval".to_string(),
+                    value: self.configuration.history_interval.clone(),
+                    detail: "The --history-interval option must be set to at least 1 second."
+                        .to_string(),
+                });
+            }
+            if self.configuration.no_metrics {
+                return Err(GooseError::InvalidOption {
+                    option: "--history-interval".to_string(),
+                    value: self.configuration.history_interval.clone(),
+                    detail: "The --history-interval option can not be set together with the --no-metrics flag.".to_string(),
+                });
+            }
+            // @TODO: support metrics history in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--history-interval".to_string(),
+                    value: self.configuration.history_interval.clone(),
+                    detail: "The --history-interval option can not be set together with the --manager or --worker flags.".to_string(),
+                });
+            }
+            debug!("history_interval = {}", self.configuration.history_interval);
+        }
+        if !self.configuration.history_file.is_empty()
+            && self.configuration.history_interval.is_empty()
+        {
+            return Err(GooseError::InvalidOption {
+                option: "--history-file".to_string(),
+                value: self.configuration.history_file.clone(),
+                detail: "The --history-interval option must be set together with the --history-file option.".to_string(),
+            });
+        }
+        if self.configuration.history_format != "json" {
+            // History format isn't relevant if the history isn't written to a file.
+            if self.configuration.history_file.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--history-format".to_string(),
+                    value: self.configuration.history_format.clone(),
+                    detail: "The --history-file option must be set together with the --history-format option.".to_string(),
+                });
+            }
+
+            // All of these options must be defined below, search for history_csv.
+            let options = ["json", "csv"];
+            if !options.contains(&self.configuration.history_format.as_str()) {
+                return Err(GooseError::InvalidOption {
+                    option: "--history-format".to_string(),
+                    value: self.configuration.history_format.clone(),
+                    detail: format!(
+                        "The --history-format option must be set to one of: {}.",
+                        options.join(", ")
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional outputs the final or running metrics are written to.
+    fn validate_outputs(&self) -> Result<(), GooseError> {
+        // Optionally write an HTML report when the load test finishes.
+        if !self.configuration.report_file.is_empty() {
+            if self.configuration.no_metrics {
+                return Err(GooseError::InvalidOption {
+                    option: "--report-file".to_string(),
+                    value: self.configuration.report_file.clone(),
+                    detail: "The --report-file option can not be set together with the --no-metrics flag.".to_string(),
+                });
+            }
+            debug!("report_file = {}", self.configuration.report_file);
+        }
+
+        // Optionally write a summary of the final metrics when the load test finishes.
+        if !self.configuration.summary_file.is_empty() && self.configuration.no_metrics {
+            return Err(GooseError::InvalidOption {
+                option: "--summary-file".to_string(),
+                value: self.configuration.summary_file.clone(),
+                detail:
+                    "The --summary-file option can not be set together with the --no-metrics flag."
+                        .to_string(),
+            });
+        }
+        if self.configuration.summary_format != "json" {
+            // Summary format isn't relevant if the summary isn't written to a file.
+            if self.configuration.summary_file.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--summary-format".to_string(),
+                    value: self.configuration.summary_format.clone(),
+                    detail: "The --summary-file option must be set together with the --summary-format option.".to_string(),
+                });
+            }
+
+            // All of these options must be defined below, search for summary_csv.
+            let options = ["json", "csv"];
+            if !options.contains(&self.configuration.summary_format.as_str()) {
+                return Err(GooseError::InvalidOption {
+                    option: "--summary-format".to_string(),
+                    value: self.configuration.summary_format.clone(),
+                    detail: format!(
+                        "The --summary-format option must be set to one of: {}.",
+                        options.join(", ")
+                    ),
+                });
+            }
+        }
+
+        // Optionally write metrics as InfluxDB line protocol.
+        for (option, value) in &[
+            ("--influxdb-file", &self.configuration.influxdb_file),
+            ("--influxdb-url", &self.configuration.influxdb_url),
+        ] {
+            if value.is_empty() {
+                continue;
+            }
+            if self.configuration.no_metrics {
+                return Err(GooseError::InvalidOption {
+                    option: option.to_string(),
+                    value: value.to_string(),
+                    detail: format!(
+                        "The {} option can not be set together with the --no-metrics flag.",
+                        option
+                    ),
+                });
+            }
+            // @TODO: support InfluxDB line protocol in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: option.to_string(),
+                    value: value.to_string(),
+                    detail: format!(
+                        "The {} option can not be set together with the --manager or --worker flags.",
+                        option
+                    ),
+                });
+            }
+            debug!("{} = {}", option, value);
+        }
+        if !self.configuration.influxdb_url.is_empty() {
+            if let Err(e) = Url::parse(&self.configuration.influxdb_url) {
+                return Err(GooseError::InvalidOption {
+                    option: "--influxdb-url".to_string(),
+                    value: self.configuration.influxdb_url.clone(),
+                    detail: format!("The --influxdb-url option must be a valid URL: {}.", e),
+                });
+            }
+        }
+
+        // Optionally serve metrics to Prometheus.
+        if let Some(address) = self.prometheus_address() {
+            if self.configuration.no_metrics {
+                return Err(GooseError::InvalidOption {
+                    option: "--prometheus-host".to_string(),
+                    value: address,
+                    detail: "The --prometheus-host and --prometheus-port options can not be set together with the --no-metrics flag.".to_string(),
+                });
+            }
+            // @TODO: support Prometheus in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--prometheus-host".to_string(),
+                    value: address,
+                    detail: "The --prometheus-host and --prometheus-port options can not be set together with the --manager or --worker flags.".to_string(),
+                });
+            }
+            debug!("prometheus = {}", address);
+        }
+
+        // Optionally stream metrics to StatsD or Graphite.
+        if let Some((option, address)) = self.stream_address() {
+            if !self.configuration.statsd_address.is_empty()
+                && !self.configuration.graphite_address.is_empty()
+            {
+                return Err(GooseError::InvalidOption {
+                    option: "--graphite-address".to_string(),
+                    value: address,
+                    detail: "The --graphite-address option can not be set together with the --statsd-address option.".to_string(),
+                });
+            }
+            if self.configuration.no_metrics {
+                return Err(GooseError::InvalidOption {
+                    option: option.to_string(),
+                    value: address,
+                    detail: format!(
+                        "The {} option can not be set together with the --no-metrics flag.",
+                        option
+                    ),
+                });
+            }
+            // @TODO: support streaming metrics in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: option.to_string(),
+                    value: address,
+                    detail: format!(
+                        "The {} option can not be set together with the --manager or --worker flags.",
+                        option
+                    ),
+                });
+            }
+            if !self.configuration.stream_interval.is_empty()
+                && util::parse_timespan(&self.configuration.stream_interval) == 0
+            {
+                return Err(GooseError::InvalidOption {
+                    option: "--stream-interval".to_string(),
+                    value: self.configuration.stream_interval.clone(),
+                    detail: "The --stream-interval option must be set to at least 1 second."
+                        .to_string(),
+                });
+            }
+            debug!("{} = {}", option, address);
+        } else {
+            if !self.configuration.stream_prefix.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--stream-prefix".to_string(),
+                    value: self.configuration.stream_prefix.clone(),
+                    detail: "The --stream-prefix option requires the --statsd-address or --graphite-address option.".to_string(),
+                });
+            }
+            if !self.configuration.stream_interval.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "--stream-interval".to_string(),
+                    value: self.configuration.stream_interval.clone(),
+                    detail: "The --stream-interval option requires the --statsd-address or --graphite-address option.".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate and parse the optional --threshold options.
+    fn validate_thresholds(&mut self) -> Result<(), GooseError> {
+        // Optionally fail the load test if thresholds aren't met.
+        if !self.configuration.threshold.is_empty() {
+            if self.configuration.no_metrics {
+                return Err(GooseError::InvalidOption {
+                    option: "--threshold".to_string(),
+                    value: self.configuration.threshold.join(", "),
+                    detail:
+                        "The --threshold option can not be set together with the --no-metrics flag."
+                            .to_string(),
+                });
+            }
+            for threshold in &self.configuration.threshold {
+                self.thresholds.push(GooseThreshold::from_str(threshold)?);
+            }
+            debug!("threshold = {:?}", self.configuration.threshold);
+        }
+
+        // @TODO: evaluate abort_on_fail thresholds in the Manager.
+        if self.configuration.manager {
+            if let Some(threshold) = self.thresholds.iter().find(|t| t.abort_on_fail) {
+                return Err(GooseError::InvalidOption {
+                    option: "--threshold".to_string(),
+                    value: threshold.threshold.clone(),
+                    detail: "The --threshold option abort_on_fail can not be set together with the --manager flag.".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional controllers and web UI.
+    fn validate_controllers(&self) -> Result<(), GooseError> {
+        // Optionally enable the controller.
+        if let Some(address) = self.controller_address() {
+            // @TODO: support the controller in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--controller-host".to_string(),
+                    value: address,
+                    detail: "The --controller-host and --controller-port options can not be set together with the --manager or --worker flags.".to_string(),
+                });
+            }
+            debug!("controller = {}", address);
+        }
+
+        // Optionally enable the WebSocket controller.
+        if let Some(address) = self.websocket_address() {
+            // @TODO: support the WebSocket controller in gaggle mode.
+            if self.configuration.manager || self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--websocket-host".to_string(),
+                    value: address,
+                    detail: "The --websocket-host and --websocket-port options can not be set together with the --manager or --worker flags.".to_string(),
+                });
+            }
+            debug!("websocket = {}", address);
+        }
+
+        // Optionally enable the web UI.
+        if let Some(address) = self.webui_address() {
+            #[cfg(not(feature = "webui"))]
+            {
+                return Err(GooseError::FeatureNotEnabled {
+                    feature: "webui".to_string(),
+                    detail: format!("Load test must be recompiled with `--features webui` to serve the web UI on {}.", address),
+                });
+            }
+            // @TODO: support the web UI in gaggle mode.
+            #[cfg(feature = "webui")]
+            {
+                if self.configuration.manager || self.configuration.worker {
+                    return Err(GooseError::InvalidOption {
+                        option: "--webui-host".to_string(),
+                        value: address,
+                        detail: "The --webui-host and --webui-port options can not be set together with the --manager or --worker flags.".to_string(),
+                    });
+                }
+                debug!("webui = {}", address);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional --iterations and --max-requests options.
+    fn validate_iterations(&self) -> Result<(), GooseError> {
+        // Optionally stop after running all tasks a number of times, or making a number
+        // of requests.
+        for (option, value) in &[
+            ("--iterations", self.configuration.iterations),
+            ("--max-requests", self.configuration.max_requests),
+        ] {
+            if let Some(value) = value {
+                if *value == 0 {
+                    return Err(GooseError::InvalidOption {
+                        option: option.to_string(),
+                        value: value.to_string(),
+                        detail: format!("The {} option must be set to at least 1.", option),
+                    });
+                }
+                // @TODO: support iterations and max requests in gaggle mode.
+                if self.configuration.manager || self.configuration.worker {
+                    return Err(GooseError::InvalidOption {
+                        option: option.to_string(),
+                        value: value.to_string(),
+                        detail: format!(
+                            "The {} option can not be set together with the --manager or --worker flags.",
+                            option
+                        ),
+                    });
+                }
+            }
+        }
+        if let Some(iterations) = self.configuration.iterations {
+            if self.test_plan.is_some() {
+                return Err(GooseError::InvalidOption {
+                    option: "--iterations".to_string(),
+                    value: iterations.to_string(),
+                    detail: "The --iterations option can not be set together with the --test-plan option.".to_string(),
+                });
+            }
+            if self.configuration.arrival_rate.is_some() {
+                return Err(GooseError::InvalidOption {
+                    option: "--iterations".to_string(),
+                    value: iterations.to_string(),
+                    detail: "The --iterations option can not be set together with the --arrival-rate option.".to_string(),
+                });
+            }
+            debug!("iterations = {}", iterations);
+        }
+
+        Ok(())
+    }
+
+    /// Validate the optional ramp down rate.
+    fn validate_ramp_down(&self) -> Result<(), GooseError> {
+        if let Some(ramp_down_rate) = self.configuration.ramp_down_rate {
+            if ramp_down_rate == 0 {
+                return Err(GooseError::InvalidOption {
+                    option: "--ramp-down-rate".to_string(),
+                    value: ramp_down_rate.to_string(),
+                    detail: "The --ramp-down-rate option must be set to at least 1.".to_string(),
+                });
+            }
+            if self.configuration.worker {
+                return Err(GooseError::InvalidOption {
+                    option: "--ramp-down-rate".to_string(),
+                    value: ramp_down_rate.to_string(),
+                    detail: "The --ramp-down-rate option can not be set together with the --worker flag."
+                        .to_string(),
+                });
+            }
+            debug!("ramp_down_rate = {}", ramp_down_rate);
+        }
+
+        Ok(())
+    }
+
+    /// Build the runtime the load test runs on: a multi-threaded runtime with one worker
+    /// thread per CPU core unless configured with --threads, or with --threads 1 a
+    /// single-threaded runtime like earlier versions of Goose used.
+    fn build_runtime(&self) -> Result<tokio::runtime::Runtime, GooseError> {
+        let mut builder = tokio::runtime::Builder::new();
+        builder.enable_all();
+        match self.configuration.threads {
+            // Run everything on the thread that started the load test.
+            Some(1) => builder.basic_scheduler(),
+            Some(threads) => builder.threaded_scheduler().core_threads(threads),
+            None => builder.threaded_scheduler(),
+        };
+        Ok(builder.build()?)
+    }
+
+    /// The address the TCP controller listens on, if enabled with --controller-host
+    /// or --controller-port.
+    fn controller_address(&self) -> Option<String> {
+        util::listen_address(
+            &self.configuration.controller_host,
+            self.configuration.controller_port,
+            DEFAULT_CONTROLLER_HOST,
+            DEFAULT_CONTROLLER_PORT,
+        )
+    }
+
+    /// The address the WebSocket controller listens on, if enabled with
+    /// --websocket-host or --websocket-port, or required by the web UI.
+    fn websocket_address(&self) -> Option<String> {
+        // The web UI connects to the WebSocket on the host it was loaded from.
+        let default_host = if self.configuration.webui_host.is_empty() {
+            DEFAULT_CONTROLLER_HOST
+        } else {
+            &self.configuration.webui_host
+        };
+        util::listen_address(
+            &self.configuration.websocket_host,
+            self.configuration.websocket_port,
+            default_host,
+            DEFAULT_WEBSOCKET_PORT,
+        )
+        .or_else(|| {
+            self.webui_address()
+                .map(|_| format!("{}:{}", default_host, DEFAULT_WEBSOCKET_PORT))
+        })
+    }
+
+    /// The address the web UI listens on, if enabled with --webui-host or --webui-port.
+    fn webui_address(&self) -> Option<String> {
+        util::listen_address(
+            &self.configuration.webui_host,
+            self.configuration.webui_port,
+            DEFAULT_CONTROLLER_HOST,
+            DEFAULT_WEBUI_PORT,
+        )
+    }
+
+    /// The address the Prometheus endpoint listens on, if enabled with --prometheus-host
+    /// or --prometheus-port.
+    fn prometheus_address(&self) -> Option<String> {
+        util::listen_address(
+            &self.configuration.prometheus_host,
+            self.configuration.prometheus_port,
+            DEFAULT_CONTROLLER_HOST,
+            DEFAULT_PROMETHEUS_PORT,
+        )
+    }
+
+    /// The option and address metrics are streamed to, if enabled with --statsd-address
+    /// or --graphite-address.
+    fn stream_address(&self) -> Option<(&'static str, String)> {
+        if !self.configuration.statsd_address.is_empty() {
+            Some((
+                "--statsd-address",
+                self.configuration.statsd_address.to_string(),
+            ))
+        } else if !self.configuration.graphite_address.is_empty() {
+            Some((
+                "--graphite-address",
+                self.configuration.graphite_address.to_string(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Helper to wrap configured host in Option<> if set.
+    fn get_configuration_host(&self) -> Option<String> {
+        if self.configuration.host.is_empty() {
+            None
+        } else {
+            Some(self.configuration.host.to_string())
+        }
+    }
+
+    // Helper to spawn a logger thread if configured.
+    fn setup_logger(
+        &self,
+    ) -> (
+        // A handle to later rejoin the logger thread.
+        Option<tokio::task::JoinHandle<()>>,
+        // A channel used by GooseClients to send logs.
+        Option<mpsc::UnboundedSender<Option<GooseDebug>>>,
+    ) {
+        // If the logger isn't configured, return immediately.
+        if self.configuration.debug_file.is_empty() {
+            return (None, None);
         }
 
         // Create an unbounded channel allowing GooseUser threads to log errors.
@@ -1369,66 +2617,549 @@ impl GooseAttack {
             self.configuration.clone(),
             logger_receiver,
         ));
-        (Some(logger_thread), Some(all_threads_logger))
+        (Some(logger_thread), Some(all_threads_logger))
+    }
+
+    // Helper to spawn a throttle thread if configured.
+    fn setup_throttle(&self) -> Result<ThrottleChannels, GooseError> {
+        // The throttle either allows a fixed number of requests per second, or follows
+        // a schedule. If neither is configured, return immediately.
+        let schedule = if let Some(throttle_requests) = self.configuration.throttle_requests {
+            vec![ThrottleStep {
+                requests: throttle_requests,
+                duration: time::Duration::from_secs(0),
+            }]
+        } else if !self.configuration.throttle_schedule.is_empty() {
+            throttle::parse_throttle_schedule(&self.configuration.throttle_schedule)?
+        } else {
+            return Ok((None, None));
+        };
+        let (all_threads_throttle, parent_to_throttle_tx) =
+            throttle::spawn_throttle(schedule, self.configuration.throttle_burst);
+        Ok((Some(all_threads_throttle), Some(parent_to_throttle_tx)))
+    }
+
+    // Helper to spawn a throttle thread for each task set that limits its requests.
+    fn setup_task_set_throttles(
+        &self,
+    ) -> (
+        // A channel per task set used by GooseClients to throttle requests, if enabled.
+        Vec<Option<mpsc::Sender<ThrottlePermit>>>,
+        // Channels used by parent to tell the throttles the load test is complete.
+        Vec<mpsc::UnboundedSender<ThrottleCommand>>,
+    ) {
+        let mut task_set_throttles = Vec::new();
+        let mut parent_to_task_set_throttles = Vec::new();
+        for task_set in &self.task_sets {
+            match task_set.throttle_requests {
+                Some(requests) => {
+                    info!(
+                        "{} throttle allowing {} request(s) per second",
+                        task_set.name, requests
+                    );
+                    let schedule = vec![ThrottleStep {
+                        requests,
+                        duration: time::Duration::from_secs(0),
+                    }];
+                    let (throttle, parent_to_throttle_tx) =
+                        throttle::spawn_throttle(schedule, None);
+                    task_set_throttles.push(Some(throttle));
+                    parent_to_task_set_throttles.push(parent_to_throttle_tx);
+                }
+                None => task_set_throttles.push(None),
+            }
+        }
+        (task_set_throttles, parent_to_task_set_throttles)
+    }
+
+    /// Launch the next GooseUser thread. Users are always launched and stopped in
+    /// order, so the next user is the one following the last running user.
+    async fn launch_user(&mut self, run_state: &mut GooseAttackRunState) -> Result<(), GooseError> {
+        let weighted_users_index = run_state.user_channels.len();
+        let mut thread_user = if weighted_users_index < self.metrics.users {
+            // This user was already launched and stopped earlier in the load test,
+            // start it over with a fresh state.
+            let task_sets_index = self.weighted_users[weighted_users_index].task_sets_index;
+            let base_url = goose::get_base_url(
+                self.get_configuration_host(),
+                self.task_sets[task_sets_index].host.clone(),
+                self.host.clone(),
+            )?;
+            GooseUser::new(
+                task_sets_index,
+                base_url,
+                &self.configuration,
+                self.metrics.hash,
+            )?
+        } else {
+            self.weighted_users[weighted_users_index].clone()
+        };
+
+        // Copy weighted tasks and weighted on start tasks into the user thread.
+        thread_user.weighted_tasks = self.task_sets[thread_user.task_sets_index]
+            .weighted_tasks
+            .clone();
+        thread_user.weighted_on_start_tasks = self.task_sets[thread_user.task_sets_index]
+            .weighted_on_start_tasks
+            .clone();
+        thread_user.weighted_on_stop_tasks = self.task_sets[thread_user.task_sets_index]
+            .weighted_on_stop_tasks
+            .clone();
+        // Remember which task group this user is using.
+        thread_user.weighted_users_index = weighted_users_index;
+
+        // Create a per-thread channel allowing parent thread to control child threads.
+        let (parent_sender, thread_receiver): (
+            mpsc::UnboundedSender<GooseUserCommand>,
+            mpsc::UnboundedReceiver<GooseUserCommand>,
+        ) = mpsc::unbounded_channel();
+        run_state.user_channels.push(parent_sender);
+
+        // Copy the GooseUser-to-logger sender channel, used by all threads.
+        thread_user.logger = run_state.all_threads_logger.clone();
+
+        // Copy the GooseUser-throttle receiver channel, used by all threads.
+        thread_user.throttle = run_state.all_threads_throttle.clone();
+
+        // Copy the task set throttle channel and per-user throttle, if enabled.
+        thread_user.task_set_throttle =
+            run_state.task_set_throttles[thread_user.task_sets_index].clone();
+        thread_user.request_interval = self.task_sets[thread_user.task_sets_index]
+            .user_throttle_requests
+            .map(|requests| time::Duration::from_secs_f64(1.0 / requests as f64));
+
+        // Share the request counter if --max-requests is enabled.
+        if self.configuration.max_requests.is_some() {
+            thread_user.request_counter = Some(run_state.request_counter.clone());
+        }
+
+        // Copy the GooseUser-to-parent sender channel, used by all threads.
+        thread_user.channel_to_parent = Some(run_state.all_threads_sender.clone());
+
+        // Copy the appropriate task_set into the thread.
+        let thread_task_set = self.task_sets[thread_user.task_sets_index].clone();
+
+        // We number threads from 1 as they're human-visible (in the logs), whereas
+        // weighted_users_index starts at 0.
+        let thread_number = weighted_users_index + 1;
+
+        let is_worker = self.configuration.worker;
+
+        // Launch a new user.
+        let user = tokio::spawn(user::user_main(
+            thread_number,
+            thread_task_set,
+            thread_user,
+            thread_receiver,
+            run_state.all_threads_ready.clone(),
+            run_state.users_finished.clone(),
+            is_worker,
+        ));
+
+        run_state.users.push(user);
+        // Track the most users that have run at the same time.
+        if thread_number > self.metrics.users {
+            self.metrics.users = thread_number;
+        }
+
+        Ok(())
     }
 
-    // Helper to spawn a throttle thread if configured.
-    async fn setup_throttle(
-        &self,
-    ) -> (
-        // A channel used by GooseClients to throttle requests.
-        Option<mpsc::Sender<bool>>,
-        // A channel used by parent to tell throttle the load test is complete.
-        Option<mpsc::Sender<bool>>,
-    ) {
-        // If the throttle isn't configured, return immediately.
-        if self.configuration.throttle_requests.is_none() {
-            return (None, None);
+    /// Tell the most recently launched GooseUser thread to exit. The user runs its
+    /// on_stop tasks before exiting.
+    fn stop_user(&self, run_state: &mut GooseAttackRunState) {
+        if let Some(send_to_user) = run_state.user_channels.pop() {
+            let index = run_state.user_channels.len();
+            match send_to_user.send(GooseUserCommand::EXIT) {
+                Ok(_) => {
+                    debug!("telling user {} to exit", index);
+                }
+                Err(e) => {
+                    info!("failed to tell user {} to exit: {}", index, e);
+                }
+            }
+        }
+    }
+
+    /// Launch or stop users as required by the current step of the test plan.
+    /// Returns how long to wait before users may need to be adjusted again.
+    async fn adjust_users(
+        &mut self,
+        run_state: &mut GooseAttackRunState,
+    ) -> Result<time::Duration, GooseError> {
+        let one_second = time::Duration::from_secs(1);
+
+        while let Some(step) = run_state.test_plan.steps.get(run_state.step).cloned() {
+            let elapsed = run_state.step_started.elapsed();
+            let change = util::abs_diff(step.users, run_state.step_users);
+
+            // Spread launching or stopping users evenly across the step: the first
+            // user is adjusted immediately, and then one every duration / change.
+            let adjusted = if elapsed >= step.duration {
+                change
+            } else {
+                let progress = elapsed.as_secs_f64() / step.duration.as_secs_f64();
+                change.min((change as f64 * progress) as usize + 1)
+            };
+            let desired = if step.users > run_state.step_users {
+                run_state.step_users + adjusted
+            } else {
+                run_state.step_users - adjusted
+            };
+            while run_state.user_channels.len() < desired {
+                self.launch_user(run_state).await?;
+            }
+            while run_state.user_channels.len() > desired {
+                self.stop_user(run_state);
+            }
+
+            if elapsed < step.duration {
+                // Wake up when the next user needs to be adjusted, or the step ends.
+                let mut next = step.duration - elapsed;
+                if adjusted < change {
+                    let next_adjustment = step.duration.mul_f64(adjusted as f64 / change as f64);
+                    if next_adjustment > elapsed {
+                        next = next.min(next_adjustment - elapsed);
+                    }
+                }
+                return Ok(next.min(one_second));
+            }
+
+            // This step is complete, advance to the next step. Adding the step
+            // duration instead of restarting the timer prevents time-drift.
+            run_state.step += 1;
+            run_state.step_held = false;
+            run_state.step_started += step.duration;
+            run_state.step_users = run_state.user_channels.len();
+            if self.test_plan.is_some() {
+                if let Some(next_step) = run_state.test_plan.steps.get(run_state.step) {
+                    info!(
+                        "test plan step {} of {}: adjusting from {} to {} users over {:?}",
+                        run_state.step + 1,
+                        run_state.test_plan.steps.len(),
+                        run_state.step_users,
+                        next_step.users,
+                        next_step.duration
+                    );
+                }
+            }
+        }
+
+        // The test plan is complete.
+        Ok(one_second)
+    }
+
+    /// When running at an arrival rate, start all iterations that are due, each on the
+    /// GooseUser that has been ready the longest. If no GooseUser is ready the iteration
+    /// is dropped. Returns how long to wait before the next iteration is due.
+    fn start_iterations(&mut self, run_state: &mut GooseAttackRunState) -> Option<time::Duration> {
+        let arrival_rate = self.configuration.arrival_rate? as f64;
+
+        // Collect GooseUsers that have become ready since last time.
+        if let Some(ready_receiver) = run_state.ready_receiver.as_mut() {
+            while let Ok(ready_user) = ready_receiver.try_recv() {
+                run_state.ready_users.push_back(ready_user);
+            }
+        }
+
+        // The first iteration starts immediately, and then one every 1 / arrival_rate
+        // fraction of a second. Calculating from when arrivals started prevents time-drift.
+        let due = (run_state.arrivals_started.elapsed().as_secs_f64() * arrival_rate) as usize + 1;
+        while run_state.arrivals < due {
+            run_state.arrivals += 1;
+            let mut started = false;
+            while let Some(ready_user) = run_state.ready_users.pop_front() {
+                // Sending fails if the GooseUser has since exited, try the next one.
+                if ready_user.send(()).is_ok() {
+                    started = true;
+                    break;
+                }
+            }
+            if started {
+                self.metrics.iterations += 1;
+            } else {
+                debug!("no users ready, dropping iteration {}", run_state.arrivals);
+                self.metrics.dropped_iterations += 1;
+            }
         }
 
-        // Unwrap is safe here as we exit early if the throttle isn't configured.
-        let throttle_requests = self.configuration.throttle_requests.unwrap();
+        let next_arrival = run_state.arrivals_started
+            + time::Duration::from_secs_f64(run_state.arrivals as f64 / arrival_rate);
+        Some(next_arrival.saturating_duration_since(time::Instant::now()))
+    }
+
+    /// Check if the load test has done all the work requested with --iterations or
+    /// --max-requests.
+    fn work_completed(&self, run_state: &GooseAttackRunState, users_launched: bool) -> bool {
+        if let Some(max_requests) = self.configuration.max_requests {
+            if run_state.request_counter.load(Ordering::SeqCst) >= max_requests {
+                info!("made {} requests", max_requests);
+                return true;
+            }
+        }
+        if let Some(iterations) = self.configuration.iterations {
+            // Wait until all users have been launched and have finished running.
+            if users_launched
+                && run_state.users_finished.load(Ordering::SeqCst) >= run_state.user_channels.len()
+            {
+                info!("all users completed {} iterations", iterations);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Tell all running users to pause, or to resume.
+    fn pause_users(&mut self, run_state: &mut GooseAttackRunState, pause: bool) {
+        if pause == run_state.paused.is_some() {
+            debug!("ignoring duplicate pause or resume");
+            return;
+        }
+        let command = if pause {
+            info!("pausing {} users...", run_state.user_channels.len());
+            run_state.paused = Some(time::Instant::now());
+            GooseUserCommand::PAUSE
+        } else {
+            let paused_at = run_state.paused.take().unwrap();
+            info!(
+                "resuming {} users after {:?}...",
+                run_state.user_channels.len(),
+                paused_at.elapsed()
+            );
+            // Exclude the paused time from the run time, test plan and arrival rate. Timers
+            // restarted while paused, for example by adjusting users, only exclude the
+            // time since they restarted.
+            let now = time::Instant::now();
+            let exclude_paused = |timer: &mut time::Instant| {
+                *timer += now.saturating_duration_since(paused_at.max(*timer));
+            };
+            if let Some(started) = self.started.as_mut() {
+                exclude_paused(started);
+            }
+            exclude_paused(&mut run_state.step_started);
+            exclude_paused(&mut run_state.arrivals_started);
+            GooseUserCommand::RESUME
+        };
+        for (index, send_to_user) in run_state.user_channels.iter().enumerate() {
+            if let Err(e) = send_to_user.send(command.clone()) {
+                debug!("failed to tell user {} to {:?}: {}", index, command, e);
+            }
+        }
+    }
 
-        // Create a bounded channel allowing single-sender multi-receiver to throttle
-        // GooseUser threads.
-        let (all_threads_throttle, throttle_receiver): (mpsc::Sender<bool>, mpsc::Receiver<bool>) =
-            mpsc::channel(throttle_requests);
+    /// Record a snapshot of the requests made during the current metrics history
+    /// interval, and start the next interval.
+    fn record_history(&mut self, run_state: &mut GooseAttackRunState) {
+        let now = time::Instant::now();
+        let duration = now.duration_since(run_state.history_started);
+        run_state.history_started = now;
+        // Intervals are consecutive, the first starting when the load test started.
+        let elapsed = match self.metrics.history.last() {
+            Some(snapshot) => time::Duration::from_millis(snapshot.elapsed) + duration,
+            None => duration,
+        };
+        let requests = run_state.history_requests.replace(HashMap::new());
+        self.metrics.history.push(GooseHistorySnapshot::new(
+            elapsed,
+            duration,
+            run_state.running_users(),
+            &requests.unwrap_or_default(),
+            self.metrics.percentiles(),
+        ));
+    }
 
-        // Create a channel allowing the parent to inform the throttle thread when the
-        // load test is finished. Even though we only send one message, we can't use a
-        // oneshot channel as we don't want to block waiting for a message.
-        let (parent_to_throttle_tx, throttle_rx) = mpsc::channel(1);
+    /// Evaluate `abort_on_fail` thresholds against the requests made since they were
+    /// last evaluated, canceling the load test once one has been breached for its grace
+    /// period.
+    fn check_thresholds(&mut self, run_state: &mut GooseAttackRunState) {
+        let now = time::Instant::now();
+        let window = GooseMetrics {
+            requests: run_state
+                .threshold_requests
+                .replace(HashMap::new())
+                .unwrap_or_default(),
+            // Checked about once a second, this is the duration requests per second
+            // are calculated over.
+            duration: (now
+                .duration_since(run_state.thresholds_checked)
+                .as_secs_f64()
+                .round() as usize)
+                .max(1),
+            ..Default::default()
+        };
+        run_state.thresholds_checked = now;
+        if self.threshold_aborted.is_some() {
+            return;
+        }
+        for (index, threshold) in self.thresholds.iter().enumerate() {
+            if !threshold.abort_on_fail {
+                continue;
+            }
+            let result = match threshold.breached(&window) {
+                Some(result) => result,
+                None => {
+                    run_state.threshold_breaches[index] = None;
+                    continue;
+                }
+            };
+            let breached = *run_state.threshold_breaches[index].get_or_insert(now);
+            if now.duration_since(breached) >= threshold.grace_period {
+                let reason = format!(
+                    "{} breached for {}s (actual {})",
+                    threshold.threshold,
+                    now.duration_since(breached).as_secs(),
+                    result.formatted_actual.unwrap_or_default()
+                );
+                warn!("aborting load test, threshold {}", reason);
+                self.threshold_aborted = Some((index, reason));
+                run_state.canceled.store(true, Ordering::SeqCst);
+                return;
+            }
+        }
+    }
 
-        // Launch a new thread for throttling, no need to rejoin it.
-        let _ = Some(tokio::spawn(throttle::throttle_main(
-            throttle_requests,
-            throttle_receiver,
-            throttle_rx,
-        )));
+    /// The per-second rate users are launched and stopped at, configured with
+    /// --hatch-rate.
+    fn hatch_rate(&self) -> usize {
+        self.configuration.hatch_rate.unwrap_or(1)
+    }
 
-        let mut sender = all_threads_throttle.clone();
-        // We start from 1 instead of 0 to intentionally fill all but one slot in the
-        // channel to avoid a burst of traffic during startup. The channel then provides
-        // an implementation of the leaky bucket algorithm as a queue. Requests have to
-        // add a token to the bucket before making a request, and are blocked until this
-        // throttle thread "leaks out" a token thereby creating space. More information
-        // can be found at: https://en.wikipedia.org/wiki/Leaky_bucket
-        for _ in 1..throttle_requests {
-            let _ = sender.send(true).await;
+    /// How long the load test has been running, excluding time spent paused.
+    fn elapsed(&self, run_state: &GooseAttackRunState) -> time::Duration {
+        let started = self.started.unwrap();
+        match run_state.paused {
+            Some(paused) => paused.saturating_duration_since(started),
+            None => started.elapsed(),
         }
+    }
 
-        (Some(all_threads_throttle), Some(parent_to_throttle_tx))
+    /// Process a command received from a GooseController.
+    fn handle_control(&mut self, command: GooseControl, run_state: &mut GooseAttackRunState) {
+        debug!("control command: {:?}", command);
+        match command {
+            GooseControl::StopUsers(users) => {
+                if run_state.ramping_down {
+                    warn!(
+                        "ignoring request to stop {} users, already ramping down",
+                        users
+                    );
+                    return;
+                }
+                let running = run_state.user_channels.len();
+                let users = users.min(running);
+                info!("stopping {} of {} running users...", users, running);
+                run_state.set_users(running - users, time::Duration::from_secs(0));
+            }
+            GooseControl::SetThrottleRequests(requests) => {
+                if requests == 0 || requests > throttle::MAX_THROTTLE_REQUESTS {
+                    warn!(
+                        "ignoring invalid throttle of {} requests per second, must be from 1 to 1,000,000",
+                        requests
+                    );
+                } else if let Some(tx) = run_state.parent_to_throttle_tx.as_ref() {
+                    let _ = tx.send(ThrottleCommand::SetRequests(requests));
+                } else {
+                    warn!("ignoring throttle change, the throttle is not enabled");
+                }
+            }
+            GooseControl::Pause => self.pause_users(run_state, true),
+            GooseControl::Resume => self.pause_users(run_state, false),
+            GooseControl::SetUsers(users) => {
+                if run_state.canceled.load(Ordering::SeqCst) {
+                    warn!(
+                        "ignoring request for {} users, the load test is stopping",
+                        users
+                    );
+                    return;
+                }
+                if run_state.ramping_down {
+                    warn!("ignoring request for {} users, already ramping down", users);
+                    return;
+                }
+                // Allocate states for any users beyond those already allocated.
+                if users > self.weighted_users.len() {
+                    self.users = users;
+                    match self.weight_task_set_users() {
+                        Ok(weighted_users) => self.weighted_users = weighted_users,
+                        Err(e) => {
+                            warn!("failed to allocate {} users: {}", users, e);
+                            return;
+                        }
+                    }
+                }
+                let running = run_state.user_channels.len();
+                info!("adjusting from {} to {} users...", running, users);
+                let hatch_rate = self.hatch_rate() as f64;
+                run_state.set_users(
+                    users,
+                    time::Duration::from_secs_f64(
+                        util::abs_diff(users, running) as f64 / hatch_rate,
+                    ),
+                );
+            }
+            GooseControl::SetHatchRate(hatch_rate) => {
+                if hatch_rate == 0 {
+                    warn!("ignoring invalid hatch rate of 0 users per second");
+                    return;
+                }
+                info!("hatch rate now {} users per second", hatch_rate);
+                self.configuration.hatch_rate = Some(hatch_rate);
+                // If users are currently being launched or stopped without a test plan,
+                // continue at the new hatch rate. Ramping down continues at the
+                // --ramp-down-rate.
+                if self.test_plan.is_none() && !run_state.ramping_down {
+                    if let Some(step) = run_state.test_plan.steps.get(run_state.step).cloned() {
+                        let running = run_state.user_channels.len();
+                        if step.users != running {
+                            run_state.set_users(
+                                step.users,
+                                time::Duration::from_secs_f64(
+                                    util::abs_diff(step.users, running) as f64 / hatch_rate as f64,
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+            GooseControl::ResetMetrics => {
+                info!("resetting metrics");
+                self.metrics.requests = HashMap::new();
+                self.metrics.failures = HashMap::new();
+                self.metrics
+                    .initialize_task_metrics(&self.task_sets, &self.configuration);
+                self.metrics.iterations = 0;
+                self.metrics.dropped_iterations = 0;
+                let now = time::Instant::now();
+                self.started = Some(now);
+                if run_state.paused.is_some() {
+                    run_state.paused = Some(now);
+                }
+            }
+            GooseControl::Metrics(reply) => {
+                self.metrics.duration = self.elapsed(run_state).as_secs() as usize;
+                self.metrics.running_users = run_state.running_users();
+                // The history grows throughout the load test, don't copy it to every
+                // controller that regularly requests metrics.
+                let history = std::mem::take(&mut self.metrics.history);
+                let _ = reply.send(self.metrics.clone());
+                self.metrics.history = history;
+            }
+            GooseControl::Stop => {
+                info!("controller stopping load test...");
+                run_state.canceled.store(true, Ordering::SeqCst);
+            }
+        }
     }
 
     /// Called internally in local-mode and gaggle-mode.
     async fn launch_users(
         mut self,
-        sleep_duration: time::Duration,
+        test_plan: TestPlan,
         socket: Option<Socket>,
     ) -> Result<GooseAttack, GooseError> {
         trace!(
-            "launch users: sleep_duration({:?}) socket({:?})",
-            sleep_duration,
+            "launch users: test_plan({:?}) socket({:?})",
+            test_plan,
             socket
         );
 
@@ -1457,102 +3188,102 @@ impl GooseAttack {
         let (logger_thread, all_threads_logger) = self.setup_logger();
 
         // If enabled, spawn a throttle thread.
-        let (all_threads_throttle, parent_to_throttle_tx) = self.setup_throttle().await;
+        let (all_threads_throttle, parent_to_throttle_tx) = self.setup_throttle()?;
+
+        // If enabled, spawn a throttle thread for each throttled task set.
+        let (task_set_throttles, parent_to_task_set_throttles) = self.setup_task_set_throttles();
 
-        // Collect user threads in a vector for when we want to stop them later.
-        let mut users = vec![];
-        // Collect user thread channels in a vector so we can talk to the user threads.
-        let mut user_channels = vec![];
         // Create a single channel allowing all Goose child threads to sync metrics back
         // to the parent process.
-        let (all_threads_sender, mut metric_receiver): (
+        let (all_threads_sender, metric_receiver): (
             mpsc::UnboundedSender<GooseMetric>,
             mpsc::UnboundedReceiver<GooseMetric>,
         ) = mpsc::unbounded_channel();
 
-        // A new user thread will be spawned at regular intervals. The spawning_user_drift
-        // variable tracks how much time is spent on everything else, and is subtracted from
-        // the time spent sleeping.
-        let mut spawning_user_drift = tokio::time::Instant::now();
-
-        // Spawn users, each with their own weighted task_set.
-        for mut thread_user in self.weighted_users.clone() {
-            // Stop launching threads if the run_timer has expired, unwrap is safe as we only get here if we started.
-            if util::timer_expired(self.started.unwrap(), self.run_time) {
-                break;
-            }
-
-            // Copy weighted tasks and weighted on start tasks into the user thread.
-            thread_user.weighted_tasks = self.task_sets[thread_user.task_sets_index]
-                .weighted_tasks
-                .clone();
-            thread_user.weighted_on_start_tasks = self.task_sets[thread_user.task_sets_index]
-                .weighted_on_start_tasks
-                .clone();
-            thread_user.weighted_on_stop_tasks = self.task_sets[thread_user.task_sets_index]
-                .weighted_on_stop_tasks
-                .clone();
-            // Remember which task group this user is using.
-            thread_user.weighted_users_index = self.metrics.users;
-
-            // Create a per-thread channel allowing parent thread to control child threads.
-            let (parent_sender, thread_receiver): (
-                mpsc::UnboundedSender<GooseUserCommand>,
-                mpsc::UnboundedReceiver<GooseUserCommand>,
-            ) = mpsc::unbounded_channel();
-            user_channels.push(parent_sender);
-
-            if !self.configuration.debug_file.is_empty() {
-                // Copy the GooseUser-to-logger sender channel, used by all threads.
-                thread_user.logger = Some(all_threads_logger.clone().unwrap());
-            } else {
-                thread_user.logger = None;
-            }
+        // Without a test plan, run until canceled if no run time is configured.
+        let run_until_canceled = self.test_plan.is_none() && self.run_time == 0;
 
-            // Copy the GooseUser-throttle receiver channel, used by all threads.
-            match self.configuration.throttle_requests {
-                Some(_) => thread_user.throttle = Some(all_threads_throttle.clone().unwrap()),
-                None => thread_user.throttle = None,
+        // If configured, users are stopped one every ramp_down_interval when the load
+        // test ends. Workers stop their share of users at their share of the rate.
+        let ramp_down_interval = self.configuration.ramp_down_rate.map(|rate| {
+            let mut rate = rate as f32;
+            if self.configuration.worker {
+                rate /= self.configuration.expect_workers as f32;
             }
-
-            // Copy the GooseUser-to-parent sender channel, used by all threads.
-            thread_user.channel_to_parent = Some(all_threads_sender.clone());
-
-            // Copy the appropriate task_set into the thread.
-            let thread_task_set = self.task_sets[thread_user.task_sets_index].clone();
-
-            // We number threads from 1 as they're human-visible (in the logs), whereas
-            // metrics.users starts at 0.
-            let thread_number = self.metrics.users + 1;
-
-            let is_worker = self.configuration.worker;
-
-            // Launch a new user.
-            let user = tokio::spawn(user::user_main(
-                thread_number,
-                thread_task_set,
-                thread_user,
-                thread_receiver,
-                is_worker,
-            ));
-
-            users.push(user);
-            self.metrics.users += 1;
-            debug!("sleeping {:?} milliseconds...", sleep_duration);
-
-            spawning_user_drift =
-                util::sleep_minus_drift(sleep_duration, spawning_user_drift).await;
-        }
-        if self.configuration.worker {
+            time::Duration::from_secs_f32(1.0 / rate)
+        });
+        // Whether the load test had been canceled when ramping down started.
+        let mut ramp_down_canceled = false;
+        if self.test_plan.is_some() {
             info!(
-                "[{}] launched {} users...",
-                get_worker_id(),
-                self.metrics.users
+                "test plan step 1 of {}: adjusting from 0 to {} users over {:?}",
+                test_plan.steps.len(),
+                test_plan.steps[0].users,
+                test_plan.steps[0].duration
             );
-        } else {
-            info!("launched {} users...", self.metrics.users);
         }
 
+        // If running at an arrival rate, GooseUsers wait to be told to start each iteration.
+        let (all_threads_ready, ready_receiver) = if self.configuration.arrival_rate.is_some() {
+            let (all_threads_ready, ready_receiver) = mpsc::unbounded_channel();
+            (Some(all_threads_ready), Some(ready_receiver))
+        } else {
+            (None, None)
+        };
+
+        let mut run_state = GooseAttackRunState {
+            logger_thread,
+            all_threads_logger,
+            all_threads_throttle,
+            parent_to_throttle_tx,
+            task_set_throttles,
+            parent_to_task_set_throttles,
+            all_threads_sender,
+            metric_receiver,
+            users: Vec::new(),
+            users_finished: Arc::new(AtomicUsize::new(0)),
+            request_counter: Arc::new(AtomicUsize::new(0)),
+            user_channels: Vec::new(),
+            test_plan,
+            step: 0,
+            step_started: time::Instant::now(),
+            step_users: 0,
+            step_held: false,
+            ramping_down: false,
+            control_receiver: self.control_receiver.lock().unwrap().take(),
+            all_threads_ready,
+            ready_receiver,
+            ready_users: VecDeque::new(),
+            arrivals_started: time::Instant::now(),
+            arrivals: 0,
+            paused: None,
+            canceled: Arc::new(AtomicBool::new(false)),
+            controller_shutdowns: Vec::new(),
+            history_interval: match self.configuration.history_interval.as_str() {
+                "" => None,
+                interval => Some(time::Duration::from_secs(
+                    util::parse_timespan(interval) as u64
+                )),
+            },
+            history_started: time::Instant::now(),
+            history_requests: if self.configuration.history_interval.is_empty() {
+                None
+            } else {
+                Some(HashMap::new())
+            },
+            prometheus: self.prometheus_address().map(|_| Default::default()),
+            metrics_stream: None,
+            influxdb: None,
+            metrics_sinks: std::mem::take(&mut *self.metrics_sinks.lock().unwrap()),
+            threshold_breaches: vec![None; self.thresholds.len()],
+            thresholds_checked: time::Instant::now(),
+            threshold_requests: if self.thresholds.iter().any(|t| t.abort_on_fail) {
+                Some(HashMap::new())
+            } else {
+                None
+            },
+        };
+
         // Only display status codes if enabled.
         self.metrics.display_status_codes = self.configuration.status_codes;
 
@@ -1560,46 +3291,178 @@ impl GooseAttack {
         let mut users_launched: bool = false;
 
         // Catch ctrl-c to allow clean shutdown to display metrics.
-        let canceled = Arc::new(AtomicBool::new(false));
-        util::setup_ctrlc_handler(&canceled);
+        util::setup_ctrlc_handler(&run_state.canceled);
+
+        // Optionally listen for controller commands on a TCP socket.
+        if let Some(address) = self.controller_address() {
+            let shutdown = controller::spawn_controller(&address, self.control_sender.clone())
+                .await
+                .map_err(|e| GooseError::InvalidOption {
+                    option: "--controller-host".to_string(),
+                    value: address.to_string(),
+                    detail: format!("Failed to start controller: {}.", e),
+                })?;
+            run_state.controller_shutdowns.push(shutdown);
+        }
+
+        // Optionally serve the web UI, waiting for the load test to be started from it.
+        #[cfg(feature = "webui")]
+        {
+            if let Some(address) = self.webui_address() {
+                let websocket_port = self
+                    .configuration
+                    .websocket_port
+                    .unwrap_or(DEFAULT_WEBSOCKET_PORT);
+                let shutdown = webui::spawn_webui(&address, websocket_port)
+                    .await
+                    .map_err(|e| GooseError::InvalidOption {
+                        option: "--webui-host".to_string(),
+                        value: address.to_string(),
+                        detail: format!("Failed to start web UI: {}.", e),
+                    })?;
+                run_state.controller_shutdowns.push(shutdown);
+                info!("waiting for the load test to be started from the web UI");
+                run_state.paused = Some(time::Instant::now());
+            }
+        }
+
+        // Optionally stream metrics and listen for controller commands on a WebSocket.
+        if let Some(address) = self.websocket_address() {
+            let shutdown = websocket::spawn_websocket(&address, self.control_sender.clone())
+                .await
+                .map_err(|e| GooseError::InvalidOption {
+                    option: "--websocket-host".to_string(),
+                    value: address.to_string(),
+                    detail: format!("Failed to start WebSocket controller: {}.", e),
+                })?;
+            run_state.controller_shutdowns.push(shutdown);
+        }
+
+        // Optionally serve metrics to Prometheus.
+        if let (Some(address), Some(prometheus)) =
+            (self.prometheus_address(), run_state.prometheus.as_ref())
+        {
+            let shutdown = prometheus::spawn_prometheus(&address, prometheus.clone())
+                .await
+                .map_err(|e| GooseError::InvalidOption {
+                    option: "--prometheus-host".to_string(),
+                    value: address.to_string(),
+                    detail: format!("Failed to start Prometheus endpoint: {}.", e),
+                })?;
+            run_state.controller_shutdowns.push(shutdown);
+        }
+
+        // Optionally stream metrics to StatsD or Graphite.
+        if let Some((option, address)) = self.stream_address() {
+            let prefix = match self.configuration.stream_prefix.as_str() {
+                "" => "goose",
+                prefix => prefix,
+            };
+            let interval = match self.configuration.stream_interval.as_str() {
+                "" => None,
+                interval => Some(time::Duration::from_secs(
+                    util::parse_timespan(interval) as u64
+                )),
+            };
+            let metrics_stream = if self.configuration.statsd_address.is_empty() {
+                MetricsStream::graphite(&address, prefix, interval).await
+            } else {
+                MetricsStream::statsd(&address, prefix, interval).await
+            };
+            run_state.metrics_stream =
+                Some(metrics_stream.map_err(|e| GooseError::InvalidOption {
+                    option: option.to_string(),
+                    value: address.to_string(),
+                    detail: format!("Failed to connect: {}.", e),
+                })?);
+        }
 
         // Determine when to display running metrics (if enabled).
         let mut metrics_timer = time::Instant::now();
         let mut display_running_metrics = false;
 
-        // Prepare an asynchronous buffered file writer for metrics_file (if enabled).
-        let mut metrics_file = None;
+        // Write requests to metrics_file in the configured format (if enabled).
         if !self.configuration.no_metrics && !self.configuration.metrics_file.is_empty() {
-            info!(
-                "opening file to log metrics: {}",
-                self.configuration.metrics_file
-            );
-            let file = File::create(&self.configuration.metrics_file).await?;
-            metrics_file = Some(BufWriter::new(file));
+            let metrics_file = &self.configuration.metrics_file;
+            let sink: Box<dyn GooseMetricsSink> = match self.configuration.metrics_format.as_str() {
+                "csv" => Box::new(CsvMetricsSink::create(metrics_file)?),
+                "raw" => Box::new(RawMetricsSink::create(metrics_file)?),
+                _ => Box::new(JsonMetricsSink::create(metrics_file)?),
+            };
+            run_state.metrics_sinks.push(sink);
+        }
+
+        // Optionally write metrics as InfluxDB line protocol to a file or an endpoint.
+        if !self.configuration.influxdb_file.is_empty()
+            || !self.configuration.influxdb_url.is_empty()
+        {
+            let mut influxdb = InfluxDbOutput::default();
+            if !self.configuration.influxdb_file.is_empty() {
+                influxdb.file(&self.configuration.influxdb_file).await?;
+            }
+            if !self.configuration.influxdb_url.is_empty() {
+                influxdb.endpoint(&self.configuration.influxdb_url)?;
+            }
+            run_state.influxdb = Some(influxdb);
         }
 
         // Initialize the optional task metrics.
         self.metrics
             .initialize_task_metrics(&self.task_sets, &self.configuration);
 
-        // If logging metrics to CSV, use this flag to write header; otherwise it's ignored.
-        let mut header = true;
         loop {
+            // Launch or stop users as required by the test plan, and start iterations
+            // at the configured arrival rate, unless paused.
+            let mut sleep_duration = time::Duration::from_secs(1);
+            if run_state.paused.is_none() {
+                sleep_duration = self.adjust_users(&mut run_state).await?;
+                if let Some(next_arrival) = self.start_iterations(&mut run_state) {
+                    sleep_duration = sleep_duration.min(next_arrival);
+                }
+            }
+            self.metrics.running_users = run_state.running_users();
+            if let Some(prometheus) = run_state.prometheus.as_ref() {
+                prometheus
+                    .lock()
+                    .unwrap()
+                    .set_users(self.metrics.running_users);
+            }
+
+            // Without a test plan, all users are launched in the first step.
+            let all_users_launched = !users_launched && run_state.step > 0;
+            if all_users_launched {
+                users_launched = true;
+                if self.test_plan.is_none() {
+                    if self.configuration.worker {
+                        info!(
+                            "[{}] launched {} users...",
+                            get_worker_id(),
+                            self.metrics.users
+                        );
+                    } else {
+                        info!("launched {} users...", self.metrics.users);
+                    }
+                }
+            }
+
             // Regularly sync data from user threads first.
             if !self.configuration.no_metrics {
-                // Check if we're displaying running metrics.
-                if !self.configuration.only_summary
-                    && !self.configuration.worker
-                    && util::timer_expired(metrics_timer, RUNNING_METRICS_EVERY)
-                {
+                // Check if we're displaying running metrics, or passing a snapshot of
+                // them to metrics sinks.
+                if util::timer_expired(metrics_timer, RUNNING_METRICS_EVERY) {
                     metrics_timer = time::Instant::now();
                     display_running_metrics = true;
                 }
 
-                // Load messages from user threads until the receiver queue is empty.
-                let received_message = self
-                    .receive_metrics(&mut metric_receiver, &mut header, &mut metrics_file)
-                    .await;
+                // Load messages from user threads until the receiver queue is empty,
+                // or for at most RECEIVE_METRICS_FOR milliseconds.
+                let received_message = self.receive_metrics(&mut run_state, false).await;
+                if let Some(metrics_stream) = run_state.metrics_stream.as_mut() {
+                    metrics_stream.flush(false).await;
+                }
+                if let Some(influxdb) = run_state.influxdb.as_mut() {
+                    influxdb.flush(false).await;
+                }
 
                 // As worker, push metrics up to manager.
                 if self.configuration.worker && received_message {
@@ -1611,24 +3474,26 @@ impl GooseAttack {
                             vec![
                                 GaggleMetrics::Requests(self.metrics.requests.clone()),
                                 GaggleMetrics::Tasks(self.metrics.tasks.clone()),
+                                GaggleMetrics::Failures(self.metrics.failures.clone()),
                             ],
                             true,
                         ) {
                             // EXIT received, cancel.
-                            canceled.store(true, Ordering::SeqCst);
+                            run_state.canceled.store(true, Ordering::SeqCst);
                         }
                         // The manager has all our metrics, reset locally.
                         self.metrics.requests = HashMap::new();
+                        self.metrics.failures = HashMap::new();
                         self.metrics
                             .initialize_task_metrics(&self.task_sets, &self.configuration);
                     }
                 }
 
-                // Flush metrics collected prior to all user threads running
-                if !users_launched {
-                    users_launched = true;
+                // Flush metrics collected prior to all user threads running. A test
+                // plan explicitly defines how users start, so its metrics aren't reset.
+                if all_users_launched && self.test_plan.is_none() {
                     if !self.configuration.no_reset_metrics {
-                        self.metrics.duration = self.started.unwrap().elapsed().as_secs() as usize;
+                        self.metrics.duration = self.elapsed(&run_state).as_secs() as usize;
                         self.metrics.print_running();
 
                         if self.metrics.display_metrics {
@@ -1644,8 +3509,11 @@ impl GooseAttack {
                         }
 
                         self.metrics.requests = HashMap::new();
+                        self.metrics.failures = HashMap::new();
                         self.metrics
                             .initialize_task_metrics(&self.task_sets, &self.configuration);
+                        self.metrics.iterations = 0;
+                        self.metrics.dropped_iterations = 0;
                         // Restart the timer now that all threads are launched.
                         self.started = Some(time::Instant::now());
                     } else if self.metrics.users < self.users {
@@ -1659,9 +3527,60 @@ impl GooseAttack {
                 }
             }
 
-            if util::timer_expired(self.started.unwrap(), self.run_time)
-                || canceled.load(Ordering::SeqCst)
+            // Optionally record a snapshot of the metrics at the end of each interval.
+            if let Some(history_interval) = run_state.history_interval {
+                if run_state.history_started.elapsed() >= history_interval {
+                    self.record_history(&mut run_state);
+                }
+            }
+
+            // Abort the load test if an abort_on_fail threshold is breached. Workers
+            // inherit thresholds from the Manager, which doesn't allow abort_on_fail.
+            if !self.configuration.no_metrics
+                && !self.configuration.worker
+                && run_state.thresholds_checked.elapsed() >= time::Duration::from_secs(1)
             {
+                self.check_thresholds(&mut run_state);
+            }
+
+            let test_plan_complete = run_state.step >= run_state.test_plan.steps.len();
+            let canceled = run_state.canceled.load(Ordering::SeqCst);
+            let stopping = if run_state.ramping_down {
+                // Canceling the load test while ramping down stops all users immediately.
+                test_plan_complete || (canceled && !ramp_down_canceled)
+            } else {
+                (test_plan_complete && !run_until_canceled)
+                    || canceled
+                    || self.work_completed(&run_state, users_launched)
+            };
+
+            // Resume paused users so they can stop, running their on_stop tasks.
+            if stopping && run_state.paused.is_some() {
+                self.pause_users(&mut run_state, false);
+            }
+
+            // Optionally stop running users gradually before ending the load test. Users
+            // that finished on their own, for example after completing --iterations,
+            // don't need to be ramped down and are stopped along the way, or straight
+            // away if no users are still running.
+            if stopping && !run_state.ramping_down && run_state.running_users() > 0 {
+                if let Some(interval) = ramp_down_interval {
+                    run_state.ramping_down = true;
+                    ramp_down_canceled = canceled;
+                    let users = run_state.running_users();
+                    if self.configuration.worker {
+                        info!("[{}] ramping down {} users...", get_worker_id(), users);
+                    } else {
+                        info!("ramping down {} users...", users);
+                    }
+                    // Replace what's left of the test plan with a final step stopping all users.
+                    run_state.test_plan.steps.truncate(run_state.step);
+                    run_state.set_users(0, interval * users as u32);
+                    continue;
+                }
+            }
+
+            if stopping {
                 if self.configuration.worker {
                     info!(
                         "[{}] stopping after {} seconds...",
@@ -1674,15 +3593,8 @@ impl GooseAttack {
                         self.started.unwrap().elapsed().as_secs()
                     );
                 }
-                for (index, send_to_user) in user_channels.iter().enumerate() {
-                    match send_to_user.send(GooseUserCommand::EXIT) {
-                        Ok(_) => {
-                            debug!("telling user {} to exit", index);
-                        }
-                        Err(e) => {
-                            info!("failed to tell user {} to exit: {}", index, e);
-                        }
-                    }
+                while !run_state.user_channels.is_empty() {
+                    self.stop_user(&mut run_state);
                 }
                 if self.configuration.worker {
                     info!("[{}] waiting for users to exit", get_worker_id());
@@ -1690,28 +3602,47 @@ impl GooseAttack {
                     info!("waiting for users to exit");
                 }
 
+                // If controllers are enabled, stop listening for commands.
+                for shutdown in run_state.controller_shutdowns.drain(..) {
+                    let _ = shutdown.send(());
+                }
+
                 // If throttle is enabled, tell throttle thread the load test is over.
-                if let Some(mut tx) = parent_to_throttle_tx {
-                    let _ = tx.send(false).await;
+                if let Some(tx) = run_state.parent_to_throttle_tx.take() {
+                    let _ = tx.send(ThrottleCommand::Exit);
+                }
+                for tx in run_state.parent_to_task_set_throttles.drain(..) {
+                    let _ = tx.send(ThrottleCommand::Exit);
                 }
 
-                futures::future::join_all(users).await;
+                futures::future::join_all(run_state.users.drain(..)).await;
                 debug!("all users exited");
+                self.metrics.running_users = 0;
 
-                if !self.configuration.debug_file.is_empty() {
+                if let Some(all_threads_logger) = run_state.all_threads_logger.take() {
                     // Tell logger thread to flush and exit.
-                    if let Err(e) = all_threads_logger.unwrap().send(None) {
+                    if let Err(e) = all_threads_logger.send(None) {
                         warn!("unexpected error telling logger thread to exit: {}", e);
                     };
+                }
+                if let Some(logger_thread) = run_state.logger_thread.take() {
                     // Wait for logger thread to flush and exit.
-                    let _ = tokio::join!(logger_thread.unwrap());
+                    let _ = tokio::join!(logger_thread);
                 }
 
                 // If we're printing metrics, collect the final metrics received from users.
                 if !self.configuration.no_metrics {
-                    let _received_message = self
-                        .receive_metrics(&mut metric_receiver, &mut header, &mut metrics_file)
-                        .await;
+                    let _received_message = self.receive_metrics(&mut run_state, true).await;
+                }
+
+                // Stream any remaining metrics, including a final partial interval.
+                if let Some(metrics_stream) = run_state.metrics_stream.as_mut() {
+                    metrics_stream.flush(true).await;
+                }
+
+                // Record the final, possibly partial, history interval.
+                if run_state.history_interval.is_some() {
+                    self.record_history(&mut run_state);
                 }
 
                 #[cfg(feature = "gaggle")]
@@ -1723,6 +3654,7 @@ impl GooseAttack {
                             vec![
                                 GaggleMetrics::Requests(self.metrics.requests.clone()),
                                 GaggleMetrics::Tasks(self.metrics.tasks.clone()),
+                                GaggleMetrics::Failures(self.metrics.failures.clone()),
                             ],
                             true,
                         );
@@ -1737,12 +3669,42 @@ impl GooseAttack {
             // If enabled, display running metrics after sync
             if display_running_metrics {
                 display_running_metrics = false;
-                self.metrics.duration = self.started.unwrap().elapsed().as_secs() as usize;
-                self.metrics.print_running();
+                self.metrics.duration = self.elapsed(&run_state).as_secs() as usize;
+                if !self.configuration.only_summary && !self.configuration.worker {
+                    self.metrics.print_running();
+                }
+                for sink in run_state.metrics_sinks.iter_mut() {
+                    sink.snapshot(&self.metrics);
+                }
+            }
+
+            // Regularly check if --iterations or --max-requests have been reached.
+            if self.configuration.iterations.is_some() || self.configuration.max_requests.is_some()
+            {
+                sleep_duration = sleep_duration.min(time::Duration::from_millis(100));
             }
 
-            let one_second = time::Duration::from_secs(1);
-            tokio::time::delay_for(one_second).await;
+            // Wait until users need to be adjusted, or a control command is received.
+            let command = match run_state.control_receiver.as_mut() {
+                Some(receiver) => match tokio::time::timeout(sleep_duration, receiver.recv()).await
+                {
+                    Ok(Some(command)) => Some(command),
+                    // All senders are gone, stop listening for commands.
+                    Ok(None) => {
+                        run_state.control_receiver = None;
+                        None
+                    }
+                    // Timed out, no command received.
+                    Err(_) => None,
+                },
+                None => {
+                    tokio::time::delay_for(sleep_duration).await;
+                    None
+                }
+            };
+            if let Some(command) = command {
+                self.handle_control(command, &mut run_state);
+            }
         }
         self.metrics.duration = self.started.unwrap().elapsed().as_secs() as usize;
 
@@ -1766,90 +3728,115 @@ impl GooseAttack {
             }
         }
 
-        // If metrics logging is enabled, flush all metrics before we exit.
-        if let Some(file) = metrics_file.as_mut() {
+        // Pass the final metrics to metrics sinks, which flush all metrics before we exit.
+        // Flushing can wait on slow storage, so don't block the runtime.
+        let metrics_sinks = std::mem::take(&mut run_state.metrics_sinks);
+        if !metrics_sinks.is_empty() {
+            let metrics = self.metrics.clone();
+            let summaries = tokio::task::spawn_blocking(move || {
+                for mut sink in metrics_sinks {
+                    sink.summary(&metrics);
+                }
+            });
+            if let Err(e) = summaries.await {
+                warn!("failed to pass the final metrics to metrics sinks: {}", e);
+            }
+        }
+        // If metrics history is enabled, optionally write it to a file.
+        if !self.configuration.history_file.is_empty() {
             info!(
-                "flushing metrics_file: {}",
-                &self.configuration.metrics_file
+                "writing metrics history to {}",
+                &self.configuration.history_file
             );
-            let _ = file.flush().await;
-        };
+            let history = match self.configuration.history_format.as_str() {
+                "csv" => self.metrics.history_csv(),
+                _ => self.metrics.history_json(),
+            };
+            tokio::fs::write(&self.configuration.history_file, history).await?;
+        }
+        // If InfluxDB line protocol is enabled, add the final metrics and write all points.
+        if let Some(influxdb) = run_state.influxdb.as_mut() {
+            influxdb.record_summary(&self.metrics.summary());
+            influxdb.flush(true).await;
+        }
         // Only display percentile once the load test is finished.
         self.metrics.display_percentile = true;
 
         Ok(self)
     }
 
-    async fn receive_metrics(
-        &mut self,
-        metric_receiver: &mut mpsc::UnboundedReceiver<GooseMetric>,
-        header: &mut bool,
-        metrics_file: &mut Option<BufWriter<File>>,
-    ) -> bool {
+    /// Load messages from user threads. Unless `flush` is set, stop after
+    /// RECEIVE_METRICS_FOR milliseconds even if more are queued: users that don't
+    /// wait between tasks can refill the queue as fast as it's drained.
+    async fn receive_metrics(&mut self, run_state: &mut GooseAttackRunState, flush: bool) -> bool {
+        let started = time::Instant::now();
         let mut received_message = false;
-        let mut message = metric_receiver.try_recv();
+        let mut message = run_state.metric_receiver.try_recv();
         while message.is_ok() {
             received_message = true;
             match message.unwrap() {
                 GooseMetric::Request(raw_request) => {
-                    // Options should appear above, search for formatted_log.
-                    let formatted_log = match self.configuration.metrics_format.as_str() {
-                        // Use serde_json to create JSON.
-                        "json" => json!(raw_request).to_string(),
-                        // Manually create CSV, library doesn't support single-row string conversion.
-                        "csv" => GooseAttack::prepare_csv(&raw_request, header),
-                        // Raw format is Debug output for GooseRawRequest structure.
-                        "raw" => format!("{:?}", raw_request).to_string(),
-                        _ => unreachable!(),
-                    };
-                    if let Some(file) = metrics_file.as_mut() {
-                        match file.write(format!("{}\n", formatted_log).as_ref()).await {
-                            Ok(_) => (),
-                            Err(e) => {
-                                warn!(
-                                    "failed to write metrics to {}: {}",
-                                    &self.configuration.metrics_file, e
-                                );
-                            }
-                        }
+                    for sink in run_state.metrics_sinks.iter_mut() {
+                        sink.request(&raw_request);
                     }
-                    let key = format!("{:?} {}", raw_request.method, raw_request.name);
-                    let mut merge_request = match self.metrics.requests.get(&key) {
-                        Some(m) => m.clone(),
-                        None => GooseRequest::new(&raw_request.name, raw_request.method, 0),
-                    };
-                    // Handle a metrics update.
-                    if raw_request.update {
-                        if raw_request.success {
-                            merge_request.success_count += 1;
-                            merge_request.fail_count -= 1;
-                        } else {
-                            merge_request.success_count -= 1;
-                            merge_request.fail_count += 1;
-                        }
+                    metrics::record_request(
+                        &mut self.metrics.requests,
+                        &raw_request,
+                        &self.configuration,
+                    );
+                    metrics::record_failure(&mut self.metrics.failures, &raw_request);
+                    // Also track requests made during the current history interval.
+                    if let Some(history_requests) = run_state.history_requests.as_mut() {
+                        metrics::record_request(
+                            history_requests,
+                            &raw_request,
+                            &self.configuration,
+                        );
                     }
-                    // Store a new metric.
-                    else {
-                        merge_request.set_response_time(raw_request.response_time);
-                        if self.configuration.status_codes {
-                            merge_request.set_status_code(raw_request.status_code);
-                        }
-                        if raw_request.success {
-                            merge_request.success_count += 1;
-                        } else {
-                            merge_request.fail_count += 1;
-                        }
+                    // And requests made since abort_on_fail thresholds were last checked.
+                    if let Some(threshold_requests) = run_state.threshold_requests.as_mut() {
+                        metrics::record_request(
+                            threshold_requests,
+                            &raw_request,
+                            &self.configuration,
+                        );
+                    }
+                    if let Some(prometheus) = run_state.prometheus.as_ref() {
+                        prometheus.lock().unwrap().record_request(&raw_request);
+                    }
+                    if let Some(metrics_stream) = run_state.metrics_stream.as_mut() {
+                        // Requests made by a single user, such as test_start_task, don't
+                        // belong to a task set.
+                        let taskset_name = self
+                            .weighted_users
+                            .get(raw_request.user)
+                            .map(|user| self.task_sets[user.task_sets_index].name.as_str())
+                            .unwrap_or("");
+                        metrics_stream.record_request(taskset_name, &raw_request);
+                    }
+                    if let Some(influxdb) = run_state.influxdb.as_mut() {
+                        influxdb.record_request(&raw_request);
                     }
-
-                    self.metrics.requests.insert(key.to_string(), merge_request);
                 }
                 GooseMetric::Task(raw_task) => {
+                    for sink in run_state.metrics_sinks.iter_mut() {
+                        sink.task(&raw_task);
+                    }
                     // Store a new metric.
                     self.metrics.tasks[raw_task.taskset_index][raw_task.task_index]
                         .set_time(raw_task.run_time, raw_task.success);
+                    if let Some(prometheus) = run_state.prometheus.as_ref() {
+                        prometheus
+                            .lock()
+                            .unwrap()
+                            .record_task(&self.task_sets[raw_task.taskset_index].name, &raw_task);
+                    }
                 }
             }
-            message = metric_receiver.try_recv();
+            if !flush && started.elapsed() >= time::Duration::from_millis(RECEIVE_METRICS_FOR) {
+                break;
+            }
+            message = run_state.metric_receiver.try_recv();
         }
         received_message
     }
@@ -1874,12 +3861,24 @@ pub struct GooseConfiguration {
     /// Sets concurrent users (default: number of CPUs)
     #[options(short = "u")]
     pub users: Option<usize>,
-    /// Sets per-second user hatch rate
-    #[options(short = "r", default = "1", meta = "RATE")]
-    pub hatch_rate: usize,
+    /// Sets per-second user hatch rate (default: 1)
+    #[options(short = "r", meta = "RATE")]
+    pub hatch_rate: Option<usize>,
+    /// Sets per-second user stop rate at end of test
+    #[options(no_short, meta = "RATE")]
+    pub ramp_down_rate: Option<usize>,
     /// Stops after (30s, 20m, 3h, 1h30m, etc)
     #[options(short = "t", meta = "TIME")]
     pub run_time: String,
+    /// Sets users,time steps to run (ie 10,30s;100,5m;0,30s)
+    #[options(no_short, meta = "PLAN")]
+    pub test_plan: String,
+    /// Stops each user after running all tasks VALUE times
+    #[options(no_short, meta = "VALUE")]
+    pub iterations: Option<usize>,
+    /// Stops after making VALUE requests
+    #[options(no_short, meta = "VALUE")]
+    pub max_requests: Option<usize>,
     /// Sets log level (-g, -gg, etc)
     #[options(short = "g", count)]
     pub log_level: u8,
@@ -1918,6 +3917,39 @@ pub struct GooseConfiguration {
     /// Sets debug log format (json, raw)
     #[options(no_short, default = "json", meta = "FORMAT")]
     pub debug_format: String,
+    /// Records exact times with VALUE (1-5) significant digits
+    #[options(no_short, meta = "VALUE")]
+    pub histogram_digits: Option<u8>,
+    /// Sets percentiles to display (ie 50,90,99.9)
+    #[options(no_short, meta = "LIST")]
+    pub percentiles: String,
+    /// Records metrics history every TIME (ie 5s)
+    #[options(no_short, meta = "TIME")]
+    pub history_interval: String,
+    /// Sets metrics history file name
+    #[options(no_short, meta = "NAME")]
+    pub history_file: String,
+    /// Sets metrics history format (csv, json)
+    #[options(no_short, default = "json", meta = "FORMAT")]
+    pub history_format: String,
+    /// Sets HTML report file name
+    #[options(no_short, meta = "NAME")]
+    pub report_file: String,
+    /// Sets final metrics summary file name
+    #[options(no_short, meta = "NAME")]
+    pub summary_file: String,
+    /// Sets final metrics summary format (csv, json)
+    #[options(no_short, default = "json", meta = "FORMAT")]
+    pub summary_format: String,
+    /// Sets InfluxDB line protocol file name
+    #[options(no_short, meta = "NAME")]
+    pub influxdb_file: String,
+    /// Posts InfluxDB line protocol to URL
+    #[options(no_short, meta = "URL")]
+    pub influxdb_url: String,
+    /// Fails if THRESHOLD isn't met (ie "error_rate < 1%")
+    #[options(no_short, meta = "THRESHOLD")]
+    pub threshold: Vec<String>,
     // Add a blank line and then an Advanced: header after this option
     #[options(no_short, help = "Tracks additional status code metrics\n\nAdvanced:")]
     pub status_codes: bool,
@@ -1925,6 +3957,54 @@ pub struct GooseConfiguration {
     /// Sets maximum requests per second
     #[options(no_short, meta = "VALUE")]
     pub throttle_requests: Option<usize>,
+    /// Sets requests,time throttle steps (ie 50,2m;200,5m)
+    #[options(no_short, meta = "SCHEDULE")]
+    pub throttle_schedule: String,
+    /// Sets maximum throttled requests in a burst
+    #[options(no_short, meta = "VALUE")]
+    pub throttle_burst: Option<usize>,
+    /// Starts task set iterations at a per-second rate
+    #[options(no_short, meta = "RATE")]
+    pub arrival_rate: Option<usize>,
+    /// Sets worker threads (default: number of CPUs)
+    #[options(no_short, meta = "VALUE")]
+    pub threads: Option<usize>,
+    /// Enables controller on host (default: 127.0.0.1)
+    #[options(no_short, meta = "HOST")]
+    pub controller_host: String,
+    /// Enables controller on port (default: 5116)
+    #[options(no_short, meta = "PORT")]
+    pub controller_port: Option<u16>,
+    /// Enables WebSocket controller on host (default: 127.0.0.1)
+    #[options(no_short, meta = "HOST")]
+    pub websocket_host: String,
+    /// Enables WebSocket controller on port (default: 5117)
+    #[options(no_short, meta = "PORT")]
+    pub websocket_port: Option<u16>,
+    /// Enables web UI on host (default: 127.0.0.1)
+    #[options(no_short, meta = "HOST")]
+    pub webui_host: String,
+    /// Enables web UI on port (default: 5118)
+    #[options(no_short, meta = "PORT")]
+    pub webui_port: Option<u16>,
+    /// Enables Prometheus metrics on host (default: 127.0.0.1)
+    #[options(no_short, meta = "HOST")]
+    pub prometheus_host: String,
+    /// Enables Prometheus metrics on port (default: 5119)
+    #[options(no_short, meta = "PORT")]
+    pub prometheus_port: Option<u16>,
+    /// Streams metrics to StatsD at HOST:PORT
+    #[options(no_short, meta = "ADDR")]
+    pub statsd_address: String,
+    /// Streams metrics to Graphite at HOST:PORT
+    #[options(no_short, meta = "ADDR")]
+    pub graphite_address: String,
+    /// Sets streamed metrics prefix (default: goose)
+    #[options(no_short, meta = "PREFIX")]
+    pub stream_prefix: String,
+    /// Streams aggregated metrics every TIME (ie 10s)
+    #[options(no_short, meta = "TIME")]
+    pub stream_interval: String,
     #[options(
         no_short,
         help = "Follows base_url redirect with subsequent requests\n\nGaggle:"
@@ -2187,4 +4267,34 @@ mod test {
         assert_eq!(is_valid_host("http:///example.com").is_ok(), true);
         assert_eq!(is_valid_host("http:// example.com").is_ok(), false);
     }
+
+    #[test]
+    fn websocket_address() {
+        let address = |args: &[&str]| {
+            let config = GooseConfiguration::parse_args_default(args).unwrap();
+            GooseAttack::initialize_with_config(config).websocket_address()
+        };
+        assert_eq!(address(&[]), None);
+        assert_eq!(
+            address(&["--websocket-port", "5000"]),
+            Some("127.0.0.1:5000".to_string())
+        );
+        // The WebSocket is enabled for the web UI, by default on the same host.
+        assert_eq!(
+            address(&["--webui-port", "5000"]),
+            Some("127.0.0.1:5117".to_string())
+        );
+        assert_eq!(
+            address(&["--webui-host", "0.0.0.0"]),
+            Some("0.0.0.0:5117".to_string())
+        );
+        assert_eq!(
+            address(&["--webui-host", "0.0.0.0", "--websocket-port", "5000"]),
+            Some("0.0.0.0:5000".to_string())
+        );
+        assert_eq!(
+            address(&["--webui-host", "0.0.0.0", "--websocket-host", "10.0.0.1"]),
+            Some("10.0.0.1:5117".to_string())
+        );
+    }
 }
@@ -7,7 +7,9 @@ use std::sync::Arc;
 use std::{thread, time};
 
 use crate::goose::GooseRequest;
-use crate::metrics::{self, GooseRequestMetrics, GooseTaskMetric, GooseTaskMetrics};
+use crate::metrics::{
+    self, GooseFailureMetrics, GooseRequestMetrics, GooseTaskMetric, GooseTaskMetrics,
+};
 use crate::util;
 use crate::worker::GaggleMetrics;
 use crate::{GooseAttack, GooseConfiguration, GooseUserCommand};
@@ -22,10 +24,6 @@ pub struct GooseUserInitializer {
     pub task_sets_index: usize,
     /// The base_url for this user thread.
     pub base_url: String,
-    /// Minimum amount of time to sleep after running a task.
-    pub min_wait: usize,
-    /// Maximum amount of time to sleep after running a task.
-    pub max_wait: usize,
     /// A local copy of the global GooseConfiguration.
     pub config: GooseConfiguration,
     /// Numerical identifier for worker.
@@ -75,6 +73,8 @@ fn merge_tasks_from_worker(
     let mut merged_task = parent_task.clone();
     // Iterate over user times, and merge into global time
     merged_task.times = metrics::merge_times(merged_task.times, user_task.times.clone());
+    // Merge exact times, if HDR histograms are enabled.
+    merged_task.histogram = metrics::merge_histograms(merged_task.histogram, &user_task.histogram);
     // Increment total task time counter.
     merged_task.total_time += &user_task.total_time;
     // Increment count of how many task counters we've seen.
@@ -103,6 +103,9 @@ fn merge_requests_from_worker(
         merged_request.response_times,
         user_request.response_times.clone(),
     );
+    // Merge exact response times, if HDR histograms are enabled.
+    merged_request.histogram =
+        metrics::merge_histograms(merged_request.histogram, &user_request.histogram);
     // Increment total response time counter.
     merged_request.total_response_time += &user_request.total_response_time;
     // Increment count of how many response counters we've seen.
@@ -196,6 +199,20 @@ fn merge_request_metrics(goose_attack: &mut GooseAttack, requests: GooseRequestM
     }
 }
 
+/// Helper to merge in failures from Worker.
+fn merge_failure_metrics(goose_attack: &mut GooseAttack, failures: GooseFailureMetrics) {
+    for (failure_key, failure) in failures {
+        trace!("failure_key: {:?}", failure_key);
+        match goose_attack.metrics.failures.get_mut(&failure_key) {
+            Some(parent_failure) => parent_failure.occurrences += failure.occurrences,
+            // First time seeing this failure, simply insert it.
+            None => {
+                goose_attack.metrics.failures.insert(failure_key, failure);
+            }
+        }
+    }
+}
+
 /// Helper to merge in task metrics from Worker.
 fn merge_task_metrics(goose_attack: &mut GooseAttack, tasks: GooseTaskMetrics) {
     for task_set in tasks {
@@ -401,8 +418,6 @@ pub async fn manager_main(mut goose_attack: GooseAttack) -> GooseAttack {
                             users.push(GooseUserInitializer {
                                 task_sets_index: user.task_sets_index,
                                 base_url: user.base_url.read().await.to_string(),
-                                min_wait: user.min_wait,
-                                max_wait: user.max_wait,
                                 config: user.config.clone(),
                                 worker_id: workers.len(),
                             });
@@ -459,6 +474,10 @@ pub async fn manager_main(mut goose_attack: GooseAttack) -> GooseAttack {
                             GaggleMetrics::Tasks(tasks) => {
                                 merge_task_metrics(&mut goose_attack, tasks)
                             }
+                            // Merge in failures from Worker.
+                            GaggleMetrics::Failures(failures) => {
+                                merge_failure_metrics(&mut goose_attack, failures)
+                            }
                             // Ignore Worker heartbeats.
                             GaggleMetrics::WorkerInit(_) => (),
                         }
@@ -499,6 +518,8 @@ pub async fn manager_main(mut goose_attack: GooseAttack) -> GooseAttack {
             }
         }
     }
+    // Only display percentile once the load test is finished.
+    goose_attack.metrics.display_percentile = true;
     goose_attack
 }
 
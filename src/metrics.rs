@@ -1,12 +1,19 @@
+use hdrhistogram::Histogram;
 use itertools::Itertools;
 use num_format::{Locale, ToFormattedString};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
 use std::collections::{BTreeMap, HashMap};
-use std::{f32, fmt};
+use std::convert::TryFrom;
+use std::{f32, fmt, time};
 
-use crate::goose::{GooseRawRequest, GooseRequest, GooseTaskSet};
+use crate::goose::{GooseMethod, GooseRawRequest, GooseRequest, GooseTaskSet};
+use crate::threshold::GooseThresholdVerdict;
 use crate::util;
-use crate::GooseConfiguration;
+use crate::{GooseConfiguration, GooseError};
+
+/// The percentiles displayed in the summary unless configured with `--percentiles`.
+pub const DEFAULT_PERCENTILES: [f32; 6] = [50.0, 75.0, 98.0, 99.0, 99.9, 99.99];
 
 /// Each GooseUser thread pushes these metrics to the parent for aggregation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +28,54 @@ pub type GooseRequestMetrics = HashMap<String, GooseRequest>;
 /// Goose optionally tracks metrics about tasks run during a load test.
 pub type GooseTaskMetrics = Vec<Vec<GooseTaskMetric>>;
 
+/// Goose optionally tracks why requests failed during a load test, keyed by method,
+/// name and error.
+pub type GooseFailureMetrics = HashMap<GooseFailureKey, GooseFailure>;
+
+/// The method, name and error a failure is grouped by.
+pub type GooseFailureKey = (GooseMethod, String, String);
+
+/// How many times a request failed with one error, for example `GET /` failing with
+/// `500 Internal Server Error`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GooseFailure {
+    /// The method of the request.
+    pub method: GooseMethod,
+    /// The name of the request.
+    pub name: String,
+    /// Why the request failed: the HTTP status, the kind of error making the request,
+    /// or the tag passed to `set_failure`.
+    pub error: String,
+    /// How many times the request failed with this error.
+    pub occurrences: usize,
+}
+
+impl GooseFailure {
+    /// The key this failure is grouped by in `GooseFailureMetrics`.
+    pub fn key(&self) -> GooseFailureKey {
+        (self.method.clone(), self.name.clone(), self.error.clone())
+    }
+}
+
+/// Serialize failures as a list, as JSON only supports strings as keys.
+fn serialize_failures<S: Serializer>(
+    failures: &GooseFailureMetrics,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(failures.values())
+}
+
+/// Deserialize a list of failures, grouping them by their keys.
+fn deserialize_failures<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<GooseFailureMetrics, D::Error> {
+    let failures = Vec::<GooseFailure>::deserialize(deserializer)?;
+    Ok(failures
+        .into_iter()
+        .map(|failure| (failure.key(), failure))
+        .collect())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GooseRawTask {
     /// How many milliseconds the load test has been running.
@@ -87,6 +142,9 @@ pub struct GooseTaskMetric {
     pub success_count: usize,
     /// Total number of times task has failed.
     pub fail_count: usize,
+    /// Optional HDR histogram of exact run-times, enabled with `--histogram-digits`.
+    #[serde(default)]
+    pub histogram: Option<GooseHistogram>,
 }
 impl GooseTaskMetric {
     pub fn new(
@@ -107,6 +165,7 @@ impl GooseTaskMetric {
             counter: 0,
             success_count: 0,
             fail_count: 0,
+            histogram: None,
         }
     }
 
@@ -158,9 +217,291 @@ impl GooseTaskMetric {
         };
         self.times.insert(rounded_time, counter);
         debug!("incremented {} counter: {}", rounded_time, counter);
+
+        // Also record the exact time if HDR histograms are enabled.
+        if let Some(histogram) = self.histogram.as_mut() {
+            histogram.record(time);
+        }
+    }
+}
+
+/// Exact response times or task run-times recorded in an HDR histogram, enabled with
+/// `--histogram-digits`. Unlike the rounded times, percentiles calculated from a
+/// histogram are accurate to the configured number of significant digits, including
+/// when histograms from many Workers are merged together.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "GooseHistogramData", try_from = "GooseHistogramData")]
+pub struct GooseHistogram {
+    histogram: Histogram<u64>,
+}
+impl GooseHistogram {
+    /// Create a new, empty histogram with between 1 and 5 significant digits.
+    pub fn new(significant_digits: u8) -> Result<Self, String> {
+        match Histogram::new(significant_digits) {
+            Ok(histogram) => Ok(GooseHistogram { histogram }),
+            Err(e) => Err(format!(
+                "invalid significant digits {}: {:?}",
+                significant_digits, e
+            )),
+        }
+    }
+
+    /// Record one time, in milliseconds.
+    pub fn record(&mut self, time: u64) {
+        if let Err(e) = self.histogram.record(time) {
+            warn!("failed to record {} in histogram: {:?}", time, e);
+        }
+    }
+
+    /// Merge another histogram into this one.
+    pub fn merge(&mut self, other: &GooseHistogram) {
+        if let Err(e) = self.histogram.add(&other.histogram) {
+            warn!("failed to merge histograms: {:?}", e);
+        }
+    }
+
+    /// The number of times recorded.
+    pub fn len(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    /// Returns true if no times have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.histogram.is_empty()
+    }
+
+    /// The number of significant digits this histogram was created with.
+    pub fn significant_digits(&self) -> u8 {
+        self.histogram.sigfig()
+    }
+
+    /// Get the time that a certain percent (between 0.0 and 1.0) of recorded times
+    /// were less than or equal to.
+    pub fn percentile(&self, percent: f32) -> usize {
+        // Avoid f32 rounding errors such as 0.999 becoming 0.99900001, which would
+        // select the next slowest time.
+        let quantile = (f64::from(percent) * 1_000_000.0).round() / 1_000_000.0;
+        self.histogram.value_at_quantile(quantile) as usize
+    }
+}
+impl Eq for GooseHistogram {}
+
+/// The serialized form of a GooseHistogram: how many times each distinct value was
+/// recorded, allowing histograms to be sent from Workers to the Manager.
+#[derive(Serialize, Deserialize)]
+struct GooseHistogramData {
+    significant_digits: u8,
+    counts: Vec<(u64, u64)>,
+}
+impl From<GooseHistogram> for GooseHistogramData {
+    fn from(histogram: GooseHistogram) -> Self {
+        GooseHistogramData {
+            significant_digits: histogram.significant_digits(),
+            counts: histogram
+                .histogram
+                .iter_recorded()
+                .map(|value| (value.value_iterated_to(), value.count_at_value()))
+                .collect(),
+        }
+    }
+}
+impl TryFrom<GooseHistogramData> for GooseHistogram {
+    type Error = String;
+
+    fn try_from(data: GooseHistogramData) -> Result<Self, Self::Error> {
+        let mut histogram = GooseHistogram::new(data.significant_digits)?;
+        for (value, count) in data.counts {
+            if let Err(e) = histogram.histogram.record_n(value, count) {
+                return Err(format!("invalid histogram value {}: {:?}", value, e));
+            }
+        }
+        Ok(histogram)
+    }
+}
+
+/// Metrics recorded over one `--history-interval` of a load test.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GooseHistorySnapshot {
+    /// How many milliseconds the load test had been running at the end of this interval.
+    pub elapsed: u64,
+    /// How many milliseconds this interval lasted.
+    pub duration: u64,
+    /// Number of users running at the end of this interval.
+    pub users: usize,
+    /// Metrics about each request made during this interval, sorted by method and name.
+    pub requests: Vec<GooseRequestSnapshot>,
+}
+impl GooseHistorySnapshot {
+    /// Summarize the requests made during one interval.
+    pub fn new(
+        elapsed: time::Duration,
+        duration: time::Duration,
+        users: usize,
+        requests: &GooseRequestMetrics,
+        percentiles: &[f32],
+    ) -> Self {
+        let seconds = duration.as_secs_f32();
+        let per_second = |count: usize| {
+            if seconds > 0.0 {
+                count as f32 / seconds
+            } else {
+                0.0
+            }
+        };
+        let requests = requests
+            .iter()
+            .sorted_by(|a, b| a.0.cmp(b.0))
+            .map(|(_, request)| {
+                let total_count = request.success_count + request.fail_count;
+                GooseRequestSnapshot {
+                    method: request.method.clone(),
+                    name: request.path.clone(),
+                    requests: total_count,
+                    fails: request.fail_count,
+                    requests_per_second: per_second(total_count),
+                    fails_per_second: per_second(request.fail_count),
+                    mean_response_time: match request.response_time_counter {
+                        0 => 0.0,
+                        counter => request.total_response_time as f32 / counter as f32,
+                    },
+                    percentiles: percentiles
+                        .iter()
+                        .map(|percentile| {
+                            (
+                                *percentile,
+                                calculate_percentile(
+                                    &request.response_times,
+                                    &request.histogram,
+                                    request.response_time_counter,
+                                    request.min_response_time,
+                                    request.max_response_time,
+                                    percentile / 100.0,
+                                ),
+                            )
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        GooseHistorySnapshot {
+            elapsed: elapsed.as_millis() as u64,
+            duration: duration.as_millis() as u64,
+            users,
+            requests,
+        }
     }
 }
 
+/// Metrics about one request, for example `/index`-`GET`, during one interval.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GooseRequestSnapshot {
+    /// The method of the request.
+    pub method: GooseMethod,
+    /// The name of the request.
+    pub name: String,
+    /// Number of requests made during this interval.
+    pub requests: usize,
+    /// Number of requests that failed during this interval.
+    pub fails: usize,
+    /// Requests per second during this interval.
+    pub requests_per_second: f32,
+    /// Failures per second during this interval.
+    pub fails_per_second: f32,
+    /// Mean response time during this interval, in milliseconds.
+    pub mean_response_time: f32,
+    /// Pairs of percentile and the slowest response time within that percentile, in
+    /// milliseconds, for each of the configured `--percentiles`.
+    pub percentiles: Vec<(f32, usize)>,
+}
+
+/// The final metrics of a load test in a stable schema, exported with `--summary-file`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GooseMetricsSummary {
+    /// A hash of the load test, useful to verify if different metrics are from
+    /// the same load test.
+    pub hash: u64,
+    /// How many seconds the load test ran.
+    pub duration: usize,
+    /// Total number of users simulated during this load test.
+    pub users: usize,
+    /// Number of task set iterations started when running at an arrival rate.
+    pub iterations: usize,
+    /// Number of task set iterations dropped when running at an arrival rate.
+    pub dropped_iterations: usize,
+    /// Final metrics about each request, sorted by method and name.
+    pub requests: Vec<GooseRequestSummary>,
+    /// Final metrics about each task, in the order they were registered.
+    pub tasks: Vec<GooseTaskSummary>,
+    /// Why requests failed, most frequent first.
+    #[serde(default)]
+    pub failures: Vec<GooseFailure>,
+    /// Whether each threshold passed, if any thresholds were set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<GooseThresholdVerdict>,
+}
+
+/// The final metrics of one request, for example `/index`-`GET`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GooseRequestSummary {
+    /// The method of the request.
+    pub method: GooseMethod,
+    /// The name of the request.
+    pub name: String,
+    /// Number of requests made.
+    pub requests: usize,
+    /// Number of requests that failed.
+    pub fails: usize,
+    /// Requests per second.
+    pub requests_per_second: f32,
+    /// Failures per second.
+    pub fails_per_second: f32,
+    /// Fastest response time, in milliseconds.
+    pub min_response_time: usize,
+    /// Mean response time, in milliseconds.
+    pub mean_response_time: f32,
+    /// Median response time, in milliseconds.
+    pub median_response_time: usize,
+    /// Slowest response time, in milliseconds.
+    pub max_response_time: usize,
+    /// Pairs of percentile and the slowest response time within that percentile, in
+    /// milliseconds, for each of the configured `--percentiles`.
+    pub percentiles: Vec<(f32, usize)>,
+    /// How many times each status code was returned.
+    pub status_codes: BTreeMap<u16, usize>,
+}
+
+/// The final metrics of one task.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GooseTaskSummary {
+    /// An index into GooseAttack.task_sets, indicating which task set this is.
+    pub taskset_index: usize,
+    /// The task set name.
+    pub taskset_name: String,
+    /// An index into GooseTaskSet.task, indicating which task this is.
+    pub task_index: usize,
+    /// The optional name of the task.
+    pub task_name: String,
+    /// Number of times the task ran.
+    pub runs: usize,
+    /// Number of times the task failed.
+    pub fails: usize,
+    /// Task runs per second.
+    pub runs_per_second: f32,
+    /// Task failures per second.
+    pub fails_per_second: f32,
+    /// Fastest run-time, in milliseconds.
+    pub min_time: usize,
+    /// Mean run-time, in milliseconds.
+    pub mean_time: f32,
+    /// Median run-time, in milliseconds.
+    pub median_time: usize,
+    /// Slowest run-time, in milliseconds.
+    pub max_time: usize,
+    /// Pairs of percentile and the slowest run-time within that percentile, in
+    /// milliseconds, for each of the configured `--percentiles`.
+    pub percentiles: Vec<(f32, usize)>,
+}
+
 /// Metrics collected during a Goose load test.
 ///
 /// # Example
@@ -187,7 +528,7 @@ impl GooseTaskMetric {
 ///     Ok(())
 /// }
 /// ```
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct GooseMetrics {
     /// A hash of the load test, useful to verify if different metrics are from
     /// the same load test.
@@ -196,10 +537,30 @@ pub struct GooseMetrics {
     pub duration: usize,
     /// Total number of users simulated during this load test.
     pub users: usize,
+    /// Number of users currently running.
+    pub running_users: usize,
     /// Goose request metrics.
     pub requests: GooseRequestMetrics,
     /// Goose task metrics.
     pub tasks: GooseTaskMetrics,
+    /// Why requests failed.
+    #[serde(
+        default,
+        serialize_with = "serialize_failures",
+        deserialize_with = "deserialize_failures"
+    )]
+    pub failures: GooseFailureMetrics,
+    /// Number of task set iterations started when running at an arrival rate.
+    pub iterations: usize,
+    /// Number of task set iterations dropped when running at an arrival rate, because
+    /// all users were still busy.
+    pub dropped_iterations: usize,
+    /// Metrics recorded at the end of each `--history-interval`, if enabled. Unlike the
+    /// other metrics, the history isn't reset after all users have started.
+    pub history: Vec<GooseHistorySnapshot>,
+    /// Percentiles of response times to display, from 0 to 100. If empty,
+    /// `DEFAULT_PERCENTILES` are displayed.
+    pub percentiles: Vec<f32>,
     /// Flag indicating whether or not to display percentile. Because we're deriving Default,
     /// this defaults to false.
     pub display_percentile: bool,
@@ -209,6 +570,9 @@ pub struct GooseMetrics {
     /// Flag indicating whether or not to display metrics, set to false on Workers. This
     /// defaults to false because we're deriving Default.
     pub display_metrics: bool,
+    /// Whether each threshold passed, if any thresholds were set.
+    #[serde(default)]
+    pub thresholds: Option<GooseThresholdVerdict>,
 }
 
 impl GooseMetrics {
@@ -222,12 +586,14 @@ impl GooseMetrics {
             for task_set in task_sets {
                 let mut task_vector = Vec::new();
                 for task in &task_set.tasks {
-                    task_vector.push(GooseTaskMetric::new(
+                    let mut task_metric = GooseTaskMetric::new(
                         task_set.task_sets_index,
                         &task_set.name,
                         task.tasks_index,
                         &task.name,
-                    ));
+                    );
+                    task_metric.histogram = new_histogram(config);
+                    task_vector.push(task_metric);
                 }
                 self.tasks.push(task_vector);
             }
@@ -389,6 +755,58 @@ impl GooseMetrics {
         Ok(())
     }
 
+    /// Optionally prepares a table of iterations started and dropped when running at
+    /// an arrival rate.
+    pub fn fmt_iterations(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // If there's nothing to display, exit immediately.
+        let total_count = self.iterations + self.dropped_iterations;
+        if total_count == 0 || !self.display_metrics {
+            return Ok(());
+        }
+
+        writeln!(
+            fmt,
+            "\n=== ITERATION METRICS ===\n------------------------------------------------------------------------------ "
+        )?;
+        writeln!(
+            fmt,
+            " {:<23} | {:<14} | {:<14} | {:<6} | {:<5}",
+            "Name", "# started", "# dropped", "iter/s", "drop/s"
+        )?;
+        writeln!(
+            fmt,
+            " ----------------------------------------------------------------------------- "
+        )?;
+        let drop_percent = self.dropped_iterations as f32 / total_count as f32 * 100.0;
+        let (iter_s, drop_s) =
+            per_second_calculations(self.duration, self.iterations, self.dropped_iterations);
+        // Compress 100.0 and 0.0 to 100 and 0 respectively to save width.
+        let dropped = if drop_percent as usize == 100 || drop_percent as usize == 0 {
+            format!(
+                "{} ({}%)",
+                self.dropped_iterations.to_formatted_string(&Locale::en),
+                drop_percent as usize
+            )
+        } else {
+            format!(
+                "{} ({:.1}%)",
+                self.dropped_iterations.to_formatted_string(&Locale::en),
+                drop_percent
+            )
+        };
+        writeln!(
+            fmt,
+            " {:<23} | {:<14} | {:<14} | {:<6} | {:<5}",
+            "Iterations",
+            self.iterations.to_formatted_string(&Locale::en),
+            dropped,
+            iter_s,
+            drop_s,
+        )?;
+
+        Ok(())
+    }
+
     /// Optionally prepares a table of tasks.
     pub fn fmt_tasks(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         // If there's nothing to display, exit immediately.
@@ -533,6 +951,7 @@ impl GooseMetrics {
         }
 
         let mut aggregate_task_times: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut aggregate_task_histogram: Option<GooseHistogram> = None;
         let mut aggregate_total_task_time: usize = 0;
         let mut aggregate_task_time_counter: usize = 0;
         let mut aggregate_min_task_time: usize = 0;
@@ -570,6 +989,8 @@ impl GooseMetrics {
 
                 // Iterate over user task times, and merge into global task times.
                 aggregate_task_times = merge_times(aggregate_task_times, task.times.clone());
+                aggregate_task_histogram =
+                    merge_histograms(aggregate_task_histogram, &task.histogram);
 
                 // Increment total task time counter.
                 aggregate_total_task_time += &task.total_time;
@@ -598,7 +1019,13 @@ impl GooseMetrics {
                     average,
                     task.min_time,
                     task.max_time,
-                    util::median(&task.times, task.counter, task.min_time, task.max_time),
+                    calculate_median(
+                        &task.times,
+                        &task.histogram,
+                        task.counter,
+                        task.min_time,
+                        task.max_time
+                    ),
                 )?;
             }
         }
@@ -617,8 +1044,9 @@ impl GooseMetrics {
                 aggregate_total_task_time / aggregate_task_time_counter,
                 aggregate_min_task_time,
                 aggregate_max_task_time,
-                util::median(
+                calculate_median(
                     &aggregate_task_times,
+                    &aggregate_task_histogram,
                     aggregate_task_time_counter,
                     aggregate_min_task_time,
                     aggregate_max_task_time
@@ -637,6 +1065,7 @@ impl GooseMetrics {
         }
 
         let mut aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut aggregate_response_histogram: Option<GooseHistogram> = None;
         let mut aggregate_total_response_time: usize = 0;
         let mut aggregate_response_time_counter: usize = 0;
         let mut aggregate_min_response_time: usize = 0;
@@ -658,6 +1087,8 @@ impl GooseMetrics {
             // Iterate over user response times, and merge into global response times.
             aggregate_response_times =
                 merge_times(aggregate_response_times, request.response_times.clone());
+            aggregate_response_histogram =
+                merge_histograms(aggregate_response_histogram, &request.histogram);
 
             // Increment total response time counter.
             aggregate_total_response_time += &request.total_response_time;
@@ -680,8 +1111,9 @@ impl GooseMetrics {
                 request.total_response_time / request.response_time_counter,
                 request.min_response_time,
                 request.max_response_time,
-                util::median(
+                calculate_median(
                     &request.response_times,
+                    &request.histogram,
                     request.response_time_counter,
                     request.min_response_time,
                     request.max_response_time
@@ -703,8 +1135,9 @@ impl GooseMetrics {
                 aggregate_total_response_time / aggregate_response_time_counter,
                 aggregate_min_response_time,
                 aggregate_max_response_time,
-                util::median(
+                calculate_median(
                     &aggregate_response_times,
+                    &aggregate_response_histogram,
                     aggregate_response_time_counter,
                     aggregate_min_response_time,
                     aggregate_max_response_time
@@ -722,7 +1155,9 @@ impl GooseMetrics {
             return Ok(());
         }
 
+        let percentiles = self.percentiles();
         let mut aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut aggregate_response_histogram: Option<GooseHistogram> = None;
         let mut aggregate_total_response_time: usize = 0;
         let mut aggregate_response_time_counter: usize = 0;
         let mut aggregate_min_response_time: usize = 0;
@@ -739,11 +1174,8 @@ impl GooseMetrics {
             fmt,
             " ------------------------------------------------------------------------------"
         )?;
-        writeln!(
-            fmt,
-            " {:<23} | {:<6} | {:<6} | {:<6} | {:<6} | {:<6} | {:6}",
-            "Name", "50%", "75%", "98%", "99%", "99.9%", "99.99%"
-        )?;
+        let header: Vec<String> = percentiles.iter().map(|p| format!("{}%", p)).collect();
+        writeln!(fmt, " {:<23} | {}", "Name", percentile_columns(&header))?;
         writeln!(
             fmt,
             " ----------------------------------------------------------------------------- "
@@ -752,6 +1184,8 @@ impl GooseMetrics {
             // Iterate over user response times, and merge into global response times.
             aggregate_response_times =
                 merge_times(aggregate_response_times, request.response_times.clone());
+            aggregate_response_histogram =
+                merge_histograms(aggregate_response_histogram, &request.histogram);
 
             // Increment total response time counter.
             aggregate_total_response_time += &request.total_response_time;
@@ -766,109 +1200,363 @@ impl GooseMetrics {
             // If user had new slowest response time, update global slowest resposne time.
             aggregate_max_response_time =
                 update_max_time(aggregate_max_response_time, request.max_response_time);
-            // Sort response times so we can calculate a mean.
+
+            let columns: Vec<String> = percentiles
+                .iter()
+                .map(|percentile| {
+                    calculate_percentile(
+                        &request.response_times,
+                        &request.histogram,
+                        request.response_time_counter,
+                        request.min_response_time,
+                        request.max_response_time,
+                        percentile / 100.0,
+                    )
+                    .to_string()
+                })
+                .collect();
             writeln!(
                 fmt,
-                " {:<23} | {:<6.2} | {:<6.2} | {:<6.2} | {:<6.2} | {:<6.2} | {:6.2}",
+                " {:<23} | {}",
                 util::truncate_string(&request_key, 23),
-                calculate_response_time_percentile(
-                    &request.response_times,
-                    request.response_time_counter,
-                    request.min_response_time,
-                    request.max_response_time,
-                    0.5
-                ),
-                calculate_response_time_percentile(
-                    &request.response_times,
-                    request.response_time_counter,
-                    request.min_response_time,
-                    request.max_response_time,
-                    0.75
-                ),
-                calculate_response_time_percentile(
-                    &request.response_times,
-                    request.response_time_counter,
-                    request.min_response_time,
-                    request.max_response_time,
-                    0.98
-                ),
-                calculate_response_time_percentile(
-                    &request.response_times,
-                    request.response_time_counter,
-                    request.min_response_time,
-                    request.max_response_time,
-                    0.99
-                ),
-                calculate_response_time_percentile(
-                    &request.response_times,
-                    request.response_time_counter,
-                    request.min_response_time,
-                    request.max_response_time,
-                    0.999
-                ),
-                calculate_response_time_percentile(
-                    &request.response_times,
-                    request.response_time_counter,
-                    request.min_response_time,
-                    request.max_response_time,
-                    0.999
-                ),
+                percentile_columns(&columns),
             )?;
         }
         if self.requests.len() > 1 {
             writeln!(
                 fmt,
-                " ------------------------+--------+--------+--------+--------+--------+------- "
+                " ------------------------+{}- ",
+                vec!["--------"; percentiles.len()].join("+")
             )?;
+            let columns: Vec<String> = percentiles
+                .iter()
+                .map(|percentile| {
+                    calculate_percentile(
+                        &aggregate_response_times,
+                        &aggregate_response_histogram,
+                        aggregate_response_time_counter,
+                        aggregate_min_response_time,
+                        aggregate_max_response_time,
+                        percentile / 100.0,
+                    )
+                    .to_string()
+                })
+                .collect();
             writeln!(
                 fmt,
-                " {:<23} | {:<6.2} | {:<6.2} | {:<6.2} | {:<6.2} | {:<6.2} | {:6.2}",
+                " {:<23} | {}",
                 "Aggregated",
-                calculate_response_time_percentile(
-                    &aggregate_response_times,
-                    aggregate_response_time_counter,
-                    aggregate_min_response_time,
-                    aggregate_max_response_time,
-                    0.5
-                ),
-                calculate_response_time_percentile(
-                    &aggregate_response_times,
-                    aggregate_response_time_counter,
-                    aggregate_min_response_time,
-                    aggregate_max_response_time,
-                    0.75
-                ),
-                calculate_response_time_percentile(
-                    &aggregate_response_times,
-                    aggregate_response_time_counter,
-                    aggregate_min_response_time,
-                    aggregate_max_response_time,
-                    0.98
-                ),
-                calculate_response_time_percentile(
-                    &aggregate_response_times,
-                    aggregate_response_time_counter,
-                    aggregate_min_response_time,
-                    aggregate_max_response_time,
-                    0.99
-                ),
-                calculate_response_time_percentile(
-                    &aggregate_response_times,
-                    aggregate_response_time_counter,
-                    aggregate_min_response_time,
-                    aggregate_max_response_time,
-                    0.999
+                percentile_columns(&columns)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Export the metrics history as CSV, one row per request per interval. Intervals
+    /// without requests have a single row with an empty method and name.
+    pub fn history_csv(&self) -> String {
+        // No quotes needed in header.
+        let mut csv = format!(
+            "elapsed,duration,users,method,name,requests,fails,requests_per_second,fails_per_second,mean_response_time,{}\n",
+            self.percentiles()
+                .iter()
+                .map(|percentile| format!("p{}", percentile))
+                .join(",")
+        );
+        for snapshot in &self.history {
+            // Snapshots of intervals without requests still record the running users.
+            if snapshot.requests.is_empty() {
+                csv.push_str(&format!(
+                    "{},{},{},,\"\",0,0,0.00,0.00,0.00,{}\n",
+                    snapshot.elapsed,
+                    snapshot.duration,
+                    snapshot.users,
+                    self.percentiles().iter().map(|_| 0).join(","),
+                ));
+            }
+            for request in &snapshot.requests {
+                // Put quotes around name as it's a string.
+                csv.push_str(&format!(
+                    "{},{},{},{:?},\"{}\",{},{},{:.2},{:.2},{:.2},{}\n",
+                    snapshot.elapsed,
+                    snapshot.duration,
+                    snapshot.users,
+                    request.method,
+                    request.name.replace('"', "\"\""),
+                    request.requests,
+                    request.fails,
+                    request.requests_per_second,
+                    request.fails_per_second,
+                    request.mean_response_time,
+                    request
+                        .percentiles
+                        .iter()
+                        .map(|(_, response_time)| response_time)
+                        .join(","),
+                ));
+            }
+        }
+        csv
+    }
+
+    /// Export the metrics history as a JSON array of snapshots.
+    pub fn history_json(&self) -> String {
+        json!(self.history).to_string()
+    }
+
+    /// Summarize the final metrics of each request and task.
+    pub fn summary(&self) -> GooseMetricsSummary {
+        let per_second = |count: usize| {
+            if self.duration > 0 {
+                count as f32 / self.duration as f32
+            } else {
+                0.0
+            }
+        };
+        let mean = |total: usize, counter: usize| match counter {
+            0 => 0.0,
+            counter => total as f32 / counter as f32,
+        };
+        let percentiles = |times, histogram, total, min, max| {
+            self.percentiles()
+                .iter()
+                .map(|percentile| {
+                    (
+                        *percentile,
+                        calculate_percentile(times, histogram, total, min, max, percentile / 100.0),
+                    )
+                })
+                .collect()
+        };
+
+        let requests = self
+            .requests
+            .iter()
+            .sorted_by(|a, b| a.0.cmp(b.0))
+            .map(|(_, request)| {
+                let total_count = request.success_count + request.fail_count;
+                GooseRequestSummary {
+                    method: request.method.clone(),
+                    name: request.path.clone(),
+                    requests: total_count,
+                    fails: request.fail_count,
+                    requests_per_second: per_second(total_count),
+                    fails_per_second: per_second(request.fail_count),
+                    min_response_time: request.min_response_time,
+                    mean_response_time: mean(
+                        request.total_response_time,
+                        request.response_time_counter,
+                    ),
+                    median_response_time: calculate_median(
+                        &request.response_times,
+                        &request.histogram,
+                        request.response_time_counter,
+                        request.min_response_time,
+                        request.max_response_time,
+                    ),
+                    max_response_time: request.max_response_time,
+                    percentiles: percentiles(
+                        &request.response_times,
+                        &request.histogram,
+                        request.response_time_counter,
+                        request.min_response_time,
+                        request.max_response_time,
+                    ),
+                    status_codes: request
+                        .status_code_counts
+                        .iter()
+                        .map(|(status_code, count)| (*status_code, *count))
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let tasks = self
+            .tasks
+            .iter()
+            .flatten()
+            .map(|task| GooseTaskSummary {
+                taskset_index: task.taskset_index,
+                taskset_name: task.taskset_name.clone(),
+                task_index: task.task_index,
+                task_name: task.task_name.clone(),
+                runs: task.counter,
+                fails: task.fail_count,
+                runs_per_second: per_second(task.counter),
+                fails_per_second: per_second(task.fail_count),
+                min_time: task.min_time,
+                mean_time: mean(task.total_time, task.counter),
+                median_time: calculate_median(
+                    &task.times,
+                    &task.histogram,
+                    task.counter,
+                    task.min_time,
+                    task.max_time,
                 ),
-                calculate_response_time_percentile(
-                    &aggregate_response_times,
-                    aggregate_response_time_counter,
-                    aggregate_min_response_time,
-                    aggregate_max_response_time,
-                    0.9999
+                max_time: task.max_time,
+                percentiles: percentiles(
+                    &task.times,
+                    &task.histogram,
+                    task.counter,
+                    task.min_time,
+                    task.max_time,
                 ),
-            )?;
+            })
+            .collect();
+
+        GooseMetricsSummary {
+            hash: self.hash,
+            duration: self.duration,
+            users: self.users,
+            iterations: self.iterations,
+            dropped_iterations: self.dropped_iterations,
+            requests,
+            tasks,
+            failures: self.sorted_failures().into_iter().cloned().collect(),
+            thresholds: self.thresholds.clone(),
+        }
+    }
+
+    /// Why requests failed, most frequent first, then sorted by method, name and error.
+    fn sorted_failures(&self) -> Vec<&GooseFailure> {
+        self.failures
+            .iter()
+            .sorted_by(|a, b| b.1.occurrences.cmp(&a.1.occurrences).then(a.0.cmp(b.0)))
+            .map(|(_, failure)| failure)
+            .collect()
+    }
+
+    /// Export the final metrics as CSV, with one row for each request followed by one
+    /// row for each task and one row for each error requests failed with. Status codes
+    /// are listed as `code:count` pairs separated by semicolons.
+    pub fn summary_csv(&self) -> String {
+        let summary = self.summary();
+        // No quotes needed in header.
+        let mut csv = format!(
+            "type,method,name,count,fails,per_second,fails_per_second,min,mean,median,max,{},status_codes,error\n",
+            self.percentiles()
+                .iter()
+                .map(|percentile| format!("p{}", percentile))
+                .join(",")
+        );
+        for request in &summary.requests {
+            // Put quotes around name and status codes as they're strings.
+            csv.push_str(&format!(
+                "request,{:?},\"{}\",{},{},{:.2},{:.2},{},{:.2},{},{},{},\"{}\",\"\"\n",
+                request.method,
+                request.name.replace('"', "\"\""),
+                request.requests,
+                request.fails,
+                request.requests_per_second,
+                request.fails_per_second,
+                request.min_response_time,
+                request.mean_response_time,
+                request.median_response_time,
+                request.max_response_time,
+                request.percentiles.iter().map(|(_, time)| time).join(","),
+                request
+                    .status_codes
+                    .iter()
+                    .map(|(status_code, count)| format!("{}:{}", status_code, count))
+                    .join(";"),
+            ));
+        }
+        for task in &summary.tasks {
+            csv.push_str(&format!(
+                "task,,\"{}: {}\",{},{},{:.2},{:.2},{},{:.2},{},{},{},\"\",\"\"\n",
+                task.taskset_name.replace('"', "\"\""),
+                task.task_name.replace('"', "\"\""),
+                task.runs,
+                task.fails,
+                task.runs_per_second,
+                task.fails_per_second,
+                task.min_time,
+                task.mean_time,
+                task.median_time,
+                task.max_time,
+                task.percentiles.iter().map(|(_, time)| time).join(","),
+            ));
+        }
+        // Failures only have a count, leave the other columns empty.
+        let empty = ",".repeat(self.percentiles().len() + 6);
+        for failure in &summary.failures {
+            csv.push_str(&format!(
+                "failure,{:?},\"{}\",{},{},{}\"\",\"{}\"\n",
+                failure.method,
+                failure.name.replace('"', "\"\""),
+                failure.occurrences,
+                failure.occurrences,
+                empty,
+                failure.error.replace('"', "\"\""),
+            ));
+        }
+        csv
+    }
+
+    /// Export the final metrics as a JSON object.
+    pub fn summary_json(&self) -> String {
+        json!(self.summary()).to_string()
+    }
+
+    /// The percentiles displayed in the summary, configured with `--percentiles`.
+    pub fn percentiles(&self) -> &[f32] {
+        if self.percentiles.is_empty() {
+            &DEFAULT_PERCENTILES
+        } else {
+            &self.percentiles
+        }
+    }
+
+    /// Optionally prepares a table of whether each threshold passed.
+    pub fn fmt_thresholds(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // If there's nothing to display, exit immediately.
+        let verdict = match &self.thresholds {
+            Some(verdict) => verdict,
+            None => return Ok(()),
+        };
+
+        writeln!(
+            fmt,
+            "-------------------------------------------------------------------------------"
+        )?;
+        writeln!(
+            fmt,
+            " Thresholds: {}",
+            if verdict.passed { "PASSED" } else { "FAILED" }
+        )?;
+        writeln!(
+            fmt,
+            " ------------------------------------------------------------------------------"
+        )?;
+        write!(fmt, "{}", verdict)
+    }
+
+    /// Optionally prepares a table of why requests failed.
+    pub fn fmt_failures(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // If there's nothing to display, exit immediately.
+        if self.failures.is_empty() {
+            return Ok(());
         }
 
+        writeln!(
+            fmt,
+            "-------------------------------------------------------------------------------"
+        )?;
+        writeln!(fmt, " {:<23} | {:<11} | Error", "Name", "# occurred")?;
+        writeln!(
+            fmt,
+            " ----------------------------------------------------------------------------- "
+        )?;
+        for failure in self.sorted_failures() {
+            writeln!(
+                fmt,
+                " {:<23} | {:<11} | {}",
+                util::truncate_string(&format!("{:?} {}", failure.method, failure.name), 23),
+                failure.occurrences.to_formatted_string(&Locale::en),
+                failure.error,
+            )?;
+        }
         Ok(())
     }
 
@@ -953,14 +1641,17 @@ impl GooseMetrics {
 
 impl fmt::Display for GooseMetrics {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        // Formats from zero to six tables of data, depending on what data is contained
+        // Formats from zero to nine tables of data, depending on what data is contained
         // and which contained flags are set.
+        self.fmt_iterations(fmt)?;
         self.fmt_tasks(fmt)?;
         self.fmt_task_times(fmt)?;
         self.fmt_requests(fmt)?;
         self.fmt_response_times(fmt)?;
         self.fmt_percentiles(fmt)?;
-        self.fmt_status_codes(fmt)
+        self.fmt_status_codes(fmt)?;
+        self.fmt_failures(fmt)?;
+        self.fmt_thresholds(fmt)
     }
 }
 
@@ -978,6 +1669,111 @@ fn per_second_calculations(duration: usize, total: usize, fail: usize) -> (Strin
     (requests_per_second, fails_per_second)
 }
 
+/// Merge a request made by a GooseUser into per-request metrics.
+pub fn record_request(
+    requests: &mut GooseRequestMetrics,
+    raw_request: &GooseRawRequest,
+    config: &GooseConfiguration,
+) {
+    let key = format!("{:?} {}", raw_request.method, raw_request.name);
+    let request = requests.entry(key).or_insert_with(|| {
+        let mut request = GooseRequest::new(&raw_request.name, raw_request.method.clone(), 0);
+        request.histogram = new_histogram(config);
+        request
+    });
+    // Handle a metrics update. The original request may have been recorded before the
+    // metrics were last reset, or in an earlier history interval.
+    if raw_request.update {
+        if raw_request.success {
+            request.success_count += 1;
+            request.fail_count = request.fail_count.saturating_sub(1);
+        } else {
+            request.success_count = request.success_count.saturating_sub(1);
+            request.fail_count += 1;
+        }
+    }
+    // Store a new metric.
+    else {
+        request.set_response_time(raw_request.response_time);
+        if config.status_codes {
+            request.set_status_code(raw_request.status_code);
+        }
+        if raw_request.success {
+            request.success_count += 1;
+        } else {
+            request.fail_count += 1;
+        }
+    }
+}
+
+/// Count why a request made by a GooseUser failed. An update marking an earlier failed
+/// request as a success forgets why it failed.
+pub fn record_failure(failures: &mut GooseFailureMetrics, raw_request: &GooseRawRequest) {
+    if raw_request.success && !raw_request.update {
+        return;
+    }
+    let key = (
+        raw_request.method.clone(),
+        raw_request.name.clone(),
+        raw_request.error.clone(),
+    );
+    if raw_request.success {
+        // The original failure may have been recorded before the metrics were reset.
+        if let Some(failure) = failures.get_mut(&key) {
+            failure.occurrences -= 1;
+            if failure.occurrences == 0 {
+                failures.remove(&key);
+            }
+        }
+    } else {
+        failures
+            .entry(key)
+            .or_insert_with(|| GooseFailure {
+                method: raw_request.method.clone(),
+                name: raw_request.name.clone(),
+                error: raw_request.error.clone(),
+                occurrences: 0,
+            })
+            .occurrences += 1;
+    }
+}
+
+/// Align percentile columns in the percentile table.
+fn percentile_columns(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|column| format!("{:<6}", column))
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
+/// Parse a comma separated list of percentiles, such as `50,90,99.9`.
+pub fn parse_percentiles(percentiles: &str) -> Result<Vec<f32>, GooseError> {
+    let invalid = |detail: &str| GooseError::InvalidOption {
+        option: "--percentiles".to_string(),
+        value: percentiles.to_string(),
+        detail: detail.to_string(),
+    };
+
+    let mut parsed = Vec::new();
+    for percentile in percentiles.split(',') {
+        match percentile.trim().parse::<f32>() {
+            Ok(value) if value > 0.0 && value <= 100.0 => parsed.push(value),
+            Ok(_) => {
+                return Err(invalid(
+                    "The --percentiles option values must be greater than 0 and at most 100.",
+                ))
+            }
+            Err(_) => {
+                return Err(invalid(
+                    "The --percentiles option must be a comma separated list of numbers, for example 50,90,99.9.",
+                ))
+            }
+        }
+    }
+    Ok(parsed)
+}
+
 /// A helper function that merges together times.
 ///
 /// Used in `lib.rs` to merge together per-thread times, and in `metrics.rs` to
@@ -999,6 +1795,30 @@ pub fn merge_times(
     global_response_times
 }
 
+/// Create a histogram for recording exact times, if enabled with `--histogram-digits`.
+pub fn new_histogram(config: &GooseConfiguration) -> Option<GooseHistogram> {
+    // The number of significant digits is validated when the load test starts.
+    config
+        .histogram_digits
+        .and_then(|digits| GooseHistogram::new(digits).ok())
+}
+
+/// A helper function that merges together optional histograms, the equivalent of
+/// `merge_times` for exact times.
+pub fn merge_histograms(
+    global_histogram: Option<GooseHistogram>,
+    local_histogram: &Option<GooseHistogram>,
+) -> Option<GooseHistogram> {
+    match (global_histogram, local_histogram) {
+        (Some(mut global), Some(local)) => {
+            global.merge(local);
+            Some(global)
+        }
+        (None, Some(local)) => Some(local.clone()),
+        (global, None) => global,
+    }
+}
+
 // Update global minimum time based on local time.
 pub fn update_min_time(mut global_min: usize, min: usize) -> usize {
     if global_min == 0 || (min > 0 && min < global_min) {
@@ -1046,6 +1866,47 @@ fn calculate_response_time_percentile(
     0
 }
 
+/// Get the median time, from the HDR histogram if enabled, otherwise from the rounded times.
+fn calculate_median(
+    times: &BTreeMap<usize, usize>,
+    histogram: &Option<GooseHistogram>,
+    total: usize,
+    min: usize,
+    max: usize,
+) -> usize {
+    match histogram {
+        Some(_) => calculate_percentile(times, histogram, total, min, max, 0.5),
+        None => util::median(times, total, min, max),
+    }
+}
+
+/// Get the time that a certain percent of requests or tasks finished within, from the
+/// HDR histogram if enabled, otherwise from the rounded times.
+pub(crate) fn calculate_percentile(
+    times: &BTreeMap<usize, usize>,
+    histogram: &Option<GooseHistogram>,
+    total: usize,
+    min: usize,
+    max: usize,
+    percent: f32,
+) -> usize {
+    match histogram {
+        Some(histogram) if !histogram.is_empty() => {
+            // Histogram values are accurate to the configured significant digits, and
+            // may be slightly larger than the slowest time actually seen.
+            let value = histogram.percentile(percent);
+            if value > max {
+                max
+            } else if value < min {
+                min
+            } else {
+                value
+            }
+        }
+        _ => calculate_response_time_percentile(times, total, min, max, percent),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1172,4 +2033,346 @@ mod test {
         assert!(requests_per_second == "10");
         assert!(fails_per_second == "1");
     }
+
+    #[test]
+    fn histogram_percentiles() {
+        // Invalid significant digits.
+        assert!(GooseHistogram::new(6).is_err());
+
+        // Record 1..=1000ms, percentiles are exact with 3 significant digits.
+        let mut histogram = GooseHistogram::new(3).unwrap();
+        assert!(histogram.is_empty());
+        for time in 1..=1000 {
+            histogram.record(time);
+        }
+        assert_eq!(histogram.len(), 1000);
+        assert_eq!(histogram.percentile(0.5), 500);
+        assert_eq!(histogram.percentile(0.99), 990);
+        assert_eq!(histogram.percentile(0.999), 999);
+        assert_eq!(histogram.percentile(1.0), 1000);
+
+        // Rounded times can't distinguish between 950ms and 999ms.
+        let histogram = Some(histogram);
+        let times = BTreeMap::new();
+        assert_eq!(
+            calculate_percentile(&times, &histogram, 1000, 1, 1000, 0.95),
+            950
+        );
+        assert_eq!(calculate_median(&times, &histogram, 1000, 1, 1000), 500);
+        // Histogram values are limited to the actual min and max.
+        assert_eq!(
+            calculate_percentile(&times, &histogram, 1000, 1, 900, 0.95),
+            900
+        );
+
+        // Without a histogram, the rounded times are used.
+        let mut times = BTreeMap::new();
+        times.insert(10, 1);
+        assert_eq!(calculate_percentile(&times, &None, 1, 10, 10, 0.5), 10);
+        assert_eq!(calculate_median(&times, &None, 1, 10, 10), 10);
+    }
+
+    #[test]
+    fn histogram_merge() {
+        // Histograms from two users or workers.
+        let mut fast = GooseHistogram::new(2).unwrap();
+        let mut slow = GooseHistogram::new(2).unwrap();
+        for _ in 0..99 {
+            fast.record(10);
+        }
+        slow.record(12345);
+
+        let merged = merge_histograms(None, &None);
+        assert!(merged.is_none());
+        let merged = merge_histograms(None, &Some(fast.clone()));
+        assert_eq!(merged, Some(fast.clone()));
+        let merged = merge_histograms(merged, &None);
+        assert_eq!(merged, Some(fast));
+        let merged = merge_histograms(merged, &Some(slow)).unwrap();
+        assert_eq!(merged.len(), 100);
+        assert_eq!(merged.percentile(0.99), 10);
+        // Accurate to 2 significant digits.
+        let slowest = merged.percentile(1.0);
+        assert!(slowest >= 12300 && slowest < 12400);
+    }
+
+    #[test]
+    fn histogram_serialize() {
+        let mut histogram = GooseHistogram::new(3).unwrap();
+        histogram.record(0);
+        histogram.record(5);
+        histogram.record(5);
+        histogram.record(54321);
+
+        // Workers send histograms to the Manager with CBOR.
+        let cbor = serde_cbor::to_vec(&histogram).unwrap();
+        let deserialized: GooseHistogram = serde_cbor::from_slice(&cbor).unwrap();
+        assert_eq!(deserialized, histogram);
+        assert_eq!(deserialized.significant_digits(), 3);
+        assert_eq!(deserialized.len(), 4);
+
+        let json = serde_json::to_string(&histogram).unwrap();
+        let deserialized: GooseHistogram = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, histogram);
+
+        // Invalid significant digits are rejected.
+        assert!(
+            serde_json::from_str::<GooseHistogram>(r#"{"significant_digits":9,"counts":[]}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn percentiles() {
+        assert_eq!(
+            parse_percentiles("50,90, 99.9,100").unwrap(),
+            vec![50.0, 90.0, 99.9, 100.0]
+        );
+        assert!(parse_percentiles("").is_err());
+        assert!(parse_percentiles("50,,90").is_err());
+        assert!(parse_percentiles("fifty").is_err());
+        assert!(parse_percentiles("0").is_err());
+        assert!(parse_percentiles("100.1").is_err());
+
+        // Default percentiles are used unless configured.
+        let mut metrics = GooseMetrics::default();
+        assert_eq!(metrics.percentiles(), &DEFAULT_PERCENTILES);
+        metrics.percentiles = vec![95.0];
+        assert_eq!(metrics.percentiles(), &[95.0]);
+    }
+
+    #[test]
+    fn history_snapshot() {
+        let config = GooseConfiguration::default();
+        let mut requests = GooseRequestMetrics::new();
+        for response_time in &[10, 20, 30, 40] {
+            let mut raw_request = GooseRawRequest::new(GooseMethod::GET, "/", "/", 0, 0);
+            raw_request.response_time = *response_time;
+            raw_request.success = *response_time < 40;
+            record_request(&mut requests, &raw_request, &config);
+        }
+        // A request made in an earlier interval is updated to be a failure.
+        let mut raw_request = GooseRawRequest::new(GooseMethod::POST, "/login", "/login", 0, 0);
+        raw_request.update = true;
+        raw_request.success = false;
+        record_request(&mut requests, &raw_request, &config);
+
+        let snapshot = GooseHistorySnapshot::new(
+            time::Duration::from_secs(4),
+            time::Duration::from_secs(2),
+            3,
+            &requests,
+            &[50.0, 100.0],
+        );
+        assert_eq!(snapshot.elapsed, 4000);
+        assert_eq!(snapshot.duration, 2000);
+        assert_eq!(snapshot.users, 3);
+        // Requests are sorted by method and name.
+        assert_eq!(snapshot.requests.len(), 2);
+        let request = &snapshot.requests[0];
+        assert_eq!(request.name, "/");
+        assert_eq!(request.requests, 4);
+        assert_eq!(request.fails, 1);
+        assert!((request.requests_per_second - 2.0).abs() < f32::EPSILON);
+        assert!((request.fails_per_second - 0.5).abs() < f32::EPSILON);
+        assert!((request.mean_response_time - 25.0).abs() < f32::EPSILON);
+        assert_eq!(request.percentiles, vec![(50.0, 20), (100.0, 40)]);
+        let request = &snapshot.requests[1];
+        assert_eq!(request.name, "/login");
+        assert_eq!(request.requests, 1);
+        assert_eq!(request.fails, 1);
+
+        // No requests are made in the next interval.
+        let idle = GooseHistorySnapshot::new(
+            time::Duration::from_secs(6),
+            time::Duration::from_secs(2),
+            3,
+            &GooseRequestMetrics::new(),
+            &[50.0, 100.0],
+        );
+        assert!(idle.requests.is_empty());
+
+        let metrics = GooseMetrics {
+            percentiles: vec![50.0, 100.0],
+            history: vec![snapshot, idle],
+            ..Default::default()
+        };
+        assert_eq!(
+            metrics.history_csv(),
+            "elapsed,duration,users,method,name,requests,fails,requests_per_second,fails_per_second,mean_response_time,p50,p100\n\
+             4000,2000,3,GET,\"/\",4,1,2.00,0.50,25.00,20,40\n\
+             4000,2000,3,POST,\"/login\",1,1,0.50,0.50,0.00,0,0\n\
+             6000,2000,3,,\"\",0,0,0.00,0.00,0.00,0,0\n"
+        );
+        assert!(metrics.history_json().starts_with("[{"));
+    }
+
+    #[test]
+    fn summary() {
+        let config = GooseConfiguration {
+            status_codes: true,
+            ..Default::default()
+        };
+        let mut requests = GooseRequestMetrics::new();
+        let mut failures = GooseFailureMetrics::new();
+        for response_time in &[10, 20, 30, 40] {
+            let mut raw_request = GooseRawRequest::new(GooseMethod::GET, "/", "/", 0, 0);
+            raw_request.response_time = *response_time;
+            raw_request.status_code = if *response_time < 40 { 200 } else { 500 };
+            raw_request.success = *response_time < 40;
+            if !raw_request.success {
+                raw_request.error = "500 Internal Server Error".to_string();
+            }
+            record_request(&mut requests, &raw_request, &config);
+            record_failure(&mut failures, &raw_request);
+        }
+        let mut task = GooseTaskMetric::new(0, "LoadTest", 0, "index");
+        task.set_time(12, true);
+        task.set_time(18, false);
+
+        let metrics = GooseMetrics {
+            hash: 123,
+            duration: 2,
+            users: 1,
+            requests,
+            tasks: vec![vec![task]],
+            failures,
+            percentiles: vec![50.0, 100.0],
+            ..Default::default()
+        };
+        let summary = metrics.summary();
+        assert_eq!(summary.hash, 123);
+        assert_eq!(summary.requests.len(), 1);
+        let request = &summary.requests[0];
+        assert_eq!(request.name, "/");
+        assert_eq!(request.requests, 4);
+        assert_eq!(request.fails, 1);
+        assert!((request.requests_per_second - 2.0).abs() < f32::EPSILON);
+        assert!((request.fails_per_second - 0.5).abs() < f32::EPSILON);
+        assert_eq!(request.min_response_time, 10);
+        assert!((request.mean_response_time - 25.0).abs() < f32::EPSILON);
+        assert_eq!(request.max_response_time, 40);
+        assert_eq!(request.percentiles, vec![(50.0, 20), (100.0, 40)]);
+        assert_eq!(request.status_codes.get(&200), Some(&3));
+        assert_eq!(request.status_codes.get(&500), Some(&1));
+        assert_eq!(summary.tasks.len(), 1);
+        let task = &summary.tasks[0];
+        assert_eq!(task.runs, 2);
+        assert_eq!(task.fails, 1);
+        assert!((task.mean_time - 15.0).abs() < f32::EPSILON);
+        assert_eq!(summary.failures.len(), 1);
+        assert_eq!(summary.failures[0].error, "500 Internal Server Error");
+        assert_eq!(summary.failures[0].occurrences, 1);
+
+        assert_eq!(
+            metrics.summary_csv(),
+            "type,method,name,count,fails,per_second,fails_per_second,min,mean,median,max,p50,p100,status_codes,error\n\
+             request,GET,\"/\",4,1,2.00,0.50,10,25.00,20,40,20,40,\"200:3;500:1\",\"\"\n\
+             task,,\"LoadTest: index\",2,1,1.00,0.50,12,15.00,12,18,12,18,\"\",\"\"\n\
+             failure,GET,\"/\",1,1,,,,,,,,,\"\",\"500 Internal Server Error\"\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&metrics.summary_json()).unwrap();
+        assert_eq!(json["requests"][0]["status_codes"]["500"], 1);
+        assert_eq!(json["failures"][0]["occurrences"], 1);
+    }
+
+    #[test]
+    fn failures() {
+        let mut failures = GooseFailureMetrics::new();
+        let mut raw_request = GooseRawRequest::new(GooseMethod::GET, "/", "/", 0, 0);
+        // Successful requests aren't recorded.
+        record_failure(&mut failures, &raw_request);
+        assert!(failures.is_empty());
+
+        raw_request.success = false;
+        raw_request.error = "500 Internal Server Error".to_string();
+        record_failure(&mut failures, &raw_request);
+        record_failure(&mut failures, &raw_request);
+        raw_request.error = "connect error".to_string();
+        record_failure(&mut failures, &raw_request);
+
+        // A request marked as failed by set_failure is recorded with its tag.
+        let mut update = GooseRawRequest::new(GooseMethod::POST, "login", "/login", 0, 0);
+        update.success = false;
+        update.update = true;
+        update.error = "login failed".to_string();
+        record_failure(&mut failures, &update);
+        assert_eq!(failures.len(), 3);
+        let key = (
+            GooseMethod::GET,
+            "/".to_string(),
+            "500 Internal Server Error".to_string(),
+        );
+        assert_eq!(failures[&key].occurrences, 2);
+        assert_eq!(failures[&key].key(), key);
+        let key = (
+            GooseMethod::POST,
+            "login".to_string(),
+            "login failed".to_string(),
+        );
+        assert_eq!(failures[&key].occurrences, 1);
+
+        // A request marked as successful by set_success forgets why it failed.
+        update.success = true;
+        record_failure(&mut failures, &update);
+        assert_eq!(failures.len(), 2);
+
+        let metrics = GooseMetrics {
+            failures,
+            ..Default::default()
+        };
+        // Failures are serialized as a list, and grouped again when deserialized.
+        let json = serde_json::to_string(&metrics).unwrap();
+        let deserialized: GooseMetrics = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.failures, metrics.failures);
+        let table = metrics.to_string();
+        assert!(
+            table.contains(" GET /                   | 2           | 500 Internal Server Error")
+        );
+        // Failures are displayed most frequent first.
+        assert!(
+            table.find("500 Internal Server Error").unwrap() < table.find("connect error").unwrap()
+        );
+    }
+
+    #[test]
+    fn csv_quotes() {
+        let config = GooseConfiguration::default();
+        let mut requests = GooseRequestMetrics::new();
+        let mut failures = GooseFailureMetrics::new();
+        let mut raw_request =
+            GooseRawRequest::new(GooseMethod::GET, "search \"goose\"", "/search", 0, 0);
+        raw_request.response_time = 10;
+        raw_request.success = false;
+        raw_request.error = "missing \"results\"".to_string();
+        record_request(&mut requests, &raw_request, &config);
+        record_failure(&mut failures, &raw_request);
+
+        let snapshot = GooseHistorySnapshot::new(
+            time::Duration::from_secs(2),
+            time::Duration::from_secs(2),
+            1,
+            &requests,
+            &[50.0],
+        );
+        let metrics = GooseMetrics {
+            duration: 1,
+            requests,
+            tasks: vec![vec![GooseTaskMetric::new(0, "Load \"Test\"", 0, "index")]],
+            failures,
+            percentiles: vec![50.0],
+            history: vec![snapshot],
+            ..Default::default()
+        };
+        // Quotes in names are doubled, like in errors.
+        assert!(metrics
+            .history_csv()
+            .contains("2000,2000,1,GET,\"search \"\"goose\"\"\",1,1,"));
+        let csv = metrics.summary_csv();
+        assert!(csv.contains("request,GET,\"search \"\"goose\"\"\",1,1,"));
+        assert!(csv.contains("task,,\"Load \"\"Test\"\": index\","));
+        assert!(csv.contains(
+            "failure,GET,\"search \"\"goose\"\"\",1,1,,,,,,,,\"\",\"missing \"\"results\"\"\"\n"
+        ));
+    }
 }
@@ -1,5 +1,7 @@
 pub use crate::goose::{
     GooseTask, GooseTaskError, GooseTaskFunction, GooseTaskResult, GooseTaskSet, GooseUser,
+    GooseWaitTime,
 };
 pub use crate::metrics::GooseMetrics;
+pub use crate::sink::GooseMetricsSink;
 pub use crate::{task, taskset, GooseAttack, GooseError};
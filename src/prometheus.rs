@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+
+use crate::goose::GooseRawRequest;
+use crate::metrics::GooseRawTask;
+use crate::util::{self, http_response, read_http_request};
+
+/// Upper bounds of the response time histogram buckets, in milliseconds.
+const RESPONSE_TIME_BUCKETS: [u64; 12] =
+    [1, 5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+/// Prometheus metrics shared between the parent thread and the HTTP endpoint.
+pub type SharedPrometheusMetrics = Arc<Mutex<PrometheusMetrics>>;
+
+/// Response times of one request, counted in cumulative buckets.
+#[derive(Debug, Default)]
+struct ResponseTimeHistogram {
+    /// How many response times were less than or equal to each bucket's upper bound.
+    buckets: [u64; RESPONSE_TIME_BUCKETS.len()],
+    /// Total of all response times, in milliseconds.
+    sum: u64,
+    /// How many response times were recorded.
+    count: u64,
+}
+impl ResponseTimeHistogram {
+    fn record(&mut self, response_time: u64) {
+        for (bucket, upper_bound) in self.buckets.iter_mut().zip(&RESPONSE_TIME_BUCKETS) {
+            if response_time <= *upper_bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += response_time;
+        self.count += 1;
+    }
+}
+
+/// Counters and histograms exposed to Prometheus. Unlike `GooseMetrics` they are
+/// never reset, as Prometheus expects counters to only increase.
+#[derive(Debug, Default)]
+pub struct PrometheusMetrics {
+    /// Requests made, by method, name and status code.
+    requests: BTreeMap<(String, String, u16), u64>,
+    /// Requests that failed, by method and name.
+    failures: BTreeMap<(String, String), u64>,
+    /// Response times, by method and name.
+    response_times: BTreeMap<(String, String), ResponseTimeHistogram>,
+    /// Tasks run, by task set and task.
+    task_runs: BTreeMap<(String, String), u64>,
+    /// Tasks that failed, by task set and task.
+    task_failures: BTreeMap<(String, String), u64>,
+    /// Number of users currently running.
+    users: usize,
+    /// Requests that waited on a throttle before being made.
+    throttle_waits: u64,
+    /// Total time requests waited on throttles, in milliseconds.
+    throttle_wait_time: u64,
+}
+impl PrometheusMetrics {
+    /// Record a request received from a GooseUser thread.
+    pub fn record_request(&mut self, raw_request: &GooseRawRequest) {
+        let method = format!("{:?}", raw_request.method);
+        // A request updated to be a failure is counted as a failure. Counters can't be
+        // decreased, so a request updated to be a success isn't uncounted.
+        if raw_request.update {
+            if !raw_request.success {
+                *self
+                    .failures
+                    .entry((method, raw_request.name.clone()))
+                    .or_insert(0) += 1;
+            }
+            return;
+        }
+
+        *self
+            .requests
+            .entry((
+                method.clone(),
+                raw_request.name.clone(),
+                raw_request.status_code,
+            ))
+            .or_insert(0) += 1;
+        if !raw_request.success {
+            *self
+                .failures
+                .entry((method.clone(), raw_request.name.clone()))
+                .or_insert(0) += 1;
+        }
+        self.response_times
+            .entry((method, raw_request.name.clone()))
+            .or_default()
+            .record(raw_request.response_time);
+        if raw_request.throttle_wait > 0 {
+            self.throttle_waits += 1;
+            self.throttle_wait_time += raw_request.throttle_wait;
+        }
+    }
+
+    /// Record a task run received from a GooseUser thread.
+    pub fn record_task(&mut self, taskset_name: &str, raw_task: &GooseRawTask) {
+        // Unnamed tasks are identified by their position in the task set.
+        let task_name = if raw_task.name.is_empty() {
+            (raw_task.task_index + 1).to_string()
+        } else {
+            raw_task.name.clone()
+        };
+        let key = (taskset_name.to_string(), task_name);
+        if !raw_task.success {
+            *self.task_failures.entry(key.clone()).or_insert(0) += 1;
+        }
+        *self.task_runs.entry(key).or_insert(0) += 1;
+    }
+
+    /// Update the number of users currently running.
+    pub fn set_users(&mut self, users: usize) {
+        self.users = users;
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+
+        header(
+            &mut text,
+            "goose_requests_total",
+            "counter",
+            "Requests made, by method, name and status code.",
+        );
+        for ((method, name, status), count) in &self.requests {
+            let _ = writeln!(
+                text,
+                "goose_requests_total{{method=\"{}\",name=\"{}\",status=\"{}\"}} {}",
+                method,
+                escape_label(name),
+                status,
+                count
+            );
+        }
+
+        header(
+            &mut text,
+            "goose_request_failures_total",
+            "counter",
+            "Requests that failed, by method and name.",
+        );
+        for ((method, name), count) in &self.failures {
+            let _ = writeln!(
+                text,
+                "goose_request_failures_total{{method=\"{}\",name=\"{}\"}} {}",
+                method,
+                escape_label(name),
+                count
+            );
+        }
+
+        header(
+            &mut text,
+            "goose_response_time_milliseconds",
+            "histogram",
+            "Response times in milliseconds, by method and name.",
+        );
+        for ((method, name), histogram) in &self.response_times {
+            let labels = format!("method=\"{}\",name=\"{}\"", method, escape_label(name));
+            for (upper_bound, count) in RESPONSE_TIME_BUCKETS.iter().zip(&histogram.buckets) {
+                let _ = writeln!(
+                    text,
+                    "goose_response_time_milliseconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, upper_bound, count
+                );
+            }
+            let _ = writeln!(
+                text,
+                "goose_response_time_milliseconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, histogram.count
+            );
+            let _ = writeln!(
+                text,
+                "goose_response_time_milliseconds_sum{{{}}} {}",
+                labels, histogram.sum
+            );
+            let _ = writeln!(
+                text,
+                "goose_response_time_milliseconds_count{{{}}} {}",
+                labels, histogram.count
+            );
+        }
+
+        header(
+            &mut text,
+            "goose_users",
+            "gauge",
+            "Number of users currently running.",
+        );
+        let _ = writeln!(text, "goose_users {}", self.users);
+
+        header(
+            &mut text,
+            "goose_task_runs_total",
+            "counter",
+            "Tasks run, by task set and task.",
+        );
+        for ((taskset, task), count) in &self.task_runs {
+            let _ = writeln!(
+                text,
+                "goose_task_runs_total{{taskset=\"{}\",task=\"{}\"}} {}",
+                escape_label(taskset),
+                escape_label(task),
+                count
+            );
+        }
+
+        header(
+            &mut text,
+            "goose_task_failures_total",
+            "counter",
+            "Tasks that failed, by task set and task.",
+        );
+        for ((taskset, task), count) in &self.task_failures {
+            let _ = writeln!(
+                text,
+                "goose_task_failures_total{{taskset=\"{}\",task=\"{}\"}} {}",
+                escape_label(taskset),
+                escape_label(task),
+                count
+            );
+        }
+
+        header(
+            &mut text,
+            "goose_throttle_waits_total",
+            "counter",
+            "Requests that waited on a throttle before being made.",
+        );
+        let _ = writeln!(text, "goose_throttle_waits_total {}", self.throttle_waits);
+        header(
+            &mut text,
+            "goose_throttle_wait_milliseconds_total",
+            "counter",
+            "Total time requests waited on throttles, in milliseconds.",
+        );
+        let _ = writeln!(
+            text,
+            "goose_throttle_wait_milliseconds_total {}",
+            self.throttle_wait_time
+        );
+
+        text
+    }
+}
+
+/// Add the HELP and TYPE lines that precede each metric.
+fn header(text: &mut String, name: &str, metric_type: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} {}", name, metric_type);
+}
+
+/// Escape a label value as required by the Prometheus text format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serve the shared Prometheus metrics on `address`.
+pub async fn spawn_prometheus(
+    address: &str,
+    metrics: SharedPrometheusMetrics,
+) -> Result<oneshot::Sender<()>, std::io::Error> {
+    let (address, shutdown) = util::spawn_server(address, "prometheus", move |socket| {
+        prometheus_connection(socket, metrics.clone())
+    })
+    .await?;
+    info!("prometheus metrics listening on http://{}/metrics", address);
+    Ok(shutdown)
+}
+
+/// Reply to one HTTP request, serving the metrics for `GET /metrics`.
+async fn prometheus_connection(mut socket: TcpStream, metrics: SharedPrometheusMetrics) {
+    let (method, path) = match read_http_request(&mut socket).await {
+        Some(request) => request,
+        None => return,
+    };
+    let response = match (method.as_str(), path.as_str()) {
+        ("GET", "/metrics") => {
+            // Render the metrics without holding the lock while writing the response.
+            let text = metrics.lock().unwrap().render();
+            http_response("200 OK", "text/plain; version=0.0.4", &text)
+        }
+        ("GET", _) => http_response("404 Not Found", "text/plain", "not found\n"),
+        _ => http_response(
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n",
+        ),
+    };
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goose::GooseMethod;
+
+    #[test]
+    fn render_metrics() {
+        let mut metrics = PrometheusMetrics::default();
+        for (response_time, status_code) in &[(4, 200), (30, 200), (2_000, 500)] {
+            let mut raw_request = GooseRawRequest::new(GooseMethod::GET, "/\"quoted\"", "/", 0, 0);
+            raw_request.response_time = *response_time;
+            raw_request.status_code = *status_code;
+            raw_request.success = *status_code == 200;
+            raw_request.throttle_wait = 5;
+            metrics.record_request(&raw_request);
+        }
+        let mut raw_task = GooseRawTask::new(0, 0, 1, "".to_string(), 0);
+        raw_task.set_time(10, false);
+        metrics.record_task("LoadTest", &raw_task);
+        metrics.set_users(3);
+
+        let text = metrics.render();
+        assert!(text.contains(
+            "goose_requests_total{method=\"GET\",name=\"/\\\"quoted\\\"\",status=\"200\"} 2\n"
+        ));
+        assert!(text.contains(
+            "goose_requests_total{method=\"GET\",name=\"/\\\"quoted\\\"\",status=\"500\"} 1\n"
+        ));
+        assert!(text
+            .contains("goose_request_failures_total{method=\"GET\",name=\"/\\\"quoted\\\"\"} 1\n"));
+        assert!(text.contains(
+            "goose_response_time_milliseconds_bucket{method=\"GET\",name=\"/\\\"quoted\\\"\",le=\"5\"} 1\n"
+        ));
+        assert!(text.contains(
+            "goose_response_time_milliseconds_bucket{method=\"GET\",name=\"/\\\"quoted\\\"\",le=\"50\"} 2\n"
+        ));
+        assert!(text.contains(
+            "goose_response_time_milliseconds_bucket{method=\"GET\",name=\"/\\\"quoted\\\"\",le=\"+Inf\"} 3\n"
+        ));
+        assert!(text.contains(
+            "goose_response_time_milliseconds_sum{method=\"GET\",name=\"/\\\"quoted\\\"\"} 2034\n"
+        ));
+        assert!(text.contains("goose_users 3\n"));
+        assert!(text.contains("goose_task_runs_total{taskset=\"LoadTest\",task=\"2\"} 1\n"));
+        assert!(text.contains("goose_task_failures_total{taskset=\"LoadTest\",task=\"2\"} 1\n"));
+        assert!(text.contains("goose_throttle_waits_total 3\n"));
+        assert!(text.contains("goose_throttle_wait_milliseconds_total 15\n"));
+    }
+}
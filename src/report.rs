@@ -0,0 +1,336 @@
+use std::fmt::{self, Write};
+
+use crate::metrics::{GooseHistorySnapshot, GooseMetrics};
+use crate::GooseConfiguration;
+
+/// Width of each chart in the HTML report, in pixels.
+const CHART_WIDTH: f32 = 720.0;
+/// Height of each chart in the HTML report, in pixels.
+const CHART_HEIGHT: f32 = 240.0;
+/// Space reserved around each chart for axis labels, in pixels.
+const CHART_MARGIN: f32 = 40.0;
+/// Colors used for the lines of each chart, in order.
+const CHART_COLORS: [&str; 2] = ["#2d7dd2", "#d2452d"];
+
+/// One of the `GooseMetrics::fmt_*` methods, each of which renders a table.
+type MetricsFormatter = fn(&GooseMetrics, &mut fmt::Formatter<'_>) -> fmt::Result;
+
+/// Displays one of the tables rendered by the `GooseMetrics::fmt_*` methods.
+struct MetricsTable<'a> {
+    metrics: &'a GooseMetrics,
+    table: MetricsFormatter,
+}
+impl fmt::Display for MetricsTable<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.table)(self.metrics, fmt)
+    }
+}
+
+/// Escape text so it can be safely included in an HTML document.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// List the configuration options that were set, as `--option` and value pairs.
+fn configuration_options(configuration: &GooseConfiguration) -> Vec<(String, String)> {
+    let mut options = Vec::new();
+    if let Ok(serde_json::Value::Object(values)) = serde_json::to_value(configuration) {
+        for (name, value) in values {
+            let value = match value {
+                // Options that weren't set.
+                serde_json::Value::Null | serde_json::Value::Bool(false) => continue,
+                serde_json::Value::String(value) if value.is_empty() => continue,
+                serde_json::Value::Array(values) if values.is_empty() => continue,
+                serde_json::Value::Number(value) if value.as_u64() == Some(0) => continue,
+                // Flags have no value.
+                serde_json::Value::Bool(true) => String::new(),
+                serde_json::Value::String(value) => value,
+                value => value.to_string(),
+            };
+            options.push((format!("--{}", name.replace('_', "-")), value));
+        }
+    }
+    options.sort();
+    options
+}
+
+/// Render one line chart as inline SVG, with time in seconds on the x axis. Each line
+/// is a label and the value it had at the end of each interval.
+fn svg_chart(title: &str, elapsed: &[f32], lines: &[(&str, Vec<f32>)]) -> String {
+    let max_x = elapsed.iter().cloned().fold(0.0, f32::max).max(1.0);
+    let max_y = lines
+        .iter()
+        .flat_map(|(_, values)| values.iter().cloned())
+        .fold(0.0, f32::max)
+        .max(1.0);
+    let plot_width = CHART_WIDTH - 2.0 * CHART_MARGIN;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_MARGIN;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, "<h3>{}</h3>", escape(title));
+    let _ = writeln!(
+        svg,
+        r#"<svg width="{w}" height="{h}" viewBox="0 0 {w} {h}" xmlns="http://www.w3.org/2000/svg">"#,
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+    );
+    // Axes, labelled with their maximum values.
+    let _ = writeln!(
+        svg,
+        r##"<polyline class="axis" fill="none" stroke="#999" points="{x0},{y0} {x0},{y1} {x1},{y1}"/>"##,
+        x0 = CHART_MARGIN,
+        y0 = CHART_MARGIN,
+        x1 = CHART_MARGIN + plot_width,
+        y1 = CHART_MARGIN + plot_height,
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="end">{:.2}</text>"#,
+        CHART_MARGIN - 4.0,
+        CHART_MARGIN + 4.0,
+        max_y
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="end">0</text>"#,
+        CHART_MARGIN - 4.0,
+        CHART_MARGIN + plot_height + 4.0
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="end">{:.0}s</text>"#,
+        CHART_MARGIN + plot_width,
+        CHART_HEIGHT - CHART_MARGIN / 2.0,
+        max_x
+    );
+    for (index, (label, values)) in lines.iter().enumerate() {
+        let color = CHART_COLORS[index % CHART_COLORS.len()];
+        let points: Vec<String> = elapsed
+            .iter()
+            .zip(values)
+            .map(|(x, y)| {
+                format!(
+                    "{:.1},{:.1}",
+                    CHART_MARGIN + x / max_x * plot_width,
+                    CHART_MARGIN + plot_height - y / max_y * plot_height
+                )
+            })
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"<polyline class="line" fill="none" stroke="{}" stroke-width="2" points="{}"/>"#,
+            color,
+            points.join(" ")
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" fill="{}">{}</text>"#,
+            CHART_MARGIN + 10.0 + index as f32 * 150.0,
+            CHART_MARGIN / 2.0,
+            color,
+            escape(label)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render latency and throughput charts from the metrics history.
+fn history_charts(history: &[GooseHistorySnapshot]) -> String {
+    let mut elapsed = Vec::new();
+    let mut requests_per_second = Vec::new();
+    let mut fails_per_second = Vec::new();
+    let mut mean_response_time = Vec::new();
+    for snapshot in history {
+        elapsed.push(snapshot.elapsed as f32 / 1_000.0);
+        requests_per_second.push(
+            snapshot
+                .requests
+                .iter()
+                .map(|request| request.requests_per_second)
+                .sum(),
+        );
+        fails_per_second.push(
+            snapshot
+                .requests
+                .iter()
+                .map(|request| request.fails_per_second)
+                .sum(),
+        );
+        // Average the mean response time of each request, weighted by its count.
+        let requests: usize = snapshot
+            .requests
+            .iter()
+            .map(|request| request.requests)
+            .sum();
+        mean_response_time.push(if requests > 0 {
+            snapshot
+                .requests
+                .iter()
+                .map(|request| request.mean_response_time * request.requests as f32)
+                .sum::<f32>()
+                / requests as f32
+        } else {
+            0.0
+        });
+    }
+
+    let mut charts = String::new();
+    charts.push_str(&svg_chart(
+        "Throughput (per second)",
+        &elapsed,
+        &[
+            ("requests", requests_per_second),
+            ("fails", fails_per_second),
+        ],
+    ));
+    charts.push_str(&svg_chart(
+        "Latency (ms)",
+        &elapsed,
+        &[("mean response time", mean_response_time)],
+    ));
+    charts
+}
+
+/// Render the final metrics of a load test, and the configuration used to run it, into
+/// a single static HTML document.
+pub fn html_report(metrics: &GooseMetrics, configuration: &GooseConfiguration) -> String {
+    let mut html = String::new();
+    html.push_str(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Goose Load Test Report</title>
+<style>
+  body { font-family: sans-serif; margin: 0 20px 20px; color: #222; }
+  h1 { font-size: 24px; }
+  table { border-collapse: collapse; margin-bottom: 20px; font-size: 13px; }
+  th, td { border-bottom: 1px solid #ddd; padding: 4px 8px; text-align: left; }
+  pre { background: #f5f5f5; padding: 10px; overflow-x: auto; }
+  svg { border: 1px solid #ddd; font-size: 11px; }
+</style>
+</head>
+<body>
+<h1>Goose Load Test Report</h1>
+"#,
+    );
+
+    html.push_str("<h2>Summary</h2>\n<table>\n");
+    let _ = writeln!(
+        html,
+        "<tr><th>Load test hash</th><td>{}</td></tr>",
+        metrics.hash
+    );
+    let _ = writeln!(
+        html,
+        "<tr><th>Duration</th><td>{} seconds</td></tr>",
+        metrics.duration
+    );
+    let _ = writeln!(html, "<tr><th>Users</th><td>{}</td></tr>", metrics.users);
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Configuration</h2>\n<table>\n");
+    for (option, value) in configuration_options(configuration) {
+        let _ = writeln!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(&option),
+            escape(&value)
+        );
+    }
+    html.push_str("</table>\n");
+
+    if !metrics.history.is_empty() {
+        html.push_str("<h2>Charts</h2>\n");
+        html.push_str(&history_charts(&metrics.history));
+    }
+
+    // Each table is displayed under its own heading.
+    let tables: [(&str, MetricsFormatter); 9] = [
+        ("Iterations", GooseMetrics::fmt_iterations),
+        ("Tasks", GooseMetrics::fmt_tasks),
+        ("Task times", GooseMetrics::fmt_task_times),
+        ("Requests", GooseMetrics::fmt_requests),
+        ("Response times", GooseMetrics::fmt_response_times),
+        ("Percentiles", GooseMetrics::fmt_percentiles),
+        ("Status codes", GooseMetrics::fmt_status_codes),
+        ("Failures", GooseMetrics::fmt_failures),
+        ("Thresholds", GooseMetrics::fmt_thresholds),
+    ];
+    for (title, table) in tables.iter() {
+        let text = MetricsTable {
+            metrics,
+            table: *table,
+        }
+        .to_string();
+        // Tables without any data aren't displayed.
+        if !text.trim().is_empty() {
+            let _ = writeln!(
+                html,
+                "<h2>{}</h2>\n<pre>{}</pre>",
+                title,
+                escape(text.trim_matches('\n'))
+            );
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_html() {
+        assert_eq!(
+            escape(r#"<a href="/">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;/&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+        assert_eq!(escape("/index.html"), "/index.html");
+    }
+
+    #[test]
+    fn report_configuration() {
+        let configuration = GooseConfiguration {
+            host: "http://localhost/?a=1&b=2".to_string(),
+            users: Some(5),
+            status_codes: true,
+            ..Default::default()
+        };
+        let options = configuration_options(&configuration);
+        assert!(options.contains(&(
+            "--host".to_string(),
+            "http://localhost/?a=1&b=2".to_string()
+        )));
+        assert!(options.contains(&("--users".to_string(), "5".to_string())));
+        assert!(options.contains(&("--status-codes".to_string(), String::new())));
+        // Options that weren't set aren't listed.
+        assert!(!options.iter().any(|(option, _)| option == "--no-metrics"));
+        assert!(!options.iter().any(|(option, _)| option == "--run-time"));
+        assert!(!options.iter().any(|(option, _)| option == "--verbose"));
+
+        let metrics = GooseMetrics {
+            hash: 12345,
+            ..Default::default()
+        };
+        let html = html_report(&metrics, &configuration);
+        assert!(html.contains("<td>12345</td>"));
+        assert!(html.contains("http://localhost/?a=1&amp;b=2"));
+        // There's no history, so there are no charts.
+        assert!(!html.contains("<svg"));
+    }
+}
@@ -0,0 +1,318 @@
+//! Outputs that receive metrics as a load test runs.
+//!
+//! Any number of [`GooseMetricsSink`](./trait.GooseMetricsSink.html)s can be registered
+//! on a [`GooseAttack`](../struct.GooseAttack.html) with
+//! [`register_metrics_sink`](../struct.GooseAttack.html#method.register_metrics_sink).
+//! The parent thread calls each sink as it receives metrics from users, so user threads
+//! don't pay any extra cost, and sinks must not block it. The `--metrics-file` option uses the built-in
+//! [`JsonMetricsSink`](./struct.JsonMetricsSink.html),
+//! [`CsvMetricsSink`](./struct.CsvMetricsSink.html) or
+//! [`RawMetricsSink`](./struct.RawMetricsSink.html), depending on `--metrics-format`.
+//!
+//! ## Example
+//! ```rust
+//!     use goose::goose::GooseRawRequest;
+//!     use goose::sink::GooseMetricsSink;
+//!
+//!     /// Count slow requests.
+//!     #[derive(Default)]
+//!     struct SlowRequests {
+//!         slow: usize,
+//!     }
+//!     impl GooseMetricsSink for SlowRequests {
+//!         fn request(&mut self, raw_request: &GooseRawRequest) {
+//!             if !raw_request.update && raw_request.response_time > 500 {
+//!                 self.slow += 1;
+//!             }
+//!         }
+//!     }
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::goose::GooseRawRequest;
+use crate::metrics::{GooseMetrics, GooseRawTask};
+
+/// Receives metrics as a load test runs. Every hook does nothing by default, so a sink
+/// only needs to implement the hooks it's interested in.
+///
+/// Hooks are called from the parent thread's async runtime, so they must not block: a
+/// sink doing any I/O, such as writing to a file or a socket, should hand its output to
+/// another thread or task, as the built-in sinks do. Only `summary` is called from a
+/// thread that may block, so it can wait for that output to be written. Hooks should
+/// also log errors rather than panicking.
+pub trait GooseMetricsSink: Send {
+    /// Called for each request made by a user, including updates changing whether an
+    /// earlier request failed.
+    fn request(&mut self, _raw_request: &GooseRawRequest) {}
+
+    /// Called for each task run by a user, unless `--no-task-metrics` is enabled.
+    fn task(&mut self, _raw_task: &GooseRawTask) {}
+
+    /// Called with the aggregated metrics every 15 seconds while the load test runs.
+    fn snapshot(&mut self, _metrics: &GooseMetrics) {}
+
+    /// Called once with the final metrics when the load test finishes, from a thread
+    /// that may block.
+    fn summary(&mut self, _metrics: &GooseMetrics) {}
+}
+
+/// A message for the thread writing the output of a built-in sink.
+enum LineMessage {
+    /// Write one line.
+    Line(String),
+    /// Flush all lines written so far, then acknowledge.
+    Flush(mpsc::Sender<()>),
+}
+
+/// A buffered writer shared by the built-in sinks, writing one line per request. Lines
+/// are written by a dedicated thread, so slow storage never blocks the parent thread.
+struct LineWriter {
+    sender: mpsc::Sender<LineMessage>,
+    /// Describes the output in warnings.
+    name: String,
+}
+impl LineWriter {
+    fn new(mut writer: Box<dyn Write + Send>, name: String) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread_name = name.clone();
+        thread::spawn(move || {
+            for message in receiver {
+                match message {
+                    LineMessage::Line(line) => {
+                        if let Err(e) = writeln!(writer, "{}", line) {
+                            warn!("failed to write metrics to {}: {}", thread_name, e);
+                        }
+                    }
+                    LineMessage::Flush(done) => {
+                        if let Err(e) = writer.flush() {
+                            warn!("failed to flush metrics to {}: {}", thread_name, e);
+                        }
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+        LineWriter { sender, name }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self
+            .sender
+            .send(LineMessage::Line(line.to_string()))
+            .is_err()
+        {
+            warn!("failed to write metrics to {}: writer exited", self.name);
+        }
+    }
+
+    /// Wait for all lines to be written, only called from `summary` which may block.
+    fn flush(&mut self) {
+        info!("flushing metrics to {}", self.name);
+        let (done, flushed) = mpsc::channel();
+        if self.sender.send(LineMessage::Flush(done)).is_err() || flushed.recv().is_err() {
+            warn!("failed to flush metrics to {}: writer exited", self.name);
+        }
+    }
+}
+
+/// Create a buffered writer for the named file.
+fn create_file<P: AsRef<Path>>(path: P) -> Result<LineWriter, io::Error> {
+    let name = path.as_ref().display().to_string();
+    info!("opening file to log metrics: {}", name);
+    Ok(LineWriter::new(
+        Box::new(BufWriter::new(File::create(path)?)),
+        name,
+    ))
+}
+
+/// Wrap any writer, described as `name` in warnings.
+fn wrap_writer<W: Write + Send + 'static>(writer: W, name: &str) -> LineWriter {
+    LineWriter::new(Box::new(writer), name.to_string())
+}
+
+/// Writes each request as a line of JSON.
+pub struct JsonMetricsSink {
+    output: LineWriter,
+}
+impl JsonMetricsSink {
+    /// Write requests to a writer, described as `name` in warnings.
+    pub fn new<W: Write + Send + 'static>(writer: W, name: &str) -> Self {
+        JsonMetricsSink {
+            output: wrap_writer(writer, name),
+        }
+    }
+
+    /// Write requests to a file, replacing it if it already exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(JsonMetricsSink {
+            output: create_file(path)?,
+        })
+    }
+}
+impl GooseMetricsSink for JsonMetricsSink {
+    fn request(&mut self, raw_request: &GooseRawRequest) {
+        match serde_json::to_string(raw_request) {
+            Ok(json) => self.output.write_line(&json),
+            Err(e) => warn!("failed to serialize request: {}", e),
+        }
+    }
+
+    fn summary(&mut self, _metrics: &GooseMetrics) {
+        self.output.flush();
+    }
+}
+
+/// Writes each request as a CSV row, after a header row.
+pub struct CsvMetricsSink {
+    output: LineWriter,
+    /// Whether the header row still needs to be written.
+    header: bool,
+}
+impl CsvMetricsSink {
+    /// Write requests to a writer, described as `name` in warnings.
+    pub fn new<W: Write + Send + 'static>(writer: W, name: &str) -> Self {
+        CsvMetricsSink {
+            output: wrap_writer(writer, name),
+            header: true,
+        }
+    }
+
+    /// Write requests to a file, replacing it if it already exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(CsvMetricsSink {
+            output: create_file(path)?,
+            header: true,
+        })
+    }
+}
+impl GooseMetricsSink for CsvMetricsSink {
+    fn request(&mut self, raw_request: &GooseRawRequest) {
+        // Write the header before the first row.
+        if self.header {
+            self.header = false;
+            self.output.write_line(
+                // No quotes needed in header.
+                "elapsed,method,name,url,final_url,redirected,response_time,status_code,success,update,user",
+            );
+        }
+        self.output.write_line(&csv_row(raw_request));
+    }
+
+    fn summary(&mut self, _metrics: &GooseMetrics) {
+        self.output.flush();
+    }
+}
+
+/// Format a request as a CSV row.
+fn csv_row(raw_request: &GooseRawRequest) -> String {
+    format!(
+        // Put quotes around name, url and final_url as they are strings.
+        "{},{:?},\"{}\",\"{}\",\"{}\",{},{},{},{},{},{}",
+        raw_request.elapsed,
+        raw_request.method,
+        raw_request.name,
+        raw_request.url,
+        raw_request.final_url,
+        raw_request.redirected,
+        raw_request.response_time,
+        raw_request.status_code,
+        raw_request.success,
+        raw_request.update,
+        raw_request.user
+    )
+}
+
+/// Writes the Debug output of each request.
+pub struct RawMetricsSink {
+    output: LineWriter,
+}
+impl RawMetricsSink {
+    /// Write requests to a writer, described as `name` in warnings.
+    pub fn new<W: Write + Send + 'static>(writer: W, name: &str) -> Self {
+        RawMetricsSink {
+            output: wrap_writer(writer, name),
+        }
+    }
+
+    /// Write requests to a file, replacing it if it already exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(RawMetricsSink {
+            output: create_file(path)?,
+        })
+    }
+}
+impl GooseMetricsSink for RawMetricsSink {
+    fn request(&mut self, raw_request: &GooseRawRequest) {
+        self.output.write_line(&format!("{:?}", raw_request));
+    }
+
+    fn summary(&mut self, _metrics: &GooseMetrics) {
+        self.output.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goose::GooseMethod;
+    use std::sync::{Arc, Mutex};
+
+    /// A writer that can be inspected after it's given to a sink.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    impl SharedBuffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn built_in_sinks() {
+        let mut raw_request =
+            GooseRawRequest::new(GooseMethod::GET, "/", "http://127.0.0.1/", 10, 1);
+        raw_request.response_time = 3;
+        raw_request.status_code = 200;
+
+        let buffer = SharedBuffer::default();
+        let mut csv = CsvMetricsSink::new(buffer.clone(), "buffer");
+        csv.request(&raw_request);
+        csv.request(&raw_request);
+        // Lines are written by another thread, and flushed with the final metrics.
+        csv.summary(&GooseMetrics::default());
+        let row = "10,GET,\"/\",\"http://127.0.0.1/\",\"\",false,3,200,true,false,1";
+        assert_eq!(
+            buffer.text(),
+            format!(
+                "elapsed,method,name,url,final_url,redirected,response_time,status_code,success,update,user\n{}\n{}\n",
+                row, row
+            )
+        );
+
+        let buffer = SharedBuffer::default();
+        let mut json = JsonMetricsSink::new(buffer.clone(), "buffer");
+        json.request(&raw_request);
+        json.summary(&GooseMetrics::default());
+        let line: serde_json::Value = serde_json::from_str(buffer.text().trim()).unwrap();
+        assert_eq!(line["response_time"], 3);
+        assert_eq!(line["method"], "GET");
+
+        let buffer = SharedBuffer::default();
+        let mut raw = RawMetricsSink::new(buffer.clone(), "buffer");
+        raw.request(&raw_request);
+        raw.summary(&GooseMetrics::default());
+        assert!(buffer.text().starts_with("GooseRawRequest { elapsed: 10,"));
+    }
+}
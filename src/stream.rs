@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time;
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::goose::GooseRawRequest;
+
+/// The largest StatsD packet sent, small enough to not be fragmented on most networks.
+const MAX_STATSD_PACKET: usize = 1_432;
+
+/// How long connecting to, or sending metrics to, the target may take before giving up.
+const STREAM_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+/// How many batches of lines may wait to be streamed before new batches are dropped.
+const STREAM_QUEUE: usize = 16;
+
+/// The protocol metrics are streamed in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StreamProtocol {
+    /// StatsD datagrams with DogStatsD-style tags.
+    Statsd,
+    /// The Graphite plaintext protocol with tags.
+    Graphite,
+}
+
+/// Where metrics are streamed to, owned by the task writing to it.
+#[derive(Debug)]
+enum StreamTarget {
+    /// A StatsD server, receiving datagrams over UDP.
+    Statsd(UdpSocket),
+    /// A Graphite server, receiving lines over TCP. The connection is dropped after an
+    /// error, and opened again before the next batch of lines is streamed.
+    Graphite {
+        address: String,
+        stream: Option<TcpStream>,
+    },
+}
+
+/// Requests made by one task set to one method and name, since metrics were last
+/// streamed.
+#[derive(Debug, Default)]
+struct StreamAggregate {
+    requests: usize,
+    fails: usize,
+    total_response_time: u64,
+    max_response_time: u64,
+}
+
+/// Streams request metrics to StatsD or Graphite, either one line per request, or
+/// aggregated every `--stream-interval`. Lines are formatted by the parent thread and
+/// written by a separate task, so a slow target never blocks the load test.
+#[derive(Debug)]
+pub struct MetricsStream {
+    protocol: StreamProtocol,
+    /// Sends batches of lines to the task writing them, until the load test ends.
+    writer: Option<(mpsc::Sender<Vec<String>>, JoinHandle<()>)>,
+    /// Prepended to the name of every metric.
+    prefix: String,
+    /// How often aggregated metrics are streamed, or None to stream every request.
+    interval: Option<time::Duration>,
+    /// When metrics were last streamed.
+    flushed: time::Instant,
+    /// Lines waiting to be streamed.
+    buffer: Vec<String>,
+    /// Requests aggregated since metrics were last streamed, by task set, method and name.
+    aggregates: BTreeMap<(String, String, String), StreamAggregate>,
+}
+impl MetricsStream {
+    /// Stream metrics to a StatsD server at `address`, over UDP.
+    pub async fn statsd(
+        address: &str,
+        prefix: &str,
+        interval: Option<time::Duration>,
+    ) -> Result<Self, std::io::Error> {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect(address).await?;
+        info!("streaming metrics to statsd at {}", address);
+        Ok(MetricsStream::new(
+            StreamProtocol::Statsd,
+            StreamTarget::Statsd(socket),
+            prefix,
+            interval,
+        ))
+    }
+
+    /// Stream metrics to a Graphite server at `address`, over TCP.
+    pub async fn graphite(
+        address: &str,
+        prefix: &str,
+        interval: Option<time::Duration>,
+    ) -> Result<Self, std::io::Error> {
+        let stream = connect(address).await?;
+        info!("streaming metrics to graphite at {}", address);
+        Ok(MetricsStream::new(
+            StreamProtocol::Graphite,
+            StreamTarget::Graphite {
+                address: address.to_string(),
+                stream: Some(stream),
+            },
+            prefix,
+            interval,
+        ))
+    }
+
+    fn new(
+        protocol: StreamProtocol,
+        target: StreamTarget,
+        prefix: &str,
+        interval: Option<time::Duration>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(STREAM_QUEUE);
+        let writer = tokio::spawn(stream_writer(target, receiver));
+        MetricsStream {
+            protocol,
+            writer: Some((sender, writer)),
+            prefix: prefix.to_string(),
+            interval,
+            flushed: time::Instant::now(),
+            buffer: Vec::new(),
+            aggregates: BTreeMap::new(),
+        }
+    }
+
+    /// Record a request made by a user running the named task set.
+    pub fn record_request(&mut self, taskset_name: &str, raw_request: &GooseRawRequest) {
+        let method = format!("{:?}", raw_request.method);
+        if self.interval.is_some() {
+            let aggregate = self
+                .aggregates
+                .entry((taskset_name.to_string(), method, raw_request.name.clone()))
+                .or_default();
+            // An update changes whether an earlier request failed.
+            if raw_request.update {
+                if raw_request.success {
+                    aggregate.fails = aggregate.fails.saturating_sub(1);
+                } else {
+                    aggregate.fails += 1;
+                }
+                return;
+            }
+            aggregate.requests += 1;
+            if !raw_request.success {
+                aggregate.fails += 1;
+            }
+            aggregate.total_response_time += raw_request.response_time;
+            aggregate.max_response_time =
+                aggregate.max_response_time.max(raw_request.response_time);
+            return;
+        }
+
+        let tags = [
+            ("taskset", taskset_name),
+            ("method", &method),
+            ("name", &raw_request.name),
+        ];
+        // Only StatsD counters can be decreased, so updates aren't streamed to Graphite.
+        if raw_request.update {
+            if self.protocol == StreamProtocol::Statsd {
+                let fails = if raw_request.success { -1 } else { 1 };
+                self.push_line("request.fails", &tags, fails as f64, "c");
+            }
+            return;
+        }
+        self.push_line("request.count", &tags, 1.0, "c");
+        let fails = if raw_request.success { 0.0 } else { 1.0 };
+        self.push_line("request.fails", &tags, fails, "c");
+        self.push_line(
+            "request.response_time",
+            &tags,
+            raw_request.response_time as f64,
+            "ms",
+        );
+    }
+
+    /// Stream all buffered metrics. Aggregated metrics are only streamed once the
+    /// interval has elapsed, unless `force` is set when the load test ends, which also
+    /// waits for all lines to be written.
+    pub async fn flush(&mut self, force: bool) {
+        if let Some(interval) = self.interval {
+            let elapsed = self.flushed.elapsed();
+            if elapsed < interval && !force {
+                return;
+            }
+            self.flushed = time::Instant::now();
+            let seconds = elapsed.as_secs_f64();
+            let aggregates = std::mem::take(&mut self.aggregates);
+            for ((taskset_name, method, name), aggregate) in &aggregates {
+                let tags = [
+                    ("taskset", taskset_name.as_str()),
+                    ("method", method.as_str()),
+                    ("name", name.as_str()),
+                ];
+                self.push_line("request.count", &tags, aggregate.requests as f64, "c");
+                self.push_line("request.fails", &tags, aggregate.fails as f64, "c");
+                if seconds > 0.0 {
+                    self.push_line(
+                        "request.per_second",
+                        &tags,
+                        aggregate.requests as f64 / seconds,
+                        "g",
+                    );
+                }
+                if aggregate.requests > 0 {
+                    self.push_line(
+                        "request.response_time.mean",
+                        &tags,
+                        aggregate.total_response_time as f64 / aggregate.requests as f64,
+                        "g",
+                    );
+                    self.push_line(
+                        "request.response_time.max",
+                        &tags,
+                        aggregate.max_response_time as f64,
+                        "g",
+                    );
+                }
+            }
+        }
+        let lines = std::mem::take(&mut self.buffer);
+        if force {
+            // Wait for all lines to be streamed before the load test exits.
+            if let Some((mut sender, writer)) = self.writer.take() {
+                if !lines.is_empty() {
+                    let _ = sender.send(lines).await;
+                }
+                drop(sender);
+                if tokio::time::timeout(STREAM_TIMEOUT, writer).await.is_err() {
+                    warn!("gave up streaming metrics");
+                }
+            }
+        } else if !lines.is_empty() {
+            if let Some((sender, _)) = self.writer.as_mut() {
+                if sender.try_send(lines).is_err() {
+                    warn!("dropped streamed metrics, the target isn't keeping up");
+                }
+            }
+        }
+    }
+
+    /// Format one metric in the protocol of the target, and buffer it.
+    fn push_line(&mut self, metric: &str, tags: &[(&str, &str)], value: f64, statsd_type: &str) {
+        let line = match self.protocol {
+            StreamProtocol::Statsd => statsd_line(&self.prefix, metric, tags, value, statsd_type),
+            StreamProtocol::Graphite => graphite_line(
+                &self.prefix,
+                metric,
+                tags,
+                value,
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|now| now.as_secs())
+                    .unwrap_or(0),
+            ),
+        };
+        self.buffer.push(line);
+    }
+}
+
+/// Connect to a Graphite server, giving up after STREAM_TIMEOUT.
+async fn connect(address: &str) -> Result<TcpStream, std::io::Error> {
+    tokio::time::timeout(STREAM_TIMEOUT, TcpStream::connect(address))
+        .await
+        .unwrap_or_else(|_| {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "connection timed out",
+            ))
+        })
+}
+
+/// Write batches of lines to the target until the load test ends. Each write gives up
+/// after STREAM_TIMEOUT, and lines that can't be written are dropped.
+async fn stream_writer(mut target: StreamTarget, mut receiver: mpsc::Receiver<Vec<String>>) {
+    while let Some(lines) = receiver.recv().await {
+        match &mut target {
+            StreamTarget::Statsd(socket) => {
+                // Send as many lines as fit in each packet.
+                let mut packets = Vec::new();
+                let mut packet = String::new();
+                for line in lines {
+                    if !packet.is_empty() && packet.len() + line.len() + 1 > MAX_STATSD_PACKET {
+                        packets.push(std::mem::take(&mut packet));
+                    }
+                    if !packet.is_empty() {
+                        packet.push('\n');
+                    }
+                    packet.push_str(&line);
+                }
+                packets.push(packet);
+                for packet in packets {
+                    match tokio::time::timeout(STREAM_TIMEOUT, socket.send(packet.as_bytes())).await
+                    {
+                        Ok(Ok(_)) => (),
+                        Ok(Err(e)) => warn!("failed to stream metrics to statsd: {}", e),
+                        Err(_) => warn!("failed to stream metrics to statsd: timed out"),
+                    }
+                }
+            }
+            StreamTarget::Graphite { address, stream } => {
+                // Reconnect if an earlier write failed.
+                if stream.is_none() {
+                    match connect(address).await {
+                        Ok(connected) => {
+                            info!("reconnected to graphite at {}", address);
+                            *stream = Some(connected);
+                        }
+                        Err(e) => {
+                            warn!("failed to reconnect to graphite at {}: {}", address, e);
+                            continue;
+                        }
+                    }
+                }
+                let mut text = lines.join("\n");
+                text.push('\n');
+                if let Some(connected) = stream.as_mut() {
+                    let error = match tokio::time::timeout(
+                        STREAM_TIMEOUT,
+                        connected.write_all(text.as_bytes()),
+                    )
+                    .await
+                    {
+                        Ok(Ok(())) => None,
+                        Ok(Err(e)) => Some(e.to_string()),
+                        Err(_) => Some("timed out".to_string()),
+                    };
+                    if let Some(e) = error {
+                        warn!("failed to stream metrics to graphite: {}", e);
+                        *stream = None;
+                    }
+                }
+            }
+        }
+    }
+    debug!("metrics stream writer exiting");
+}
+
+/// Replace characters with special meaning in StatsD and Graphite tags.
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            ',' | ':' | ';' | '=' | '|' | '#' | '~' | '!' | '^' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Format a StatsD metric with DogStatsD-style tags, for example
+/// `goose.request.count:1|c|#taskset:LoadTest,method:GET,name:/`.
+fn statsd_line(
+    prefix: &str,
+    metric: &str,
+    tags: &[(&str, &str)],
+    value: f64,
+    statsd_type: &str,
+) -> String {
+    let mut line = format!("{}.{}:{}|{}", prefix, metric, value, statsd_type);
+    for (index, (tag, tag_value)) in tags.iter().enumerate() {
+        let separator = if index == 0 { "|#" } else { "," };
+        let _ = write!(line, "{}{}:{}", separator, tag, sanitize(tag_value));
+    }
+    line
+}
+
+/// Format a Graphite plaintext metric with tags, for example
+/// `goose.request.count;taskset=LoadTest;method=GET;name=/ 1 1600000000`.
+fn graphite_line(
+    prefix: &str,
+    metric: &str,
+    tags: &[(&str, &str)],
+    value: f64,
+    timestamp: u64,
+) -> String {
+    let mut line = format!("{}.{}", prefix, metric);
+    for (tag, tag_value) in tags {
+        let _ = write!(line, ";{}={}", tag, sanitize(tag_value));
+    }
+    let _ = write!(line, " {} {}", value, timestamp);
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_lines() {
+        let tags = [
+            ("taskset", "Load Test"),
+            ("method", "GET"),
+            ("name", "/a;b"),
+        ];
+        assert_eq!(
+            statsd_line("goose", "request.response_time", &tags, 12.0, "ms"),
+            "goose.request.response_time:12|ms|#taskset:Load_Test,method:GET,name:/a_b"
+        );
+        assert_eq!(
+            statsd_line("goose", "request.fails", &tags[..0], -1.0, "c"),
+            "goose.request.fails:-1|c"
+        );
+        assert_eq!(
+            graphite_line("goose", "request.count", &tags, 1.0, 1_600_000_000),
+            "goose.request.count;taskset=Load_Test;method=GET;name=/a_b 1 1600000000"
+        );
+        assert_eq!(
+            graphite_line("goose", "request.per_second", &tags[..1], 2.5, 0),
+            "goose.request.per_second;taskset=Load_Test 2.5 0"
+        );
+    }
+}
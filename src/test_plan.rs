@@ -0,0 +1,179 @@
+use std::str::FromStr;
+use std::time;
+
+use crate::util;
+use crate::GooseError;
+
+/// One step of a load test plan: over `duration`, linearly adjust the number of
+/// running users to `users`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestPlanStep {
+    /// The number of users that should be running when this step completes.
+    pub users: usize,
+    /// How long it takes to get from the previous number of users to `users`.
+    pub duration: time::Duration,
+}
+
+/// A load test plan, a sequence of steps each increasing, decreasing or holding
+/// steady the number of running users. For example, `10,30s;100,5m;0,30s` starts
+/// 10 users over 30 seconds, then increases to 100 users over the next 5 minutes,
+/// and finally stops all users over the last 30 seconds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestPlan {
+    pub steps: Vec<TestPlanStep>,
+}
+
+impl TestPlan {
+    /// Build a test plan from the traditional `--users`, `--hatch-rate` and
+    /// `--run-time` options: start `users` users one every `hatch_interval`, then
+    /// run for `run_time` seconds. If `run_time` is 0, the plan only launches users,
+    /// and the load test runs until canceled. With `--no-reset-metrics` the run time
+    /// also includes launching users, as the timer isn't restarted once they're all
+    /// running.
+    pub fn from_hatch_interval(
+        users: usize,
+        hatch_interval: time::Duration,
+        run_time: usize,
+        no_reset_metrics: bool,
+    ) -> TestPlan {
+        // If the run time expires before all users have been launched, only
+        // launch as many users as there's time for.
+        let users = if run_time > 0 && hatch_interval.as_nanos() > 0 {
+            let launchable = (time::Duration::from_secs(run_time as u64).as_nanos() as f64
+                / hatch_interval.as_nanos() as f64)
+                .ceil() as usize;
+            users.min(launchable)
+        } else {
+            users
+        };
+
+        let hatch_duration = hatch_interval.mul_f64(users as f64);
+        let mut steps = vec![TestPlanStep {
+            users,
+            duration: hatch_duration,
+        }];
+        if run_time > 0 {
+            let mut duration = time::Duration::from_secs(run_time as u64);
+            if no_reset_metrics {
+                duration = duration.checked_sub(hatch_duration).unwrap_or_default();
+            }
+            if duration > time::Duration::from_secs(0) {
+                steps.push(TestPlanStep { users, duration });
+            }
+        }
+        TestPlan { steps }
+    }
+
+    /// The maximum number of users running at any point in the plan.
+    pub fn max_users(&self) -> usize {
+        self.steps.iter().map(|step| step.users).max().unwrap_or(0)
+    }
+}
+
+/// Parse a test plan from a string of semicolon separated `users,timespan` steps,
+/// for example `10,30s;100,5m;0,30s`.
+impl FromStr for TestPlan {
+    type Err = GooseError;
+
+    fn from_str(test_plan: &str) -> Result<Self, Self::Err> {
+        let steps = util::parse_steps("--test-plan", test_plan, "users")?
+            .into_iter()
+            .map(|(users, duration)| TestPlanStep { users, duration })
+            .collect();
+
+        let plan = TestPlan { steps };
+        if plan.max_users() == 0 {
+            return Err(GooseError::InvalidOption {
+                option: "--test-plan".to_string(),
+                value: test_plan.to_string(),
+                detail: "The --test-plan option must start at least 1 user.".to_string(),
+            });
+        }
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test_plan() {
+        let test_plan = TestPlan::from_str("10,30s;100,5m;0,30s").unwrap();
+        assert_eq!(
+            test_plan.steps,
+            vec![
+                TestPlanStep {
+                    users: 10,
+                    duration: time::Duration::from_secs(30)
+                },
+                TestPlanStep {
+                    users: 100,
+                    duration: time::Duration::from_secs(300)
+                },
+                TestPlanStep {
+                    users: 0,
+                    duration: time::Duration::from_secs(30)
+                },
+            ]
+        );
+        assert_eq!(test_plan.max_users(), 100);
+
+        // Whitespace, plain seconds and a trailing semicolon are allowed.
+        let test_plan = TestPlan::from_str(" 5, 1h2m3s ; 5,10 ;").unwrap();
+        assert_eq!(test_plan.steps.len(), 2);
+        assert_eq!(test_plan.steps[0].duration, time::Duration::from_secs(3723));
+        assert_eq!(test_plan.steps[1].duration, time::Duration::from_secs(10));
+
+        // Invalid test plans.
+        assert!(TestPlan::from_str("").is_err());
+        assert!(TestPlan::from_str("10").is_err());
+        assert!(TestPlan::from_str("10,30s,5").is_err());
+        assert!(TestPlan::from_str("ten,30s").is_err());
+        assert!(TestPlan::from_str("10,thirty").is_err());
+        assert!(TestPlan::from_str("10,30s;;0,30s").is_err());
+        assert!(TestPlan::from_str("0,30s").is_err());
+    }
+
+    #[test]
+    fn hatch_interval_test_plan() {
+        // Run until canceled: only launch users.
+        let test_plan =
+            TestPlan::from_hatch_interval(10, time::Duration::from_millis(500), 0, false);
+        assert_eq!(
+            test_plan.steps,
+            vec![TestPlanStep {
+                users: 10,
+                duration: time::Duration::from_secs(5)
+            }]
+        );
+
+        // Launch users, then run for 60 seconds.
+        let test_plan = TestPlan::from_hatch_interval(10, time::Duration::from_secs(1), 60, false);
+        assert_eq!(test_plan.steps.len(), 2);
+        assert_eq!(test_plan.steps[1].users, 10);
+        assert_eq!(test_plan.steps[1].duration, time::Duration::from_secs(60));
+
+        // Without resetting metrics, the run time includes launching users.
+        let test_plan = TestPlan::from_hatch_interval(10, time::Duration::from_secs(1), 60, true);
+        assert_eq!(test_plan.steps[0].duration, time::Duration::from_secs(10));
+        assert_eq!(test_plan.steps[1].duration, time::Duration::from_secs(50));
+
+        // The run time expires before all users are launched.
+        let test_plan = TestPlan::from_hatch_interval(10, time::Duration::from_secs(1), 3, false);
+        assert_eq!(test_plan.steps[0].users, 3);
+        assert_eq!(test_plan.steps[0].duration, time::Duration::from_secs(3));
+        assert_eq!(test_plan.max_users(), 3);
+        let test_plan = TestPlan::from_hatch_interval(10, time::Duration::from_secs(1), 3, true);
+        assert_eq!(test_plan.steps.len(), 1);
+        assert_eq!(test_plan.steps[0].users, 3);
+
+        // More users than fit in a u32.
+        let test_plan =
+            TestPlan::from_hatch_interval(1 << 33, time::Duration::from_nanos(1), 0, false);
+        assert_eq!(
+            test_plan.steps[0].duration,
+            time::Duration::from_nanos(1 << 33)
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! Pass/fail thresholds evaluated against the final metrics of a load test.
+//!
+//! A threshold compares one metric to a value, for example `p95("GET /") < 300ms`,
+//! `error_rate < 1%` or `rps("POST /comment") > 50`. The metric is optionally limited to
+//! one request, named by its method and name as displayed in the metrics tables. Without
+//! a request, the metric is aggregated across all requests.
+//!
+//! The following metrics are available:
+//!  - `pNN`: the slowest response time within the NN percentile, in `ms` or `s`;
+//!  - `mean`: the mean response time, in `ms` or `s`;
+//!  - `max`: the slowest response time, in `ms` or `s`;
+//!  - `rps`: requests per second;
+//!  - `error_rate`: the percentage of requests that failed;
+//!  - `requests`: the number of requests made;
+//!  - `fails`: the number of requests that failed.
+//!
+//! A threshold ending with `, abort_on_fail` is also evaluated once a second while the
+//! load test runs, stopping users gracefully as soon as it's breached. While running, it
+//! is evaluated against a sliding window of only the requests made during the last
+//! second, so `requests` and `fails` count, and `rps` measures, requests in that second,
+//! and a slow start doesn't hide a later breach. An optional grace period, for example
+//! `error_rate < 20%, abort_on_fail=30s`, only aborts the load test once the threshold
+//! has been breached in every window for that long.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time;
+
+use crate::goose::GooseRequest;
+use crate::metrics::{self, GooseHistogram, GooseMetrics};
+use crate::GooseError;
+
+/// A metric that can be compared by a threshold.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GooseThresholdMetric {
+    /// The slowest response time within a percentile, in milliseconds.
+    Percentile(f32),
+    /// The mean response time, in milliseconds.
+    Mean,
+    /// The slowest response time, in milliseconds.
+    Max,
+    /// Requests per second.
+    RequestsPerSecond,
+    /// The percentage of requests that failed.
+    ErrorRate,
+    /// The number of requests made.
+    Requests,
+    /// The number of requests that failed.
+    Fails,
+}
+impl GooseThresholdMetric {
+    /// Whether the metric is a response time.
+    fn is_time(&self) -> bool {
+        matches!(
+            self,
+            GooseThresholdMetric::Percentile(_)
+                | GooseThresholdMetric::Mean
+                | GooseThresholdMetric::Max
+        )
+    }
+
+    /// Whether the metric can only be calculated from requests that were made. Without
+    /// any matching requests, counts are 0.
+    fn needs_requests(&self) -> bool {
+        !matches!(
+            self,
+            GooseThresholdMetric::Requests | GooseThresholdMetric::Fails
+        )
+    }
+
+    /// Format a value of this metric with its unit.
+    fn format(&self, value: f32) -> String {
+        match self {
+            GooseThresholdMetric::ErrorRate => format!("{:.2}%", value),
+            GooseThresholdMetric::RequestsPerSecond => format!("{:.2}", value),
+            GooseThresholdMetric::Mean => format!("{:.2}ms", value),
+            GooseThresholdMetric::Percentile(_) | GooseThresholdMetric::Max => {
+                format!("{}ms", value)
+            }
+            _ => format!("{}", value),
+        }
+    }
+}
+
+/// How a metric is compared to the value of a threshold.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GooseThresholdComparison {
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+/// A pass/fail threshold, set with `--threshold` or `GooseAttack::set_threshold()`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GooseThreshold {
+    /// The metric being compared.
+    pub metric: GooseThresholdMetric,
+    /// The method and name of the request the metric is limited to, or None for all
+    /// requests.
+    pub request: Option<String>,
+    /// How the metric is compared to the value.
+    pub comparison: GooseThresholdComparison,
+    /// The value the metric is compared to, in milliseconds for response times and as a
+    /// percentage for the error rate.
+    pub value: f32,
+    /// Whether the threshold is evaluated while the load test runs, aborting it when
+    /// breached.
+    pub abort_on_fail: bool,
+    /// How long the threshold must be breached before aborting the load test.
+    pub grace_period: time::Duration,
+    /// The threshold as it was defined.
+    pub threshold: String,
+}
+
+/// Parse a threshold, for example `p95("GET /") < 300ms`.
+impl FromStr for GooseThreshold {
+    type Err = GooseError;
+
+    fn from_str(threshold: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref THRESHOLD: Regex = Regex::new(
+                r#"^\s*([a-z_][a-z0-9_.]*)\s*(?:\(\s*"([^"]+)"\s*\))?\s*(<=|>=|<|>)\s*([0-9]*\.?[0-9]+)\s*(ms|s|%)?\s*(,\s*abort_on_fail(?:\s*=\s*([0-9]*\.?[0-9]+)\s*(ms|s|m)?)?)?\s*$"#
+            )
+            .unwrap();
+        }
+        let invalid = |detail: &str| GooseError::InvalidOption {
+            option: "--threshold".to_string(),
+            value: threshold.to_string(),
+            detail: detail.to_string(),
+        };
+
+        let captures = THRESHOLD.captures(threshold).ok_or_else(|| {
+            invalid("The --threshold option must be formatted as METRIC < VALUE or METRIC(\"METHOD NAME\") < VALUE, ie p95(\"GET /\") < 300ms, optionally followed by , abort_on_fail or , abort_on_fail=30s.")
+        })?;
+
+        let metric = match &captures[1] {
+            "mean" => GooseThresholdMetric::Mean,
+            "max" => GooseThresholdMetric::Max,
+            "rps" => GooseThresholdMetric::RequestsPerSecond,
+            "error_rate" => GooseThresholdMetric::ErrorRate,
+            "requests" => GooseThresholdMetric::Requests,
+            "fails" => GooseThresholdMetric::Fails,
+            name if name.starts_with('p') => match name[1..].parse::<f32>() {
+                Ok(percentile) if percentile > 0.0 && percentile <= 100.0 => {
+                    GooseThresholdMetric::Percentile(percentile)
+                }
+                _ => {
+                    return Err(invalid(
+                        "The --threshold option percentiles must be between 0 and 100, ie p95.",
+                    ))
+                }
+            },
+            _ => return Err(invalid(
+                "The --threshold option metric must be one of: pNN, mean, max, rps, error_rate, requests, fails.",
+            )),
+        };
+
+        let comparison = match &captures[3] {
+            "<" => GooseThresholdComparison::LessThan,
+            "<=" => GooseThresholdComparison::LessThanOrEqual,
+            ">" => GooseThresholdComparison::GreaterThan,
+            _ => GooseThresholdComparison::GreaterThanOrEqual,
+        };
+
+        // Response times are compared in milliseconds, error rates as percentages.
+        let mut value: f32 = captures[4].parse().unwrap_or(0.0);
+        match captures.get(5).map(|unit| unit.as_str()) {
+            None => (),
+            Some("ms") if metric.is_time() => (),
+            Some("s") if metric.is_time() => value *= 1_000.0,
+            Some("%") if metric == GooseThresholdMetric::ErrorRate => (),
+            Some(unit) => {
+                return Err(invalid(&format!(
+                    "The --threshold option unit {} can not be used with the {} metric.",
+                    unit, &captures[1]
+                )))
+            }
+        }
+
+        // The grace period defaults to seconds.
+        let grace_period: f32 = captures.get(7).map_or(0.0, |grace_period| {
+            grace_period.as_str().parse().unwrap_or(0.0)
+        });
+        let grace_period = match captures.get(8).map(|unit| unit.as_str()) {
+            Some("ms") => time::Duration::from_secs_f32(grace_period / 1_000.0),
+            Some("m") => time::Duration::from_secs_f32(grace_period * 60.0),
+            _ => time::Duration::from_secs_f32(grace_period),
+        };
+
+        Ok(GooseThreshold {
+            metric,
+            request: captures.get(2).map(|request| request.as_str().to_string()),
+            comparison,
+            value,
+            abort_on_fail: captures.get(6).is_some(),
+            grace_period,
+            threshold: threshold.trim().to_string(),
+        })
+    }
+}
+
+impl GooseThreshold {
+    /// Compare the threshold to the final metrics of a load test.
+    pub fn evaluate(&self, metrics: &GooseMetrics) -> GooseThresholdResult {
+        let actual = match self.actual(metrics) {
+            None if !self.metric.needs_requests() => Some(0.0),
+            actual => actual,
+        };
+        self.result(actual)
+    }
+
+    /// Compare the threshold to a window of the metrics of a running load test,
+    /// returning the breached result. Requests that haven't been made yet don't breach
+    /// a threshold.
+    pub fn breached(&self, metrics: &GooseMetrics) -> Option<GooseThresholdResult> {
+        let result = self.result(Some(self.actual(metrics)?));
+        if result.passed {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// The result of comparing the threshold to the value of its metric.
+    fn result(&self, actual: Option<f32>) -> GooseThresholdResult {
+        GooseThresholdResult {
+            threshold: self.threshold.clone(),
+            actual,
+            formatted_actual: actual.map(|actual| self.metric.format(actual)),
+            // A threshold on a response time or rate of a request that was never made
+            // fails.
+            passed: matches!(actual, Some(actual) if self.compare(actual)),
+        }
+    }
+
+    /// Whether a value of the metric meets the threshold.
+    fn compare(&self, actual: f32) -> bool {
+        match self.comparison {
+            GooseThresholdComparison::LessThan => actual < self.value,
+            GooseThresholdComparison::LessThanOrEqual => actual <= self.value,
+            GooseThresholdComparison::GreaterThan => actual > self.value,
+            GooseThresholdComparison::GreaterThanOrEqual => actual >= self.value,
+        }
+    }
+
+    /// The value of the metric, or None if no matching requests were made.
+    fn actual(&self, metrics: &GooseMetrics) -> Option<f32> {
+        let requests: Vec<&GooseRequest> = match &self.request {
+            Some(request) => vec![metrics.requests.get(request)?],
+            None => metrics.requests.values().collect(),
+        };
+        let count: usize = requests
+            .iter()
+            .map(|request| request.success_count + request.fail_count)
+            .sum();
+        let fails: usize = requests.iter().map(|request| request.fail_count).sum();
+        if count == 0 {
+            return None;
+        }
+
+        let response_time_counter: usize = requests
+            .iter()
+            .map(|request| request.response_time_counter)
+            .sum();
+        let max_response_time = requests
+            .iter()
+            .map(|request| request.max_response_time)
+            .max()
+            .unwrap_or(0);
+        Some(match self.metric {
+            GooseThresholdMetric::Percentile(percentile) => {
+                let mut times = BTreeMap::new();
+                let mut histogram: Option<GooseHistogram> = None;
+                let mut min_response_time = 0;
+                for request in &requests {
+                    times = metrics::merge_times(times, request.response_times.clone());
+                    histogram = metrics::merge_histograms(histogram, &request.histogram);
+                    min_response_time =
+                        metrics::update_min_time(min_response_time, request.min_response_time);
+                }
+                metrics::calculate_percentile(
+                    &times,
+                    &histogram,
+                    response_time_counter,
+                    min_response_time,
+                    max_response_time,
+                    percentile / 100.0,
+                ) as f32
+            }
+            GooseThresholdMetric::Mean => {
+                let total: usize = requests
+                    .iter()
+                    .map(|request| request.total_response_time)
+                    .sum();
+                total as f32 / response_time_counter.max(1) as f32
+            }
+            GooseThresholdMetric::Max => max_response_time as f32,
+            GooseThresholdMetric::RequestsPerSecond => match metrics.duration {
+                0 => 0.0,
+                duration => count as f32 / duration as f32,
+            },
+            GooseThresholdMetric::ErrorRate => fails as f32 / count as f32 * 100.0,
+            GooseThresholdMetric::Requests => count as f32,
+            GooseThresholdMetric::Fails => fails as f32,
+        })
+    }
+}
+
+/// Whether one threshold passed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GooseThresholdResult {
+    /// The threshold as it was defined.
+    pub threshold: String,
+    /// The value of the metric, or None if no matching requests were made.
+    pub actual: Option<f32>,
+    /// The value of the metric formatted for display, if any.
+    pub formatted_actual: Option<String>,
+    /// Whether the threshold passed.
+    pub passed: bool,
+}
+
+/// Whether all thresholds passed, and the result of each.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GooseThresholdVerdict {
+    /// Whether all thresholds passed.
+    pub passed: bool,
+    /// The result of each threshold, in the order they were set.
+    pub results: Vec<GooseThresholdResult>,
+    /// Why the load test was aborted early, if an `abort_on_fail` threshold was breached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aborted: Option<String>,
+}
+impl GooseThresholdVerdict {
+    /// Evaluate all thresholds against the final metrics of a load test.
+    pub fn evaluate(thresholds: &[GooseThreshold], metrics: &GooseMetrics) -> Self {
+        let results: Vec<GooseThresholdResult> = thresholds
+            .iter()
+            .map(|threshold| threshold.evaluate(metrics))
+            .collect();
+        GooseThresholdVerdict {
+            passed: results.iter().all(|result| result.passed),
+            results,
+            aborted: None,
+        }
+    }
+
+    /// Record that the load test was aborted by the threshold at `index`, which fails
+    /// even if its final value recovered.
+    pub fn abort(&mut self, index: usize, reason: &str) {
+        if let Some(result) = self.results.get_mut(index) {
+            result.passed = false;
+        }
+        self.passed = false;
+        self.aborted = Some(reason.to_string());
+    }
+
+    /// The thresholds that failed.
+    pub fn failures(&self) -> Vec<&GooseThresholdResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.passed)
+            .collect()
+    }
+}
+impl fmt::Display for GooseThresholdVerdict {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(fmt, " {:<45} | {:<15} | Result", "Threshold", "Actual")?;
+        writeln!(
+            fmt,
+            " ----------------------------------------------------------------------------- "
+        )?;
+        for result in &self.results {
+            writeln!(
+                fmt,
+                " {:<45} | {:<15} | {}",
+                result.threshold,
+                result.formatted_actual.as_deref().unwrap_or("no requests"),
+                if result.passed { "PASS" } else { "FAIL" }
+            )?;
+        }
+        if let Some(aborted) = &self.aborted {
+            writeln!(fmt, " Aborted: {}", aborted)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goose::GooseMethod;
+
+    #[test]
+    fn parse_thresholds() {
+        let threshold = GooseThreshold::from_str(r#"p95("GET /") < 300ms"#).unwrap();
+        assert_eq!(threshold.metric, GooseThresholdMetric::Percentile(95.0));
+        assert_eq!(threshold.request, Some("GET /".to_string()));
+        assert_eq!(threshold.comparison, GooseThresholdComparison::LessThan);
+        assert_eq!(threshold.value, 300.0);
+
+        let threshold = GooseThreshold::from_str("error_rate<=1%").unwrap();
+        assert_eq!(threshold.metric, GooseThresholdMetric::ErrorRate);
+        assert_eq!(threshold.request, None);
+        assert_eq!(
+            threshold.comparison,
+            GooseThresholdComparison::LessThanOrEqual
+        );
+        assert_eq!(threshold.value, 1.0);
+
+        let threshold = GooseThreshold::from_str(r#" rps( "POST /comment" ) > 50 "#).unwrap();
+        assert_eq!(threshold.metric, GooseThresholdMetric::RequestsPerSecond);
+        assert_eq!(threshold.request, Some("POST /comment".to_string()));
+        assert_eq!(threshold.threshold, r#"rps( "POST /comment" ) > 50"#);
+
+        let threshold = GooseThreshold::from_str("p99.9 >= 1.5s").unwrap();
+        assert_eq!(threshold.metric, GooseThresholdMetric::Percentile(99.9));
+        assert_eq!(threshold.value, 1_500.0);
+
+        let threshold = GooseThreshold::from_str("error_rate < 20%, abort_on_fail=30s").unwrap();
+        assert_eq!(threshold.value, 20.0);
+        assert!(threshold.abort_on_fail);
+        assert_eq!(threshold.grace_period, time::Duration::from_secs(30));
+        assert_eq!(threshold.threshold, "error_rate < 20%, abort_on_fail=30s");
+
+        let threshold = GooseThreshold::from_str("p50 < 2s,abort_on_fail").unwrap();
+        assert_eq!(threshold.value, 2_000.0);
+        assert!(threshold.abort_on_fail);
+        assert_eq!(threshold.grace_period, time::Duration::from_secs(0));
+
+        let threshold = GooseThreshold::from_str("max < 1s, abort_on_fail=1.5m").unwrap();
+        assert_eq!(threshold.grace_period, time::Duration::from_secs(90));
+        let threshold = GooseThreshold::from_str("max < 1s, abort_on_fail=500ms").unwrap();
+        assert_eq!(threshold.grace_period, time::Duration::from_millis(500));
+        assert!(!GooseThreshold::from_str("max < 1s").unwrap().abort_on_fail);
+
+        // Invalid thresholds.
+        assert!(GooseThreshold::from_str("p95").is_err());
+        assert!(GooseThreshold::from_str("p95 = 300ms").is_err());
+        assert!(GooseThreshold::from_str("p101 < 300ms").is_err());
+        assert!(GooseThreshold::from_str("slowest < 300ms").is_err());
+        assert!(GooseThreshold::from_str("rps > 50ms").is_err());
+        assert!(GooseThreshold::from_str("mean < 1%").is_err());
+        assert!(GooseThreshold::from_str(r#"mean(GET /) < 300"#).is_err());
+        assert!(GooseThreshold::from_str("mean < 300, abort").is_err());
+        assert!(GooseThreshold::from_str("mean < 300, abort_on_fail=30%").is_err());
+    }
+
+    #[test]
+    fn evaluate_thresholds() {
+        let mut request = GooseRequest::new("/", GooseMethod::GET, 0);
+        for response_time in 1..=100 {
+            request.set_response_time(response_time);
+        }
+        request.success_count = 98;
+        request.fail_count = 2;
+        let mut metrics = GooseMetrics {
+            duration: 10,
+            ..Default::default()
+        };
+        metrics.requests.insert("GET /".to_string(), request);
+
+        let thresholds: Vec<GooseThreshold> = [
+            r#"p95("GET /") < 300ms"#,
+            "max <= 100",
+            "error_rate < 1%",
+            "rps >= 10",
+            r#"fails("POST /comment") < 1"#,
+            r#"p95("POST /comment") < 300ms"#,
+        ]
+        .iter()
+        .map(|threshold| GooseThreshold::from_str(threshold).unwrap())
+        .collect();
+        let verdict = GooseThresholdVerdict::evaluate(&thresholds, &metrics);
+        assert!(!verdict.passed);
+        assert_eq!(verdict.results[0].actual, Some(95.0));
+        assert!(verdict.results[0].passed);
+        assert!(verdict.results[1].passed);
+        assert_eq!(verdict.results[2].actual, Some(2.0));
+        assert_eq!(
+            verdict.results[2].formatted_actual,
+            Some("2.00%".to_string())
+        );
+        assert!(!verdict.results[2].passed);
+        assert_eq!(verdict.results[3].actual, Some(10.0));
+        assert!(verdict.results[3].passed);
+        // Requests that were never made count as 0, but have no response times.
+        assert_eq!(verdict.results[4].actual, Some(0.0));
+        assert!(verdict.results[4].passed);
+        assert_eq!(verdict.results[5].actual, None);
+        assert!(!verdict.results[5].passed);
+        assert_eq!(verdict.failures().len(), 2);
+
+        // Only requests that were made can breach a threshold while the load test runs.
+        assert!(thresholds[0].breached(&metrics).is_none());
+        assert_eq!(thresholds[2].breached(&metrics).unwrap().actual, Some(2.0));
+        assert!(thresholds[4].breached(&metrics).is_none());
+        assert!(thresholds[5].breached(&metrics).is_none());
+
+        let table = verdict.to_string();
+        assert!(table.contains("error_rate < 1%"));
+        assert!(table.contains("no requests"));
+        assert!(!table.contains("Aborted"));
+
+        let mut verdict = GooseThresholdVerdict::evaluate(&thresholds[..2], &metrics);
+        assert!(verdict.passed);
+        verdict.abort(1, "max <= 100 breached");
+        assert!(!verdict.passed);
+        assert!(!verdict.results[1].passed);
+        assert!(verdict
+            .to_string()
+            .contains(" Aborted: max <= 100 breached"));
+    }
+}
@@ -1,71 +1,264 @@
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{self, Receiver, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
 use tokio::time;
 
 use crate::util;
+use crate::GooseError;
+
+/// The most requests per second the throttle can be configured to allow.
+pub const MAX_THROTTLE_REQUESTS: usize = 1_000_000;
+
+/// One step of a throttle schedule: allow `requests` requests per second for `duration`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThrottleStep {
+    /// Maximum requests per second allowed during this step.
+    pub requests: usize,
+    /// How long this step lasts. The last step of a schedule lasts until the load
+    /// test ends.
+    pub duration: time::Duration,
+}
+
+/// Parse a throttle schedule from a string of semicolon separated `requests,timespan`
+/// steps, for example `50,2m;200,5m`.
+pub fn parse_throttle_schedule(schedule: &str) -> Result<Vec<ThrottleStep>, GooseError> {
+    let steps: Vec<ThrottleStep> = util::parse_steps("--throttle-schedule", schedule, "requests")?
+        .into_iter()
+        .map(|(requests, duration)| ThrottleStep { requests, duration })
+        .collect();
+    for step in &steps {
+        if step.requests == 0 || step.requests > MAX_THROTTLE_REQUESTS {
+            return Err(GooseError::InvalidOption {
+                option: "--throttle-schedule".to_string(),
+                value: schedule.to_string(),
+                detail: "The --throttle-schedule option must allow from 1 to 1,000,000 requests per second in each step.".to_string(),
+            });
+        }
+    }
+    Ok(steps)
+}
+
+/// Commands sent from the parent to the throttle thread.
+#[derive(Debug)]
+pub enum ThrottleCommand {
+    /// Change the maximum requests per second, replacing the rest of the schedule.
+    SetRequests(usize),
+    /// The load test is complete.
+    Exit,
+}
+
+/// Sent by a GooseUser to the throttle thread, which replies once the user is allowed
+/// to make a request.
+pub type ThrottlePermit = oneshot::Sender<()>;
+
+/// Wait until the throttle allows a request. Once the load test is over the throttle
+/// thread exits, failing all waiting requests with the same error as a closed channel.
+pub async fn acquire(
+    throttle: &mpsc::Sender<ThrottlePermit>,
+) -> Result<(), mpsc::error::SendError<bool>> {
+    let (permit, allowed) = oneshot::channel();
+    throttle
+        .clone()
+        .send(permit)
+        .await
+        .map_err(|_| mpsc::error::SendError(true))?;
+    allowed.await.map_err(|_| mpsc::error::SendError(true))
+}
+
+/// Launch a throttle thread following `schedule`, returning the channel GooseUsers
+/// request permits on before making requests, and the channel used to control the
+/// throttle. Without a configured `burst`, the bucket holds the fewest tokens needed to
+/// reach the throttled rate.
+pub fn spawn_throttle(
+    schedule: Vec<ThrottleStep>,
+    burst: Option<usize>,
+) -> (
+    mpsc::Sender<ThrottlePermit>,
+    UnboundedSender<ThrottleCommand>,
+) {
+    // Create a bounded channel allowing single-sender multi-receiver to throttle
+    // GooseUser threads. Requests wait in the channel, in order, until the throttle
+    // thread grants them a permit.
+    let (all_threads_throttle, throttle_receiver) = mpsc::channel(1);
+
+    // Create a channel allowing the parent to control the throttle thread, and to
+    // inform it when the load test is finished.
+    let (parent_to_throttle_tx, throttle_rx) = mpsc::unbounded_channel();
+
+    // Launch a new thread for throttling, no need to rejoin it.
+    tokio::spawn(throttle_main(
+        schedule,
+        burst,
+        throttle_receiver,
+        throttle_rx,
+    ));
+
+    (all_threads_throttle, parent_to_throttle_tx)
+}
+
+/// The default number of tokens the bucket holds: a single token, or 10ms worth of
+/// tokens above 100 requests per second so the throttle can keep up.
+fn default_burst(requests: usize) -> usize {
+    (requests / 100).max(1)
+}
+
+/// Add tokens to the bucket for the time elapsed since it was last refilled, up to
+/// `capacity` tokens.
+fn refill(tokens: &mut f64, refilled: &mut time::Instant, requests: usize, capacity: f64) {
+    let now = time::Instant::now();
+    *tokens = (*tokens + (now - *refilled).as_secs_f64() * requests as f64).min(capacity);
+    *refilled = now;
+}
 
 /// This throttle thread limits the maximum number of requests that can be made across
-/// all GooseUser threads. When enabled, GooseUser threads must add a token to the
-/// bounded channel before making a request, and this thread limits how frequently
-/// tokens are removed thereby throttling how fast requests can be made. It is an
-/// implementation of the leaky bucket algorithm as a queue: instead of leaking the
-/// overflow we asynchronously block. More information on the leaky bucket algorithm
-/// can be found at: https://en.wikipedia.org/wiki/Leaky_bucket
+/// all GooseUser threads. When enabled, GooseUser threads must request a permit on the
+/// bounded channel before making a request, and this thread only grants a permit when
+/// a request is allowed, otherwise asynchronously blocking the GooseUser. It is an
+/// implementation of the token bucket algorithm: tokens are added to the bucket at
+/// the configured rate, up to `burst` tokens, and each request consumes a token. More
+/// information on the token bucket algorithm can be found at:
+/// https://en.wikipedia.org/wiki/Token_bucket
 pub async fn throttle_main(
-    throttle_requests: usize,
-    mut throttle_receiver: Receiver<bool>,
-    mut parent_receiver: Receiver<bool>,
+    schedule: Vec<ThrottleStep>,
+    burst: Option<usize>,
+    mut throttle_receiver: Receiver<ThrottlePermit>,
+    mut parent_receiver: UnboundedReceiver<ThrottleCommand>,
 ) {
-    // Use microseconds to allow configurations up to 1,000,000 requests per second.
-    let mut sleep_duration = time::Duration::from_micros(1_000_000 / throttle_requests as u64);
-    let tokens_per_duration;
-
-    let ten_milliseconds = time::Duration::from_millis(10);
-    debug!(
-        "sleep_duration: {:?} ten_milliseconds: {:?}",
-        sleep_duration, ten_milliseconds
-    );
-
-    // Keep sleep_duration at least ~10ms as `delay_for` has millisecond granularity.
-    if sleep_duration < ten_milliseconds {
-        tokens_per_duration = (ten_milliseconds.as_nanos() / sleep_duration.as_nanos()) as u32;
-        sleep_duration *= tokens_per_duration;
-    } else {
-        tokens_per_duration = 1;
-    }
-
-    info!(
-        "throttle allowing {} request(s) every {:?}",
-        tokens_per_duration, sleep_duration
-    );
+    let mut step = 0;
+    let mut step_started = time::Instant::now();
+    let mut requests = schedule[0].requests;
+    info!("throttle allowing {} request(s) per second", requests);
 
-    // One or more token gets removed from the throttle_receiver bucket at regular
-    // intervals. The throttle_drift variable tracks how much time is spent on
-    // everything else, and is subtracted from the time spent sleeping.
-    let mut throttle_drift = tokio::time::Instant::now();
+    // Start with an empty bucket to avoid a burst of traffic during startup.
+    let mut tokens: f64 = 0.0;
+    let mut refilled = time::Instant::now();
 
-    // Loop and remove tokens from channel at controlled rate until load test ends.
     loop {
-        debug!(
-            "throttle removing {} token(s) from channel",
-            tokens_per_duration
-        );
-        throttle_drift = util::sleep_minus_drift(sleep_duration, throttle_drift).await;
-
-        // A message will be received when the load test is over.
-        if parent_receiver.try_recv().is_ok() {
-            // Close throttle channel to prevent any further requests.
-            info!("load test complete, closing throttle channel");
-            throttle_receiver.close();
-            break;
+        // Advance through the schedule, the last step lasts until the load test ends.
+        while step + 1 < schedule.len() && step_started.elapsed() >= schedule[step].duration {
+            step_started += schedule[step].duration;
+            step += 1;
+            requests = schedule[step].requests;
+            info!(
+                "throttle schedule step {} of {}: allowing {} request(s) per second",
+                step + 1,
+                schedule.len(),
+                requests
+            );
         }
 
-        // Remove tokens from the channel, freeing spots for request to be made.
-        for token in 0..tokens_per_duration {
-            // If the channel is empty, we will get an error, so stop trying to remove tokens.
-            if throttle_receiver.try_recv().is_err() {
-                debug!("empty channel, exit after removing {} tokens", token);
-                break;
+        // Add tokens for the time elapsed since the bucket was last refilled. As
+        // `delay_for` has millisecond granularity, by default the bucket holds 10ms worth
+        // of tokens. A configured burst is honored exactly.
+        let capacity = burst.unwrap_or_else(|| default_burst(requests)) as f64;
+        refill(&mut tokens, &mut refilled, requests, capacity);
+
+        // Wake up when the next schedule step starts, or when the next token is added
+        // to an empty bucket.
+        let mut wait = time::Duration::from_secs(1);
+        if step + 1 < schedule.len() {
+            wait = wait.min(
+                (step_started + schedule[step].duration)
+                    .saturating_duration_since(time::Instant::now()),
+            );
+        }
+        let has_token = tokens >= 1.0;
+        if !has_token {
+            wait = wait.min(time::Duration::from_secs_f64(
+                (1.0 - tokens) / requests as f64,
+            ));
+        }
+
+        tokio::select! {
+            // Grant the next waiting request a permit, allowing it to be made. A user
+            // that stopped waiting doesn't use up a token.
+            permit = throttle_receiver.recv(), if has_token => {
+                if let Some(permit) = permit {
+                    if permit.send(()).is_ok() {
+                        // The bucket may have been full while waiting for a request.
+                        refill(&mut tokens, &mut refilled, requests, capacity);
+                        tokens -= 1.0;
+                    }
+                }
             }
+            command = parent_receiver.recv() => match command {
+                Some(ThrottleCommand::SetRequests(new_requests)) => {
+                    info!("throttle now allowing {} request(s) per second", new_requests);
+                    requests = new_requests;
+                    // Follow the new rate until the load test ends.
+                    step = schedule.len();
+                }
+                // A message will be received when the load test is over.
+                Some(ThrottleCommand::Exit) | None => {
+                    // Close throttle channel to prevent any further requests, waiting
+                    // requests are canceled as their permits are dropped.
+                    info!("load test complete, closing throttle channel");
+                    throttle_receiver.close();
+                    break;
+                }
+            },
+            _ = time::delay_for(wait) => (),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_schedule() {
+        let schedule = parse_throttle_schedule("50,2m;200,5m").unwrap();
+        assert_eq!(
+            schedule,
+            vec![
+                ThrottleStep {
+                    requests: 50,
+                    duration: time::Duration::from_secs(120)
+                },
+                ThrottleStep {
+                    requests: 200,
+                    duration: time::Duration::from_secs(300)
+                },
+            ]
+        );
+
+        // Each step must allow from 1 to 1,000,000 requests per second.
+        assert!(parse_throttle_schedule("0,2m").is_err());
+        assert!(parse_throttle_schedule("50,2m;1000001,5m").is_err());
+        assert!(parse_throttle_schedule("50").is_err());
+    }
+
+    #[tokio::test]
+    async fn throttle_burst() {
+        // 10 requests per second, with a bucket of 5 tokens.
+        let schedule = vec![ThrottleStep {
+            requests: 10,
+            duration: time::Duration::from_secs(1),
+        }];
+        let (throttle, parent) = spawn_throttle(schedule, Some(5));
+
+        // Wait for the bucket to fill, then make as many requests as are allowed
+        // before the next token is added.
+        time::delay_for(time::Duration::from_millis(1_000)).await;
+        let mut allowed = 0;
+        while time::timeout(time::Duration::from_millis(50), acquire(&throttle))
+            .await
+            .is_ok()
+        {
+            allowed += 1;
+        }
+        assert_eq!(allowed, 5);
+
+        // Waiting requests are canceled once the load test is over.
+        parent.send(ThrottleCommand::Exit).unwrap();
+        assert!(acquire(&throttle).await.is_err());
+    }
+
+    #[test]
+    fn throttle_default_burst() {
+        assert_eq!(default_burst(1), 1);
+        assert_eq!(default_burst(100), 1);
+        assert_eq!(default_burst(1_000), 10);
+        assert_eq!(default_burst(1_000_000), 10_000);
+    }
+}
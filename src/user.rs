@@ -1,12 +1,13 @@
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use rand::Rng;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time;
-use tokio::sync::mpsc;
+use tokio::sync::mpsc::{self, error::TryRecvError};
+use tokio::sync::oneshot;
 
 use crate::get_worker_id;
-use crate::goose::{GooseTaskFunction, GooseTaskSet, GooseUser, GooseUserCommand};
+use crate::goose::{GooseTaskError, GooseTaskFunction, GooseTaskSet, GooseUser, GooseUserCommand};
 use crate::metrics::{GooseMetric, GooseRawTask};
 
 pub async fn user_main(
@@ -14,6 +15,8 @@ pub async fn user_main(
     thread_task_set: GooseTaskSet,
     mut thread_user: GooseUser,
     mut thread_receiver: mpsc::UnboundedReceiver<GooseUserCommand>,
+    thread_ready: Option<mpsc::UnboundedSender<oneshot::Sender<()>>>,
+    users_finished: Arc<AtomicUsize>,
     worker: bool,
 ) {
     if worker {
@@ -60,7 +63,12 @@ pub async fn user_main(
     if thread_user.weighted_tasks.is_empty() {
         // Handle the edge case where a load test doesn't define any normal tasks.
         thread_continue = false;
+        users_finished.fetch_add(1, Ordering::SeqCst);
     }
+    let mut iterations = 0;
+    // When running at an arrival rate, each pass through all weighted tasks is an
+    // iteration that only starts when scheduled by the parent thread.
+    let mut iteration_starting = true;
     while thread_continue {
         // Weighted_tasks is divided into buckets of tasks sorted by sequence, and then all non-sequenced tasks.
         if thread_user.weighted_tasks[weighted_bucket].len() <= weighted_bucket_position {
@@ -73,6 +81,7 @@ pub async fn user_main(
             weighted_bucket += 1;
             if thread_user.weighted_tasks.len() <= weighted_bucket {
                 weighted_bucket = 0;
+                iteration_starting = true;
             }
             thread_user
                 .weighted_bucket
@@ -85,6 +94,16 @@ pub async fn user_main(
             );
         }
 
+        // Wait until the parent thread schedules the next iteration.
+        if iteration_starting {
+            iteration_starting = false;
+            if let Some(ready) = &thread_ready {
+                if !user_wait_for_iteration(ready, &mut thread_receiver).await {
+                    break;
+                }
+            }
+        }
+
         // Determine which task we're going to run next.
         let thread_weighted_task =
             thread_user.weighted_tasks[weighted_bucket][weighted_bucket_position];
@@ -100,56 +119,53 @@ pub async fn user_main(
         }
 
         // Invoke the task function.
-        invoke_task_function(
+        let task_started = time::Instant::now();
+        if !invoke_task_function(
             function,
             &thread_user,
             thread_weighted_task,
             thread_task_name,
         )
-        .await;
+        .await
+        {
+            // The --max-requests limit was reached, no more requests can be made.
+            users_finished.fetch_add(1, Ordering::SeqCst);
+            break;
+        }
 
-        // Prepare to sleep for a random value from min_wait to max_wait.
-        let wait_time = if thread_user.max_wait > 0 {
-            rand::thread_rng().gen_range(thread_user.min_wait, thread_user.max_wait)
-        } else {
-            0
-        };
-        // Counter to track how long we've slept, waking regularly to check for messages.
-        let mut slept: usize = 0;
-
-        // Check if the parent thread has sent us any messages.
-        let mut in_sleep_loop = true;
-        while in_sleep_loop {
-            let mut message = thread_receiver.try_recv();
-            while message.is_ok() {
-                match message.unwrap() {
-                    // Time to exit.
-                    GooseUserCommand::EXIT => {
-                        // No need to reset per-thread counters, we're exiting and memory will be freed
-                        thread_continue = false;
-                    }
-                    command => {
-                        debug!("ignoring unexpected GooseUserCommand: {:?}", command);
-                    }
-                }
-                message = thread_receiver.try_recv();
-            }
-            if thread_continue && thread_user.max_wait > 0 {
-                let sleep_duration = time::Duration::from_secs(1);
+        // Optionally stop after running all tasks --iterations times.
+        let iteration_complete = weighted_bucket_position + 1
+            >= thread_user.weighted_tasks[weighted_bucket].len()
+            && weighted_bucket + 1 >= thread_user.weighted_tasks.len();
+        if iteration_complete {
+            iterations += 1;
+            if Some(iterations) == thread_user.config.iterations {
                 debug!(
-                    "user {} from {} sleeping {:?} second...",
-                    thread_number, thread_task_set.name, sleep_duration
+                    "user {} from {} completed {} iterations",
+                    thread_number, thread_task_set.name, iterations
                 );
-                tokio::time::delay_for(sleep_duration).await;
-                slept += 1;
-                if slept > wait_time {
-                    in_sleep_loop = false;
-                }
-            } else {
-                in_sleep_loop = false;
+                users_finished.fetch_add(1, Ordering::SeqCst);
+                break;
             }
         }
 
+        // Prepare to sleep as long as the task set's wait time strategy requires. When
+        // running at an arrival rate there's no need to sleep after an iteration, as the
+        // parent thread schedules when the next one starts.
+        let wait_time = if thread_ready.is_some() && iteration_complete {
+            time::Duration::from_secs(0)
+        } else {
+            thread_task_set.wait_time.wait_time(task_started.elapsed())
+        };
+        if wait_time.as_nanos() > 0 {
+            debug!(
+                "user {} from {} sleeping {:?}...",
+                thread_number, thread_task_set.name, wait_time
+            );
+        }
+        // Sleep, waking immediately if the parent thread tells us to exit.
+        thread_continue = user_wait(wait_time, &mut thread_receiver).await;
+
         // Move to the next task in thread_user.weighted_tasks.
         weighted_bucket_position += 1;
         thread_user
@@ -157,7 +173,9 @@ pub async fn user_main(
             .store(weighted_bucket_position, Ordering::SeqCst);
     }
 
-    // User is exiting, first invoke the weighted on_stop tasks.
+    // User is exiting, first invoke the weighted on_stop tasks. These requests don't
+    // count toward --max-requests, so users can always clean up.
+    thread_user.request_counter = None;
     if !thread_user.weighted_on_stop_tasks.is_empty() {
         for mut sequence in thread_user.weighted_on_stop_tasks.clone() {
             if sequence.len() > 1 {
@@ -196,13 +214,121 @@ pub async fn user_main(
     }
 }
 
-// Invoke the task function, collecting task metrics.
+// Sleep for wait_time, or until the parent thread tells the user to exit. Returns false
+// if the user should exit.
+async fn user_wait(
+    wait_time: time::Duration,
+    thread_receiver: &mut mpsc::UnboundedReceiver<GooseUserCommand>,
+) -> bool {
+    // Check if the parent thread has already sent us any messages.
+    loop {
+        match thread_receiver.try_recv() {
+            Ok(command) => {
+                if !handle_command(command, thread_receiver).await {
+                    return false;
+                }
+            }
+            Err(TryRecvError::Empty) => break,
+            // The parent thread has gone away, time to exit.
+            Err(TryRecvError::Closed) => return false,
+        }
+    }
+
+    if wait_time.as_nanos() == 0 {
+        return true;
+    }
+
+    // Sleep, waking immediately if the parent thread sends us a message.
+    let mut delay = tokio::time::delay_for(wait_time);
+    loop {
+        tokio::select! {
+            _ = &mut delay => return true,
+            command = thread_receiver.recv() => match command {
+                Some(command) => {
+                    if !handle_command(command, thread_receiver).await {
+                        return false;
+                    }
+                }
+                // The parent thread has gone away, time to exit.
+                None => return false,
+            },
+        }
+    }
+}
+
+// Tell the parent thread this user is ready to start an iteration, and wait until it
+// is scheduled. Returns false if the user should exit instead.
+async fn user_wait_for_iteration(
+    thread_ready: &mpsc::UnboundedSender<oneshot::Sender<()>>,
+    thread_receiver: &mut mpsc::UnboundedReceiver<GooseUserCommand>,
+) -> bool {
+    let (start_sender, mut start_receiver) = oneshot::channel();
+    if thread_ready.send(start_sender).is_err() {
+        // The parent thread has gone away, time to exit.
+        return false;
+    }
+
+    loop {
+        tokio::select! {
+            // If the parent thread drops the sender the load test is over.
+            start = &mut start_receiver => return start.is_ok(),
+            command = thread_receiver.recv() => match command {
+                Some(command) => {
+                    if !handle_command(command, thread_receiver).await {
+                        return false;
+                    }
+                }
+                // The parent thread has gone away, time to exit.
+                None => return false,
+            },
+        }
+    }
+}
+
+// Process a command from the parent thread, returning false if the user should exit.
+async fn handle_command(
+    command: GooseUserCommand,
+    thread_receiver: &mut mpsc::UnboundedReceiver<GooseUserCommand>,
+) -> bool {
+    match command {
+        // Time to exit.
+        GooseUserCommand::EXIT => {
+            // No need to reset per-thread counters, we're exiting and memory will be freed
+            false
+        }
+        // Stop running tasks until resumed. The GooseUser and its client are kept, so
+        // the session and any cookies survive the pause.
+        GooseUserCommand::PAUSE => {
+            debug!("user paused");
+            loop {
+                match thread_receiver.recv().await {
+                    Some(GooseUserCommand::RESUME) => {
+                        debug!("user resumed");
+                        return true;
+                    }
+                    // Exit, even while paused.
+                    Some(GooseUserCommand::EXIT) | None => return false,
+                    Some(command) => {
+                        debug!("ignoring GooseUserCommand while paused: {:?}", command);
+                    }
+                }
+            }
+        }
+        command => {
+            debug!("ignoring unexpected GooseUserCommand: {:?}", command);
+            true
+        }
+    }
+}
+
+// Invoke the task function, collecting task metrics. Returns false if the task was
+// interrupted because the --max-requests limit was reached.
 async fn invoke_task_function(
     function: &GooseTaskFunction,
     thread_user: &GooseUser,
     task_index: usize,
     thread_task_name: &str,
-) {
+) -> bool {
     let started = time::Instant::now();
     let mut raw_task = GooseRawTask::new(
         thread_user.started.elapsed().as_millis(),
@@ -211,12 +337,16 @@ async fn invoke_task_function(
         thread_task_name.to_string(),
         thread_user.weighted_users_index,
     );
-    let success = function(&thread_user).await.is_ok();
-    raw_task.set_time(started.elapsed().as_millis(), success);
+    let result = function(&thread_user).await;
+    // Don't record tasks that were interrupted by reaching --max-requests.
+    if let Err(GooseTaskError::MaxRequestsReached) = result {
+        return false;
+    }
+    raw_task.set_time(started.elapsed().as_millis(), result.is_ok());
 
     // Exit if all metrics or task metrics are disabled.
     if thread_user.config.no_metrics || thread_user.config.no_task_metrics {
-        return;
+        return true;
     }
 
     // Otherwise send metrics to parent.
@@ -224,4 +354,5 @@ async fn invoke_task_function(
         // Best effort metrics.
         let _ = parent.send(GooseMetric::Task(raw_task));
     }
+    true
 }
@@ -5,7 +5,15 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time;
 
+use lazy_static::lazy_static;
 use regex::Regex;
+use std::future::Future;
+use std::net::SocketAddr;
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use crate::GooseError;
 
 /// Parse a string representing a time span and return the number of seconds.
 /// Valid formats are: 20, 20s, 3m, 2h, 1h20m, 3h30m10s, etc.
@@ -46,18 +54,55 @@ pub fn parse_timespan(time_str: &str) -> usize {
     }
 }
 
-/// Sleep for a specified duration, minus an time spent doing other things.
-pub async fn sleep_minus_drift(
-    duration: tokio::time::Duration,
-    drift: tokio::time::Instant,
-) -> tokio::time::Instant {
-    let elapsed = drift.elapsed();
-    if (duration - elapsed).as_nanos() > 0 {
-        tokio::time::delay_for(duration - elapsed).await;
-    } else {
-        warn!("sleep_minus_drift: drift was greater than or equal to duration, not sleeping");
+/// Parse a string of semicolon separated `value,timespan` steps, such as
+/// `10,30s;100,5m;0,30s`, as used by `--test-plan` and `--throttle-schedule`. The
+/// `unit` describes what the values are in error messages.
+pub fn parse_steps(
+    option: &str,
+    steps: &str,
+    unit: &str,
+) -> Result<Vec<(usize, time::Duration)>, GooseError> {
+    let timespan = Regex::new(r"^(\d+|(\d+h)?(\d+m)?(\d+s)?)$").unwrap();
+    let invalid = |detail: String| GooseError::InvalidOption {
+        option: option.to_string(),
+        value: steps.to_string(),
+        detail,
+    };
+
+    // Allow a trailing semicolon.
+    let trimmed = steps.trim();
+    let trimmed = trimmed.strip_suffix(';').unwrap_or(trimmed);
+
+    let mut parsed = Vec::new();
+    for step in trimmed.split(';').map(str::trim) {
+        let parts: Vec<&str> = step.split(',').map(str::trim).collect();
+        if parts.len() != 2 {
+            return Err(invalid(format!(
+                "Step \"{}\" must be formatted as {},timespan (ie 10,30s).",
+                step, unit
+            )));
+        }
+        let value = match usize::from_str(parts[0]) {
+            Ok(v) => v,
+            Err(_) => {
+                return Err(invalid(format!(
+                    "Step \"{}\" must start with a number of {}.",
+                    step, unit
+                )))
+            }
+        };
+        if parts[1].is_empty() || !timespan.is_match(parts[1]) {
+            return Err(invalid(format!(
+                "Step \"{}\" must end with a timespan (30s, 20m, 3h, 1h30m, etc).",
+                step
+            )));
+        }
+        parsed.push((
+            value,
+            time::Duration::from_secs(parse_timespan(parts[1]) as u64),
+        ));
     }
-    tokio::time::Instant::now()
+    Ok(parsed)
 }
 
 /// Calculate the greatest commond divisor using binary GCD (or Stein's) algorithm.
@@ -75,6 +120,11 @@ pub fn gcd(u: usize, v: usize) -> usize {
     }
 }
 
+/// Calculate the absolute difference between two usizes.
+pub fn abs_diff(a: usize, b: usize) -> usize {
+    max(a, b) - min(a, b)
+}
+
 /// Calculate median for a BTreeMap of usizes.
 pub fn median(
     btree: &BTreeMap<usize, usize>,
@@ -137,6 +187,128 @@ pub fn setup_ctrlc_handler(canceled: &Arc<AtomicBool>) {
     }
 }
 
+/// Build the address to listen on from an optional host and port, returning None if
+/// neither is configured.
+pub fn listen_address(
+    host: &str,
+    port: Option<u16>,
+    default_host: &str,
+    default_port: u16,
+) -> Option<String> {
+    if host.is_empty() && port.is_none() {
+        return None;
+    }
+    let host = if host.is_empty() { default_host } else { host };
+    Some(format!("{}:{}", host, port.unwrap_or(default_port)))
+}
+
+lazy_static! {
+    /// Runs the controllers and endpoints on a dedicated thread, so busy GooseUsers can't
+    /// starve them however many threads the load test runs on.
+    static ref SERVER_RUNTIME: tokio::runtime::Handle = {
+        let mut runtime = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .expect("failed to build server runtime");
+        let handle = runtime.handle().clone();
+        std::thread::Builder::new()
+            .name("goose-servers".to_string())
+            .spawn(move || runtime.block_on(futures::future::pending::<()>()))
+            .expect("failed to start server thread");
+        handle
+    };
+}
+
+/// Bind a server named `name` to `address`, serving each connection with `handler`,
+/// returning the bound address and a channel used to shut it down when the load test
+/// ends.
+pub async fn spawn_server<H, F>(
+    address: &str,
+    name: &'static str,
+    handler: H,
+) -> Result<(SocketAddr, oneshot::Sender<()>), std::io::Error>
+where
+    H: Fn(TcpStream) -> F + Send + 'static,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let address = address.to_string();
+    SERVER_RUNTIME
+        .spawn(async move {
+            let listener = TcpListener::bind(&address).await?;
+            let local_address = listener.local_addr()?;
+            let (shutdown_tx, shutdown_rx) = oneshot::channel();
+            tokio::spawn(accept_connections(listener, shutdown_rx, name, handler));
+            Ok((local_address, shutdown_tx))
+        })
+        .await
+        // The server thread panicked.
+        .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Interrupted, e)))
+}
+
+/// Accept connections until told to shut down, serving each on its own task.
+async fn accept_connections<H, F>(
+    mut listener: TcpListener,
+    mut shutdown: oneshot::Receiver<()>,
+    name: &'static str,
+    handler: H,
+) where
+    H: Fn(TcpStream) -> F,
+    F: Future<Output = ()> + Send + 'static,
+{
+    loop {
+        tokio::select! {
+            connection = listener.accept() => match connection {
+                Ok((socket, address)) => {
+                    debug!("{} connection from {}", name, address);
+                    tokio::spawn(handler(socket));
+                }
+                Err(e) => warn!("{} failed to accept connection: {}", name, e),
+            },
+            // The load test is over.
+            _ = &mut shutdown => break,
+        }
+    }
+    debug!("{} exiting", name);
+}
+
+/// The most bytes read from an HTTP request before giving up on finding its end.
+const MAX_HTTP_REQUEST_SIZE: usize = 8192;
+
+/// Read the headers of an HTTP request, returning its method and path. The request
+/// body is never needed by the pages Goose serves.
+pub async fn read_http_request(socket: &mut TcpStream) -> Option<(String, String)> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        match socket.read(&mut buffer).await {
+            Ok(0) | Err(_) => return None,
+            Ok(read) => request.extend_from_slice(&buffer[..read]),
+        }
+        if request.len() > MAX_HTTP_REQUEST_SIZE {
+            return None;
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    Some((
+        request_line.next().unwrap_or("").to_string(),
+        request_line.next().unwrap_or("").to_string(),
+    ))
+}
+
+/// Build a complete HTTP response.
+pub fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,6 +352,13 @@ mod tests {
         assert_eq!(gcd(gcd(25, 7425), gcd(15, 9025)), 5);
     }
 
+    #[test]
+    fn absolute_difference() {
+        assert_eq!(abs_diff(3, 10), 7);
+        assert_eq!(abs_diff(10, 3), 7);
+        assert_eq!(abs_diff(4, 4), 0);
+    }
+
     #[test]
     fn median_test() {
         // Simple median test - add 3 numbers and pick the middle one.
@@ -254,6 +433,15 @@ mod tests {
         assert_eq!(truncate_string("abcde", 2), "..");
     }
 
+    #[test]
+    fn response() {
+        let response = http_response("200 OK", "text/plain", "hello");
+        assert_eq!(
+            response,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello"
+        );
+    }
+
     #[test]
     fn timer() {
         use std::thread;
@@ -0,0 +1,161 @@
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::controller::{self, ControllerCommand};
+use crate::metrics::GooseMetrics;
+use crate::util;
+use crate::GooseControl;
+
+/// How often a metrics snapshot is sent to each WebSocket client.
+const WEBSOCKET_METRICS_EVERY: time::Duration = time::Duration::from_secs(1);
+
+/// A command received from a WebSocket client, for example
+/// `{"command": "users", "value": 50}`.
+#[derive(Debug, Deserialize)]
+struct WebSocketRequest {
+    /// A controller command, such as `users`, `pause` or `metrics`.
+    command: String,
+    /// The value required by some commands.
+    value: Option<usize>,
+}
+
+/// A message sent to WebSocket clients, serialized as JSON with a `type` field.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WebSocketMessage {
+    /// A snapshot of the current metrics, sent regularly and in reply to `metrics`.
+    /// Replies include the `command` they answer, distinguishing them from the
+    /// regular snapshots.
+    Metrics {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        command: Option<String>,
+        metrics: Box<GooseMetrics>,
+    },
+    /// The result of a command.
+    Response {
+        command: String,
+        success: bool,
+        error: Option<String>,
+    },
+}
+
+/// Start the WebSocket controller on `address`, streaming metrics snapshots to each
+/// client.
+pub async fn spawn_websocket(
+    address: &str,
+    sender: mpsc::UnboundedSender<GooseControl>,
+) -> Result<oneshot::Sender<()>, std::io::Error> {
+    let (address, shutdown) = util::spawn_server(address, "websocket", move |socket| {
+        websocket_connection(socket, sender.clone())
+    })
+    .await?;
+    info!("websocket controller listening on {}", address);
+    Ok(shutdown)
+}
+
+/// Request a snapshot of the current metrics from the running load test.
+async fn metrics_snapshot(sender: &mpsc::UnboundedSender<GooseControl>) -> Option<GooseMetrics> {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    sender.send(GooseControl::Metrics(reply_tx)).ok()?;
+    reply_rx.await.ok()
+}
+
+/// Process a text message from a WebSocket client, returning the reply.
+async fn handle_message(
+    text: &str,
+    sender: &mpsc::UnboundedSender<GooseControl>,
+) -> Option<WebSocketMessage> {
+    let request: WebSocketRequest = match serde_json::from_str(text) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(WebSocketMessage::Response {
+                command: String::new(),
+                success: false,
+                error: Some(format!("invalid request: {}", e)),
+            })
+        }
+    };
+    let command = request.command.to_lowercase();
+    let error = match controller::parse_control(&command, request.value) {
+        Ok(ControllerCommand::Control(control)) => {
+            debug!("websocket command: {:?}", control);
+            if sender.send(control).is_ok() {
+                None
+            } else {
+                Some("load test is no longer running".to_string())
+            }
+        }
+        Ok(ControllerCommand::Metrics) => {
+            return metrics_snapshot(sender)
+                .await
+                .map(|metrics| WebSocketMessage::Metrics {
+                    command: Some(command),
+                    metrics: Box::new(metrics),
+                })
+        }
+        Ok(_) => Some(format!("unsupported command: {}", command)),
+        Err(e) => Some(e),
+    };
+    Some(WebSocketMessage::Response {
+        command,
+        success: error.is_none(),
+        error,
+    })
+}
+
+/// Stream metrics snapshots to one WebSocket client, and process its commands, until
+/// it disconnects or the load test ends.
+async fn websocket_connection(socket: TcpStream, sender: mpsc::UnboundedSender<GooseControl>) {
+    let websocket = match tokio_tungstenite::accept_async(socket).await {
+        Ok(websocket) => websocket,
+        Err(e) => {
+            info!("websocket handshake failed: {}", e);
+            return;
+        }
+    };
+    let (mut outgoing, mut incoming) = websocket.split();
+    let mut snapshot_timer = time::interval(WEBSOCKET_METRICS_EVERY);
+    loop {
+        let reply = tokio::select! {
+            _ = snapshot_timer.tick() => match metrics_snapshot(&sender).await {
+                Some(metrics) => WebSocketMessage::Metrics {
+                    command: None,
+                    metrics: Box::new(metrics),
+                },
+                // The load test is over.
+                None => break,
+            },
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => match handle_message(&text, &sender).await {
+                    Some(reply) => reply,
+                    None => break,
+                },
+                Some(Ok(Message::Ping(data))) => {
+                    if outgoing.send(Message::Pong(data)).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Ignore binary and pong messages.
+                Some(Ok(_)) => continue,
+            },
+        };
+        let json = match serde_json::to_string(&reply) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("failed to serialize websocket message: {}", e);
+                continue;
+            }
+        };
+        if outgoing.send(Message::Text(json)).await.is_err() {
+            break;
+        }
+    }
+    let _ = outgoing.close().await;
+    debug!("websocket connection closed");
+}
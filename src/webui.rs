@@ -0,0 +1,52 @@
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+
+use crate::util::{self, http_response, read_http_request};
+
+/// The self-contained web UI page, which connects to the WebSocket controller.
+const WEBUI_HTML: &str = include_str!("webui.html");
+
+/// Serve the web UI page on `address`, pointing it at the WebSocket controller's port.
+pub async fn spawn_webui(
+    address: &str,
+    websocket_port: u16,
+) -> Result<oneshot::Sender<()>, std::io::Error> {
+    let page = WEBUI_HTML.replace("{{WEBSOCKET_PORT}}", &websocket_port.to_string());
+    let (address, shutdown) = util::spawn_server(address, "web UI", move |socket| {
+        webui_connection(socket, page.clone())
+    })
+    .await?;
+    info!("web UI listening on http://{}/", address);
+    Ok(shutdown)
+}
+
+/// Reply to one HTTP request, serving the page for `GET /`.
+async fn webui_connection(mut socket: TcpStream, page: String) {
+    let (method, path) = match read_http_request(&mut socket).await {
+        Some(request) => request,
+        None => return,
+    };
+    let response = match (method.as_str(), path.as_str()) {
+        ("GET", "/") | ("GET", "/index.html") => {
+            http_response("200 OK", "text/html; charset=utf-8", &page)
+        }
+        ("GET", _) => http_response("404 Not Found", "text/plain", "not found\n"),
+        _ => http_response(
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n",
+        ),
+    };
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webui_page() {
+        assert!(WEBUI_HTML.contains("{{WEBSOCKET_PORT}}"));
+    }
+}
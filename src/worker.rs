@@ -6,7 +6,8 @@ use url::Url;
 
 use crate::goose::{GooseUser, GooseUserCommand};
 use crate::manager::GooseUserInitializer;
-use crate::metrics::{GooseRequestMetrics, GooseTaskMetrics};
+use crate::metrics::{GooseFailureMetrics, GooseRequestMetrics, GooseTaskMetrics};
+use crate::test_plan::TestPlan;
 use crate::util;
 use crate::{get_worker_id, GooseAttack, GooseConfiguration, WORKER_ID};
 
@@ -19,6 +20,8 @@ pub enum GaggleMetrics {
     Requests(GooseRequestMetrics),
     /// Goose task metrics.
     Tasks(GooseTaskMetrics),
+    /// Why requests failed.
+    Failures(GooseFailureMetrics),
 }
 
 // If pipe closes unexpectedly, exit.
@@ -124,8 +127,6 @@ pub async fn worker_main(goose_attack: &GooseAttack) -> GooseAttack {
         let user = GooseUser::new(
             initializer.task_sets_index,
             Url::parse(&initializer.base_url).unwrap(),
-            initializer.min_wait,
-            initializer.max_wait,
             &initializer.config,
             goose_attack.metrics.hash,
         )
@@ -135,7 +136,7 @@ pub async fn worker_main(goose_attack: &GooseAttack) -> GooseAttack {
         weighted_users.push(user);
         if hatch_rate == None {
             hatch_rate = Some(
-                1.0 / (initializer.config.hatch_rate as f32
+                1.0 / (initializer.config.hatch_rate.unwrap_or(1) as f32
                     / (initializer.config.expect_workers as f32)),
             );
             config = initializer.config;
@@ -213,10 +214,18 @@ pub async fn worker_main(goose_attack: &GooseAttack) -> GooseAttack {
     } else {
         worker_goose_attack.run_time = 0;
     }
+    // Launch this worker's share of users at its share of the hatch_rate.
+    worker_goose_attack.users = weighted_users.len();
+    let test_plan = TestPlan::from_hatch_interval(
+        weighted_users.len(),
+        sleep_duration,
+        worker_goose_attack.run_time,
+        worker_goose_attack.configuration.no_reset_metrics,
+    );
     worker_goose_attack.weighted_users = weighted_users;
     worker_goose_attack.configuration.worker = true;
     worker_goose_attack
-        .launch_users(sleep_duration, Some(manager))
+        .launch_users(test_plan, Some(manager))
         .await
         .map_err(|error| eprintln!("{:?} worker_id({})", error, get_worker_id()))
         .expect("failed to launch GooseAttack")
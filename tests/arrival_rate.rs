@@ -0,0 +1,104 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const SLOW_PATH: &str = "/slow";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn get_slow(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(SLOW_PATH).await?;
+    // Simulate a slow server response.
+    tokio::time::delay_for(Duration::from_millis(500)).await;
+    Ok(())
+}
+
+#[test]
+/// Start 10 iterations per second with enough users to run them all.
+fn test_arrival_rate() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.users = Some(2);
+    config.hatch_rate = Some(2);
+    config.run_time = "2".to_string();
+    config.arrival_rate = Some(10);
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                // The wait time is ignored at the end of each iteration.
+                .set_wait_time(Duration::from_secs(5), Duration::from_secs(5))
+                .unwrap(),
+        )
+        .execute()
+        .unwrap();
+
+    // Metrics are reset after the users are launched, and then about 20 iterations
+    // start during the 2 second run time.
+    assert!(goose_metrics.iterations >= 15);
+    assert!(goose_metrics.iterations <= 25);
+    assert!(goose_metrics.dropped_iterations == 0);
+    assert!(index.times_called() >= goose_metrics.iterations);
+}
+
+#[test]
+/// Drop iterations when all users are busy.
+fn test_arrival_rate_dropped_iterations() {
+    let server = MockServer::start();
+
+    let slow = Mock::new()
+        .expect_method(GET)
+        .expect_path(SLOW_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.run_time = "2".to_string();
+    config.arrival_rate = Some(10);
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_slow)))
+        .execute()
+        .unwrap();
+
+    // The single user can only run about 2 iterations per second, the rest are dropped.
+    assert!(goose_metrics.iterations >= 2);
+    assert!(goose_metrics.iterations <= 6);
+    assert!(goose_metrics.dropped_iterations > goose_metrics.iterations);
+    assert!(slow.times_called() >= goose_metrics.iterations);
+}
+
+#[test]
+/// The --arrival-rate option must be at least 1.
+fn test_arrival_rate_invalid() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.arrival_rate = Some(0);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
@@ -0,0 +1,124 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::{thread, time};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const STOP_PATH: &str = "/stop";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn stop(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(STOP_PATH).await?;
+    Ok(())
+}
+
+// Read from the controller until it displays its prompt.
+fn read_reply(stream: &mut TcpStream) -> String {
+    let mut reply = Vec::new();
+    let mut buffer = [0; 1024];
+    while !reply.ends_with(b"goose> ") {
+        let read = stream.read(&mut buffer).unwrap();
+        assert!(read > 0, "controller closed connection");
+        reply.extend_from_slice(&buffer[..read]);
+    }
+    String::from_utf8(reply).unwrap()
+}
+
+// Send a command to the controller, returning its reply.
+fn send_command(stream: &mut TcpStream, command: &str) -> String {
+    stream
+        .write_all(format!("{}\n", command).as_bytes())
+        .unwrap();
+    read_reply(stream)
+}
+
+#[test]
+/// Steer a running load test with the TCP controller.
+fn test_controller() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    // Find an available port for the controller.
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(1);
+    config.hatch_rate = Some(10);
+    // Run until stopped by the controller.
+    config.run_time = "".to_string();
+    config.no_metrics = false;
+    config.controller_port = Some(port);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .set_wait_time(
+                    time::Duration::from_millis(100),
+                    time::Duration::from_millis(100),
+                )
+                .unwrap()
+                .register_task(task!(get_index))
+                .register_task(task!(stop).set_on_stop()),
+        );
+    let load_test = thread::spawn(move || goose_attack.execute().unwrap());
+
+    // Connect to the controller once it's listening.
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = TcpStream::connect(("127.0.0.1", port)) {
+            stream = Some(s);
+            break;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    let mut stream = stream.expect("failed to connect to controller");
+    read_reply(&mut stream);
+
+    assert!(send_command(&mut stream, "help").contains("reset-metrics"));
+    assert!(send_command(&mut stream, "users").starts_with("error:"));
+    assert!(send_command(&mut stream, "launch 5").starts_with("error:"));
+
+    // Launch 3 more users.
+    assert!(send_command(&mut stream, "hatch-rate 20").starts_with("ok"));
+    assert!(send_command(&mut stream, "users 4").starts_with("ok"));
+    // Allow time to allocate the new users, which builds their HTTP clients.
+    thread::sleep(time::Duration::from_millis(2000));
+    assert!(send_command(&mut stream, "metrics").contains("GET /"));
+
+    // Pause and resume, then reset the metrics.
+    assert!(send_command(&mut stream, "pause").starts_with("ok"));
+    assert!(send_command(&mut stream, "resume").starts_with("ok"));
+    assert!(send_command(&mut stream, "reset-metrics").starts_with("ok"));
+    assert!(send_command(&mut stream, "stop").starts_with("ok"));
+
+    let goose_metrics = load_test.join().unwrap();
+
+    assert!(goose_metrics.users == 4);
+    assert!(index.times_called() > 0);
+    // All users ran their on_stop task when the load test stopped.
+    assert!(stop_path.times_called() == 4);
+}
@@ -0,0 +1,101 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::goose::GooseMethod;
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const ERROR_PATH: &str = "/error";
+const TEXT_PATH: &str = "/text";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn get_error(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(ERROR_PATH).await?;
+    Ok(())
+}
+
+pub async fn get_text(user: &GooseUser) -> GooseTaskResult {
+    let mut goose = user.get(TEXT_PATH).await?;
+    if let Ok(response) = goose.response {
+        if let Ok(text) = response.text().await {
+            if !text.contains("this string must exist") {
+                return user.set_failure("string missing", &mut goose.request, None, None);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+/// Failed requests are grouped by method, name and error.
+fn test_failures() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let error = Mock::new()
+        .expect_method(GET)
+        .expect_path(ERROR_PATH)
+        .return_status(503)
+        .create_on(&server);
+    let text = Mock::new()
+        .expect_method(GET)
+        .expect_path(TEXT_PATH)
+        .return_status(200)
+        .return_body("unexpected text")
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.no_reset_metrics = true;
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(get_error))
+                .register_task(task!(get_text)),
+        )
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    assert!(error.times_called() > 0);
+    assert!(text.times_called() > 0);
+
+    // Successful requests aren't failures.
+    assert_eq!(goose_metrics.failures.len(), 2);
+    let key = (
+        GooseMethod::GET,
+        ERROR_PATH.to_string(),
+        "503 Service Unavailable".to_string(),
+    );
+    let failure = &goose_metrics.failures[&key];
+    assert_eq!(failure.name, ERROR_PATH);
+    assert_eq!(failure.occurrences, error.times_called());
+    let key = (
+        GooseMethod::GET,
+        TEXT_PATH.to_string(),
+        "string missing".to_string(),
+    );
+    let failure = &goose_metrics.failures[&key];
+    assert_eq!(failure.error, "string missing");
+    assert_eq!(failure.occurrences, text.times_called());
+
+    // Failures are displayed with the other metrics, and exported in the summary.
+    assert!(goose_metrics
+        .to_string()
+        .contains("| 503 Service Unavailable"));
+    let summary: serde_json::Value = serde_json::from_str(&goose_metrics.summary_json()).unwrap();
+    assert_eq!(summary["failures"].as_array().unwrap().len(), 2);
+}
@@ -45,6 +45,7 @@ fn test_gaggle() {
     worker_configuration.worker = true;
     worker_configuration.host = "".to_string();
     worker_configuration.users = None;
+    worker_configuration.hatch_rate = None;
     worker_configuration.no_metrics = false;
     worker_configuration.run_time = "".to_string();
     // Can't change this on the worker.
@@ -66,7 +67,7 @@ fn test_gaggle() {
     // Start manager instance in current thread and run a distributed load test.
     let mut manager_configuration = common::build_configuration(&server);
     manager_configuration.users = Some(2);
-    manager_configuration.hatch_rate = 4;
+    manager_configuration.hatch_rate = Some(4);
     manager_configuration.manager = true;
     manager_configuration.expect_workers = 2;
     manager_configuration.run_time = "3".to_string();
@@ -0,0 +1,94 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Record exact response and task times in HDR histograms.
+fn test_histogram() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.no_task_metrics = false;
+    config.histogram_digits = Some(3);
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+
+    // Every response time was recorded in the histogram.
+    let request = goose_metrics.requests.get("GET /").unwrap();
+    let histogram = request.histogram.as_ref().unwrap();
+    assert_eq!(histogram.significant_digits(), 3);
+    assert_eq!(histogram.len() as usize, request.response_time_counter);
+
+    // Every task run-time was recorded in the histogram.
+    let task = &goose_metrics.tasks[0][0];
+    let histogram = task.histogram.as_ref().unwrap();
+    assert_eq!(histogram.len() as usize, task.counter);
+}
+
+#[test]
+/// Without --histogram-digits only rounded times are recorded.
+fn test_histogram_disabled() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.no_task_metrics = false;
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    let request = goose_metrics.requests.get("GET /").unwrap();
+    assert!(request.histogram.is_none());
+    assert!(goose_metrics.tasks[0][0].histogram.is_none());
+}
+
+#[test]
+/// The --histogram-digits option must be between 1 and 5.
+fn test_histogram_invalid() {
+    let server = MockServer::start();
+
+    for digits in &[0, 6] {
+        let mut config = common::build_configuration(&server);
+        config.no_metrics = false;
+        config.histogram_digits = Some(*digits);
+        let goose_attack = crate::GooseAttack::initialize_with_config(config)
+            .setup()
+            .unwrap()
+            .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+            .execute();
+        assert!(goose_attack.is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+fn cleanup_file(history_file: &str) {
+    if std::path::Path::new(history_file).exists() {
+        std::fs::remove_file(history_file).expect("failed to delete history file");
+    }
+}
+
+/// Build a configuration recording metrics history every second.
+fn build_configuration(server: &MockServer) -> GooseConfiguration {
+    let mut config = common::build_configuration(server);
+    config.no_metrics = false;
+    config.run_time = "3".to_string();
+    config.history_interval = "1".to_string();
+    config
+}
+
+#[test]
+/// Record a snapshot of the metrics every --history-interval.
+fn test_history() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let config = build_configuration(&server);
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    // The 3 second load test was recorded in at least 3 intervals.
+    assert!(goose_metrics.history.len() >= 3);
+    let mut elapsed = 0;
+    for snapshot in &goose_metrics.history {
+        assert_eq!(snapshot.elapsed, elapsed + snapshot.duration);
+        elapsed = snapshot.elapsed;
+    }
+    let snapshot = &goose_metrics.history[1];
+    assert_eq!(snapshot.users, 1);
+    assert_eq!(snapshot.requests.len(), 1);
+    assert_eq!(snapshot.requests[0].name, "/");
+    assert!(snapshot.requests[0].requests_per_second > 0.0);
+    assert_eq!(
+        snapshot.requests[0].percentiles.len(),
+        goose_metrics.percentiles().len()
+    );
+
+    // The history isn't reset after all users start, so includes every request.
+    let requests: usize = goose_metrics
+        .history
+        .iter()
+        .flat_map(|snapshot| snapshot.requests.iter())
+        .map(|request| request.requests)
+        .sum();
+    assert_eq!(requests, index.times_called());
+}
+
+#[test]
+/// Write the metrics history to a CSV file.
+fn test_history_file_csv() {
+    const HISTORY_FILE: &str = "history-csv.log";
+
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = build_configuration(&server);
+    config.history_file = HISTORY_FILE.to_string();
+    config.history_format = "csv".to_string();
+    config.percentiles = "50,99.9".to_string();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    let csv = std::fs::read_to_string(HISTORY_FILE).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "elapsed,duration,users,method,name,requests,fails,requests_per_second,fails_per_second,mean_response_time,p50,p99.9"
+    );
+    assert_eq!(lines.count(), goose_metrics.history.len());
+    assert_eq!(csv, goose_metrics.history_csv());
+
+    cleanup_file(HISTORY_FILE);
+}
+
+#[test]
+/// Write the metrics history to a JSON file.
+fn test_history_file_json() {
+    const HISTORY_FILE: &str = "history-json.log";
+
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = build_configuration(&server);
+    config.history_file = HISTORY_FILE.to_string();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    let json = std::fs::read_to_string(HISTORY_FILE).unwrap();
+    let history: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        history.as_array().unwrap().len(),
+        goose_metrics.history.len()
+    );
+
+    cleanup_file(HISTORY_FILE);
+}
+
+#[test]
+/// Invalid metrics history options.
+fn test_history_invalid() {
+    let server = MockServer::start();
+
+    let mut configs = Vec::new();
+    // The interval must be at least 1 second.
+    let mut config = build_configuration(&server);
+    config.history_interval = "0".to_string();
+    configs.push(config);
+    // History requires metrics.
+    let mut config = build_configuration(&server);
+    config.no_metrics = true;
+    configs.push(config);
+    // A history file requires an interval.
+    let mut config = build_configuration(&server);
+    config.history_interval = "".to_string();
+    config.history_file = "history-invalid.log".to_string();
+    configs.push(config);
+    // A history format requires a file.
+    let mut config = build_configuration(&server);
+    config.history_format = "csv".to_string();
+    configs.push(config);
+    // Unsupported format.
+    let mut config = build_configuration(&server);
+    config.history_file = "history-invalid.log".to_string();
+    config.history_format = "xml".to_string();
+    configs.push(config);
+
+    for config in configs {
+        let goose_attack = crate::GooseAttack::initialize_with_config(config)
+            .setup()
+            .unwrap()
+            .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+            .execute();
+        assert!(goose_attack.is_err());
+    }
+}
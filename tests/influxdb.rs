@@ -0,0 +1,120 @@
+use httpmock::Method::{GET, POST};
+use httpmock::{Mock, MockServer};
+use std::fs;
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const WRITE_PATH: &str = "/write";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Write a point for each request, and the final metrics, to a file.
+fn test_influxdb_file() {
+    let influxdb_file = "influxdb-file.txt";
+
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.no_reset_metrics = true;
+    config.influxdb_file = influxdb_file.to_string();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    let lines = fs::read_to_string(influxdb_file).unwrap();
+    let requests: Vec<&str> = lines
+        .lines()
+        .filter(|line| line.starts_with("goose_request,"))
+        .collect();
+    assert!(index.times_called() > 0);
+    assert_eq!(requests.len(), index.times_called());
+    for request in requests {
+        assert!(request.starts_with("goose_request,method=GET,name=/,status_code=200,user=0 "));
+        assert!(request.contains(" elapsed="));
+        assert!(request.contains(",success=true,"));
+        // Timestamps are in nanoseconds.
+        let timestamp = request.rsplit(' ').next().unwrap();
+        assert!(timestamp.len() >= 19);
+        assert!(timestamp.parse::<u64>().is_ok());
+    }
+    assert!(lines.contains(&format!(
+        "goose_request_summary,method=GET,name=/ requests={}i,fails=0i,",
+        index.times_called()
+    )));
+
+    fs::remove_file(influxdb_file).unwrap();
+}
+
+#[test]
+/// Post points to an InfluxDB-compatible endpoint.
+fn test_influxdb_url() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let write = Mock::new()
+        .expect_method(POST)
+        .expect_path(WRITE_PATH)
+        .expect_body_contains("goose_request_summary,method=GET,name=/ ")
+        .return_status(204)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.influxdb_url = server.url(WRITE_PATH);
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    // The final metrics are posted once when the load test ends.
+    assert_eq!(write.times_called(), 1);
+}
+
+#[test]
+/// InfluxDB line protocol requires metrics and a valid URL.
+fn test_influxdb_invalid() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.influxdb_file = "influxdb-invalid.txt".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.influxdb_url = "not a url".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
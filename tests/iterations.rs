@@ -0,0 +1,141 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const ABOUT_PATH: &str = "/about.html";
+const STOP_PATH: &str = "/stop";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn get_about(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(ABOUT_PATH).await?;
+    Ok(())
+}
+
+pub async fn stop(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(STOP_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Each user runs all tasks exactly --iterations times, then stops.
+fn test_iterations() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let about = Mock::new()
+        .expect_method(GET)
+        .expect_path(ABOUT_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(3);
+    config.hatch_rate = Some(3);
+    // Run until all iterations are complete.
+    config.run_time = "".to_string();
+    config.iterations = Some(5);
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(get_about))
+                .register_task(task!(stop).set_on_stop()),
+        )
+        .execute()
+        .unwrap();
+
+    assert!(goose_metrics.users == 3);
+    // Each of the 3 users ran both tasks 5 times, and then its on_stop task.
+    assert!(index.times_called() == 15);
+    assert!(about.times_called() == 15);
+    assert!(stop_path.times_called() == 3);
+}
+
+#[test]
+/// The load test stops after making exactly --max-requests requests.
+fn test_max_requests() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(4);
+    config.hatch_rate = Some(4);
+    config.run_time = "".to_string();
+    config.max_requests = Some(25);
+    // Include requests made while launching users in the final metrics.
+    config.no_metrics = false;
+    config.no_reset_metrics = true;
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(stop).set_on_stop()),
+        )
+        .execute()
+        .unwrap();
+
+    // Exactly 25 requests were made, and then each launched user ran its on_stop
+    // task. Users that hadn't launched before the limit was reached never start.
+    assert!(index.times_called() == 25);
+    assert!(goose_metrics.users >= 1);
+    assert!(stop_path.times_called() == goose_metrics.users);
+
+    let index_metrics = goose_metrics.requests.get("GET /").unwrap();
+    assert!(index_metrics.success_count == 25);
+    assert!(index_metrics.fail_count == 0);
+}
+
+#[test]
+/// The --iterations and --max-requests options must be at least 1.
+fn test_iterations_invalid() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.iterations = Some(0);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    let mut config = common::build_configuration(&server);
+    config.max_requests = Some(0);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
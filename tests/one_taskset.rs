@@ -41,7 +41,7 @@ fn test_single_taskset() {
     config.no_metrics = false;
     // Start users in .5 seconds.
     config.users = Some(2);
-    config.hatch_rate = 4;
+    config.hatch_rate = Some(4);
     config.status_codes = true;
     config.no_reset_metrics = true;
     let goose_metrics = crate::GooseAttack::initialize_with_config(config.clone())
@@ -192,7 +192,7 @@ fn test_single_taskset_closure() {
     config.no_metrics = false;
     // Start users in .5 seconds.
     config.users = Some(test_endpoints.len());
-    config.hatch_rate = 2 * test_endpoints.len();
+    config.hatch_rate = Some(2 * test_endpoints.len());
     config.status_codes = true;
     config.no_reset_metrics = true;
 
@@ -324,7 +324,7 @@ fn test_single_taskset_reset_metrics() {
     config.no_metrics = false;
     // Start users in .5 seconds.
     config.users = Some(2);
-    config.hatch_rate = 4;
+    config.hatch_rate = Some(4);
     config.status_codes = true;
     let goose_metrics = crate::GooseAttack::initialize_with_config(config.clone())
         .setup()
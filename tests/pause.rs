@@ -0,0 +1,93 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{thread, time};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const STOP_PATH: &str = "/stop";
+
+// Set while the load test is paused, to detect tasks running while paused.
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static TASKS_WHILE_PAUSED: AtomicUsize = AtomicUsize::new(0);
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    if PAUSED.load(Ordering::SeqCst) {
+        TASKS_WHILE_PAUSED.fetch_add(1, Ordering::SeqCst);
+    }
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn stop(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(STOP_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Pause and resume a running load test with a GooseController.
+fn test_controller_pause_resume() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(2);
+    config.hatch_rate = Some(4);
+    config.run_time = "2".to_string();
+    config.no_metrics = false;
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .set_wait_time(
+                    time::Duration::from_millis(100),
+                    time::Duration::from_millis(100),
+                )
+                .unwrap()
+                .register_task(task!(get_index))
+                .register_task(task!(stop).set_on_stop()),
+        );
+
+    // Pause the load test for 2 seconds after it has run for 1 second.
+    let controller = goose_attack.controller();
+    let pauser = thread::spawn(move || {
+        thread::sleep(time::Duration::from_secs(1));
+        let paused = controller.pause();
+        // Give running tasks time to complete.
+        thread::sleep(time::Duration::from_millis(250));
+        PAUSED.store(true, Ordering::SeqCst);
+        thread::sleep(time::Duration::from_millis(1750));
+        PAUSED.store(false, Ordering::SeqCst);
+        paused && controller.resume()
+    });
+
+    let started = time::Instant::now();
+    let goose_metrics = goose_attack.execute().unwrap();
+    assert!(pauser.join().unwrap());
+
+    // No tasks ran while paused, and the paused time extended the load test.
+    assert!(TASKS_WHILE_PAUSED.load(Ordering::SeqCst) == 0);
+    assert!(index.times_called() > 0);
+    assert!(started.elapsed() >= time::Duration::from_millis(3500));
+
+    // The paused time isn't included in the metrics duration.
+    assert!(goose_metrics.duration <= 2);
+
+    // Paused users kept running, and then ran their on_stop task.
+    assert!(goose_metrics.users == 2);
+    assert!(stop_path.times_called() == 2);
+}
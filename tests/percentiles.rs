@@ -0,0 +1,95 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+/// Run a load test with metrics enabled, returning the displayed metrics.
+fn run_load_test(config: GooseConfiguration, percentiles: Option<&str>) -> GooseMetrics {
+    let mut goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)));
+    if let Some(percentiles) = percentiles {
+        goose_attack = goose_attack.set_percentiles(percentiles).unwrap();
+    }
+    goose_attack.execute().unwrap()
+}
+
+#[test]
+/// Display the percentiles configured with --percentiles.
+fn test_percentiles() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.percentiles = "90,99.9".to_string();
+    // The --percentiles option overrides set_percentiles().
+    let goose_metrics = run_load_test(config, Some("50,75"));
+
+    assert!(index.times_called() > 0);
+    assert_eq!(goose_metrics.percentiles, vec![90.0, 99.9]);
+    let summary = goose_metrics.to_string();
+    assert!(summary.contains("| 90%    | 99.9%"));
+    assert!(!summary.contains("75%"));
+}
+
+#[test]
+/// Display the percentiles configured with GooseAttack::set_percentiles().
+fn test_set_percentiles() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    let goose_metrics = run_load_test(config, Some("95,99.99"));
+
+    assert!(index.times_called() > 0);
+    let summary = goose_metrics.to_string();
+    assert!(summary.contains("| 95%    | 99.99%"));
+    assert!(!summary.contains("50%"));
+}
+
+#[test]
+/// Percentiles must be numbers greater than 0 and at most 100.
+fn test_percentiles_invalid() {
+    let server = MockServer::start();
+
+    for percentiles in &["0", "101", "50,ninety"] {
+        let mut config = common::build_configuration(&server);
+        config.percentiles = percentiles.to_string();
+        let goose_attack = crate::GooseAttack::initialize_with_config(config)
+            .setup()
+            .unwrap()
+            .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+            .execute();
+        assert!(goose_attack.is_err());
+
+        let goose_attack =
+            crate::GooseAttack::initialize_with_config(common::build_configuration(&server))
+                .setup()
+                .unwrap()
+                .set_percentiles(percentiles);
+        assert!(goose_attack.is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::{thread, time};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// Find an available port.
+fn available_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// Request a page from the Prometheus endpoint once it's listening.
+fn get(port: u16, path: &str) -> String {
+    for _ in 0..50 {
+        if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)) {
+            stream
+                .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+                .unwrap();
+            let mut page = String::new();
+            stream.read_to_string(&mut page).unwrap();
+            return page;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    panic!("failed to connect to port {}", port);
+}
+
+#[test]
+/// Scrape metrics from a running load test.
+fn test_prometheus() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let port = available_port();
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(2);
+    config.hatch_rate = Some(10);
+    config.no_metrics = false;
+    config.no_task_metrics = false;
+    // Run until stopped by the controller.
+    config.run_time = "".to_string();
+    config.prometheus_port = Some(port);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)));
+    let controller = goose_attack.controller();
+    let load_test = thread::spawn(move || goose_attack.execute().unwrap());
+
+    // Scrape until both users are running and have made requests.
+    let mut page = String::new();
+    for _ in 0..50 {
+        page = get(port, "/metrics");
+        if page.contains("goose_users 2\n")
+            && page.contains("goose_requests_total{method=\"GET\",name=\"/\",status=\"200\"}")
+        {
+            break;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    assert!(page.starts_with("HTTP/1.1 200 OK"));
+    assert!(page.contains("goose_users 2\n"));
+    assert!(page.contains("goose_requests_total{method=\"GET\",name=\"/\",status=\"200\"}"));
+    assert!(page.contains(
+        "goose_response_time_milliseconds_bucket{method=\"GET\",name=\"/\",le=\"+Inf\"}"
+    ));
+    assert!(page.contains("goose_task_runs_total{taskset=\"LoadTest\",task=\"1\"}"));
+    assert!(page.contains("goose_throttle_waits_total 0\n"));
+
+    assert!(get(port, "/missing").starts_with("HTTP/1.1 404 Not Found"));
+
+    assert!(controller.stop());
+    load_test.join().unwrap();
+    assert!(index.times_called() > 0);
+}
+
+#[test]
+/// Prometheus metrics require metrics.
+fn test_prometheus_no_metrics() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.prometheus_port = Some(available_port());
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
@@ -0,0 +1,201 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{thread, time};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const STOP_PATH: &str = "/stop";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn stop(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(STOP_PATH).await?;
+    Ok(())
+}
+
+// Set once the first user of test_controller_stop_ramp_down starts stopping.
+static RAMPING_DOWN: AtomicBool = AtomicBool::new(false);
+
+pub async fn stop_ramping_down(user: &GooseUser) -> GooseTaskResult {
+    RAMPING_DOWN.store(true, Ordering::SeqCst);
+    stop(user).await
+}
+
+#[test]
+/// Stop users gradually at the end of the load test.
+fn test_ramp_down_rate() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(4);
+    // Start all users in one second.
+    config.hatch_rate = Some(4);
+    // Stop one user every half second.
+    config.ramp_down_rate = Some(2);
+    let started = time::Instant::now();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(stop).set_on_stop()),
+        )
+        .execute()
+        .unwrap();
+
+    assert!(goose_metrics.users == 4);
+    assert!(index.times_called() > 0);
+
+    // All users ran their on_stop task.
+    assert!(stop_path.times_called() == 4);
+
+    // Hatching takes 1 second, running 1 second, and stopping the last of the
+    // users happens 1.5 seconds after the first.
+    assert!(started.elapsed() >= time::Duration::from_millis(3500));
+}
+
+#[test]
+/// Users that finished their iterations aren't ramped down.
+fn test_ramp_down_after_iterations() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(4);
+    config.hatch_rate = Some(4);
+    config.run_time = "".to_string();
+    config.iterations = Some(1);
+    // Stopping 4 running users would take 4 seconds.
+    config.ramp_down_rate = Some(1);
+    let started = time::Instant::now();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(goose_metrics.users == 4);
+    assert!(index.times_called() == 4);
+    assert!(started.elapsed() < time::Duration::from_secs(3));
+}
+
+#[test]
+/// Stop some users with a GooseController while the load test is running.
+fn test_controller_stop_users() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(4);
+    config.hatch_rate = Some(4);
+    config.run_time = "3".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(stop).set_on_stop()),
+        );
+
+    // Stop 3 users after all users have launched, while the load test is running.
+    // Allow time to allocate the users, which builds their HTTP clients.
+    let controller = goose_attack.controller();
+    let stopper = thread::spawn(move || {
+        thread::sleep(time::Duration::from_millis(2500));
+        controller.stop_users(3)
+    });
+
+    let goose_metrics = goose_attack.execute().unwrap();
+    assert!(stopper.join().unwrap());
+
+    assert!(goose_metrics.users == 4);
+    assert!(index.times_called() > 0);
+
+    // The 3 stopped users and the last user all ran their on_stop task.
+    assert!(stop_path.times_called() == 4);
+}
+
+#[test]
+/// Stopping the load test while ramping down stops all remaining users immediately.
+fn test_controller_stop_ramp_down() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(8);
+    config.hatch_rate = Some(8);
+    // Stopping all users would take 8 seconds.
+    config.ramp_down_rate = Some(1);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(stop_ramping_down).set_on_stop()),
+        );
+
+    // Stop once the first user has been ramped down.
+    let controller = goose_attack.controller();
+    let stopper = thread::spawn(move || {
+        while !RAMPING_DOWN.load(Ordering::SeqCst) {
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        assert!(controller.stop());
+        time::Instant::now()
+    });
+
+    let goose_metrics = goose_attack.execute().unwrap();
+    let stopped = stopper.join().unwrap();
+
+    assert!(goose_metrics.users == 8);
+    assert!(index.times_called() > 0);
+    assert!(stop_path.times_called() == 8);
+    assert!(stopped.elapsed() < time::Duration::from_secs(3));
+}
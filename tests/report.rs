@@ -0,0 +1,80 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+fn cleanup_file(report_file: &str) {
+    if std::path::Path::new(report_file).exists() {
+        std::fs::remove_file(report_file).expect("failed to delete report file");
+    }
+}
+
+#[test]
+/// Write the final metrics to an HTML report, with charts from the metrics history.
+fn test_report_file() {
+    const REPORT_FILE: &str = "report.html";
+
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.no_task_metrics = false;
+    config.run_time = "2".to_string();
+    config.history_interval = "1".to_string();
+    config.status_codes = true;
+    config.report_file = REPORT_FILE.to_string();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    let html = std::fs::read_to_string(REPORT_FILE).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    // The report includes the load test hash and configuration.
+    assert!(html.contains(&format!("<td>{}</td>", goose_metrics.hash)));
+    assert!(html.contains("<th>--report-file</th><td>report.html</td>"));
+    // The report includes the metrics tables.
+    assert!(html.contains("<h2>Requests</h2>"));
+    assert!(html.contains("PER REQUEST METRICS"));
+    assert!(html.contains("<h2>Tasks</h2>"));
+    assert!(html.contains("<h2>Response times</h2>"));
+    assert!(html.contains("<h2>Percentiles</h2>"));
+    assert!(html.contains("<h2>Status codes</h2>"));
+    // The metrics history is charted.
+    assert!(html.contains("<svg"));
+
+    cleanup_file(REPORT_FILE);
+}
+
+#[test]
+/// An HTML report requires metrics.
+fn test_report_file_no_metrics() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.report_file = "report-invalid.html".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
@@ -133,7 +133,7 @@ fn test_setup_teardown() {
     let mut configuration = common::build_configuration(&server);
     // Launch several user threads, confirm we still only setup and teardown one time.
     configuration.users = Some(5);
-    configuration.hatch_rate = 5;
+    configuration.hatch_rate = Some(5);
 
     let _goose_stats = crate::GooseAttack::initialize_with_config(configuration)
         .setup()
@@ -0,0 +1,82 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use goose::goose::GooseRawRequest;
+use goose::metrics::GooseRawTask;
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+/// Counts the metrics it receives.
+#[derive(Default)]
+struct Counts {
+    requests: usize,
+    tasks: usize,
+    summaries: usize,
+    summary_requests: usize,
+}
+
+/// A custom metrics sink, sharing its counts with the test.
+struct CountingSink(Arc<Mutex<Counts>>);
+impl GooseMetricsSink for CountingSink {
+    fn request(&mut self, raw_request: &GooseRawRequest) {
+        if !raw_request.update {
+            self.0.lock().unwrap().requests += 1;
+        }
+    }
+
+    fn task(&mut self, _raw_task: &GooseRawTask) {
+        self.0.lock().unwrap().tasks += 1;
+    }
+
+    fn summary(&mut self, metrics: &GooseMetrics) {
+        let mut counts = self.0.lock().unwrap();
+        counts.summaries += 1;
+        counts.summary_requests = metrics
+            .requests
+            .values()
+            .map(|request| request.success_count + request.fail_count)
+            .sum();
+    }
+}
+
+#[test]
+/// Register a custom metrics sink.
+fn test_metrics_sink() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let counts = Arc::new(Mutex::new(Counts::default()));
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.no_task_metrics = false;
+    config.no_reset_metrics = true;
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .register_metrics_sink(CountingSink(counts.clone()))
+        .execute()
+        .unwrap();
+
+    let counts = counts.lock().unwrap();
+    assert!(index.times_called() > 0);
+    assert_eq!(counts.requests, index.times_called());
+    assert_eq!(counts.tasks, index.times_called());
+    assert_eq!(counts.summaries, 1);
+    assert_eq!(counts.summary_requests, index.times_called());
+}
@@ -0,0 +1,171 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::io::Read;
+use std::net::{TcpListener, UdpSocket};
+use std::{thread, time};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Stream a line for each request to StatsD.
+fn test_statsd() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let statsd = UdpSocket::bind("127.0.0.1:0").unwrap();
+    statsd
+        .set_read_timeout(Some(time::Duration::from_secs(5)))
+        .unwrap();
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.statsd_address = statsd.local_addr().unwrap().to_string();
+    config.stream_prefix = "loadtest".to_string();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    let mut buffer = [0; 2048];
+    let size = statsd.recv(&mut buffer).unwrap();
+    let packet = String::from_utf8_lossy(&buffer[..size]);
+    let tags = "|#taskset:LoadTest,method:GET,name:/";
+    assert!(packet.contains(&format!("loadtest.request.count:1|c{}", tags)));
+    assert!(packet.contains(&format!("loadtest.request.fails:0|c{}", tags)));
+    assert!(packet.contains("loadtest.request.response_time:"));
+    assert!(index.times_called() > 0);
+}
+
+#[test]
+/// Stream aggregated metrics to Graphite.
+fn test_graphite_interval() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let graphite = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = graphite.local_addr().unwrap().to_string();
+    let received = thread::spawn(move || {
+        let (mut stream, _) = graphite.accept().unwrap();
+        let mut text = String::new();
+        stream.read_to_string(&mut text).unwrap();
+        text
+    });
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.graphite_address = address;
+    config.stream_interval = "10s".to_string();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    // The connection is closed when the load test ends, after the final interval.
+    let text = received.join().unwrap();
+    let count = format!(
+        "goose.request.count;taskset=LoadTest;method=GET;name=/ {} ",
+        index.times_called()
+    );
+    assert!(text.contains(&count));
+    assert!(text.contains("goose.request.fails;taskset=LoadTest;method=GET;name=/ 0 "));
+    assert!(text.contains("goose.request.response_time.mean;taskset=LoadTest"));
+    assert!(index.times_called() > 0);
+}
+
+#[test]
+/// Reconnect to Graphite after the connection is lost.
+fn test_graphite_reconnect() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let graphite = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = graphite.local_addr().unwrap().to_string();
+    let received = thread::spawn(move || {
+        // Close the first connection right away.
+        let (stream, _) = graphite.accept().unwrap();
+        drop(stream);
+        let (mut stream, _) = graphite.accept().unwrap();
+        let mut text = String::new();
+        stream.read_to_string(&mut text).unwrap();
+        text
+    });
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.graphite_address = address;
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    // Requests made after reconnecting are streamed.
+    let text = received.join().unwrap();
+    assert!(text.contains("goose.request.count;taskset=LoadTest;method=GET;name=/ 1 "));
+    assert!(index.times_called() > 0);
+}
+
+#[test]
+/// Streaming metrics requires an address and metrics.
+fn test_stream_invalid() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.statsd_address = "127.0.0.1:8125".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.stream_prefix = "loadtest".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.statsd_address = "127.0.0.1:8125".to_string();
+    config.graphite_address = "127.0.0.1:2003".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
@@ -0,0 +1,148 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+fn cleanup_file(summary_file: &str) {
+    if std::path::Path::new(summary_file).exists() {
+        std::fs::remove_file(summary_file).expect("failed to delete summary file");
+    }
+}
+
+/// Build a configuration tracking request and task metrics.
+fn build_configuration(server: &MockServer) -> GooseConfiguration {
+    let mut config = common::build_configuration(server);
+    config.no_metrics = false;
+    config.no_task_metrics = false;
+    config.status_codes = true;
+    // Count every request made, so metrics can be compared to the mock server.
+    config.no_reset_metrics = true;
+    config.run_time = "2".to_string();
+    config
+}
+
+#[test]
+/// Write a summary of the final metrics to a JSON file.
+fn test_summary_file_json() {
+    const SUMMARY_FILE: &str = "summary-json.log";
+
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = build_configuration(&server);
+    config.summary_file = SUMMARY_FILE.to_string();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    let json = std::fs::read_to_string(SUMMARY_FILE).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(summary["hash"], goose_metrics.hash);
+    let request = &summary["requests"][0];
+    assert_eq!(request["method"], "GET");
+    assert_eq!(request["name"], "/");
+    assert_eq!(request["requests"], index.times_called());
+    assert_eq!(request["fails"], 0);
+    assert_eq!(request["status_codes"]["200"], index.times_called());
+    assert_eq!(
+        request["percentiles"].as_array().unwrap().len(),
+        goose_metrics.percentiles().len()
+    );
+    let task = &summary["tasks"][0];
+    assert_eq!(task["taskset_name"], "LoadTest");
+    assert_eq!(task["runs"], index.times_called());
+
+    cleanup_file(SUMMARY_FILE);
+}
+
+#[test]
+/// Write a summary of the final metrics to a CSV file.
+fn test_summary_file_csv() {
+    const SUMMARY_FILE: &str = "summary-csv.log";
+
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = build_configuration(&server);
+    config.summary_file = SUMMARY_FILE.to_string();
+    config.summary_format = "csv".to_string();
+    config.percentiles = "50,95".to_string();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    let csv = std::fs::read_to_string(SUMMARY_FILE).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "type,method,name,count,fails,per_second,fails_per_second,min,mean,median,max,p50,p95,status_codes,error"
+    );
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with(&format!("request,GET,\"/\",{},0,", index.times_called())));
+    assert!(lines.next().unwrap().starts_with("task,,\"LoadTest: \","));
+    assert_eq!(lines.next(), None);
+    assert_eq!(csv, goose_metrics.summary_csv());
+
+    cleanup_file(SUMMARY_FILE);
+}
+
+#[test]
+/// Invalid metrics summary options.
+fn test_summary_invalid() {
+    let server = MockServer::start();
+
+    let mut configs = Vec::new();
+    // A summary requires metrics.
+    let mut config = build_configuration(&server);
+    config.no_metrics = true;
+    config.status_codes = false;
+    config.summary_file = "summary-invalid.log".to_string();
+    configs.push(config);
+    // A summary format requires a file.
+    let mut config = build_configuration(&server);
+    config.summary_format = "csv".to_string();
+    configs.push(config);
+    // Unsupported format.
+    let mut config = build_configuration(&server);
+    config.summary_file = "summary-invalid.log".to_string();
+    config.summary_format = "xml".to_string();
+    configs.push(config);
+
+    for config in configs {
+        let goose_attack = crate::GooseAttack::initialize_with_config(config)
+            .setup()
+            .unwrap()
+            .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+            .execute();
+        assert!(goose_attack.is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+
+const INDEX_PATH: &str = "/";
+const STOP_PATH: &str = "/stop";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn stop(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(STOP_PATH).await?;
+    Ok(())
+}
+
+/// Build a configuration without the --users, --hatch-rate and --run-time options.
+fn build_test_plan_configuration(server: &MockServer) -> GooseConfiguration {
+    let mut config = common::build_configuration(server);
+    config.users = None;
+    config.hatch_rate = None;
+    config.run_time = "".to_string();
+    config
+}
+
+#[test]
+/// Follow a test plan that launches users, then stops all of them.
+fn test_test_plan() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = build_test_plan_configuration(&server);
+    config.test_plan = "3,1s;3,1s;0,1s".to_string();
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(stop).set_on_stop()),
+        )
+        .execute()
+        .unwrap();
+
+    // Confirm that the test plan launched 3 users.
+    assert!(goose_metrics.users == 3);
+    assert!(index.times_called() > 0);
+
+    // Confirm that all 3 users ran their on_stop task when the plan stopped them.
+    assert!(stop_path.times_called() == 3);
+}
+
+#[test]
+/// Use the API to define a test plan that stops and then restarts a user.
+fn test_set_test_plan() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let stop_path = Mock::new()
+        .expect_method(GET)
+        .expect_path(STOP_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let config = build_test_plan_configuration(&server);
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .set_test_plan("2,0s;1,1s;2,1s")
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(stop).set_on_stop()),
+        )
+        .execute()
+        .unwrap();
+
+    // No more than 2 users ran at the same time.
+    assert!(goose_metrics.users == 2);
+    assert!(index.times_called() > 0);
+
+    // One user was stopped during the test plan, and two more when it finished.
+    assert!(stop_path.times_called() == 3);
+}
+
+#[test]
+/// A test plan can't be combined with --users, --hatch-rate or --run-time.
+fn test_test_plan_invalid_options() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.test_plan = "1,1s".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    // Any explicit --hatch-rate is rejected, including the default of 1.
+    for hatch_rate in &[1, 2] {
+        let mut config = build_test_plan_configuration(&server);
+        config.test_plan = "1,1s".to_string();
+        config.hatch_rate = Some(*hatch_rate);
+        let goose_attack = crate::GooseAttack::initialize_with_config(config)
+            .setup()
+            .unwrap()
+            .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+            .execute();
+        assert!(goose_attack.is_err());
+    }
+
+    let mut config = build_test_plan_configuration(&server);
+    config.test_plan = "1,1s".to_string();
+    config.run_time = "1".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
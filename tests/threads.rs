@@ -0,0 +1,56 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Run a load test on a single thread, and on a configured number of worker threads.
+fn test_threads() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    for threads in &[1, 2] {
+        let mut config = common::build_configuration(&server);
+        config.users = Some(4);
+        config.hatch_rate = Some(4);
+        config.threads = Some(*threads);
+        let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+            .setup()
+            .unwrap()
+            .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+            .execute()
+            .unwrap();
+
+        assert!(goose_metrics.users == 4);
+        assert!(index.times_called() > 0);
+    }
+}
+
+#[test]
+/// The --threads option must be at least 1.
+fn test_threads_invalid() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.threads = Some(0);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
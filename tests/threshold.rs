@@ -0,0 +1,187 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::time;
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+const ERROR_PATH: &str = "/error";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+pub async fn get_error(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(ERROR_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Thresholds that are met return the verdict with the metrics.
+fn test_thresholds_passed() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.threshold = vec!["error_rate < 1%".to_string()];
+    let goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .set_threshold(r#"p95("GET /") < 10s"#)
+        .unwrap()
+        .execute()
+        .unwrap();
+
+    assert!(index.times_called() > 0);
+    let verdict = goose_metrics.thresholds.unwrap();
+    assert!(verdict.passed);
+    // Thresholds set on the GooseAttack come first, followed by --threshold options.
+    assert_eq!(verdict.results.len(), 2);
+    assert_eq!(verdict.results[0].threshold, r#"p95("GET /") < 10s"#);
+    assert_eq!(verdict.results[1].threshold, "error_rate < 1%");
+    assert_eq!(verdict.results[1].actual, Some(0.0));
+}
+
+#[test]
+/// Thresholds that aren't met return an error with the verdict.
+fn test_thresholds_failed() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let error = Mock::new()
+        .expect_method(GET)
+        .expect_path(ERROR_PATH)
+        .return_status(500)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.threshold = vec![
+        "error_rate < 1%".to_string(),
+        r#"requests("GET /") > 0"#.to_string(),
+    ];
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .register_task(task!(get_error)),
+        )
+        .execute();
+
+    assert!(index.times_called() > 0);
+    assert!(error.times_called() > 0);
+    match goose_attack {
+        Err(GooseError::ThresholdsFailed { verdict }) => {
+            assert!(!verdict.passed);
+            assert!(!verdict.results[0].passed);
+            assert!(verdict.results[0].actual.unwrap() > 1.0);
+            assert!(verdict.results[1].passed);
+            assert_eq!(verdict.failures().len(), 1);
+        }
+        _ => panic!("thresholds should have failed"),
+    }
+}
+
+#[test]
+/// Thresholds set to abort_on_fail stop the load test once breached for their grace
+/// period.
+fn test_thresholds_abort_on_fail() {
+    let server = MockServer::start();
+
+    let error = Mock::new()
+        .expect_method(GET)
+        .expect_path(ERROR_PATH)
+        .return_status(500)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.no_reset_metrics = true;
+    config.run_time = "30".to_string();
+    config.threshold = vec!["error_rate < 20%, abort_on_fail=2s".to_string()];
+    let started = time::Instant::now();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_error)))
+        .execute();
+
+    // The load test stopped long before the run time expired.
+    assert!(started.elapsed() < time::Duration::from_secs(15));
+    assert!(error.times_called() > 0);
+    match goose_attack {
+        Err(GooseError::ThresholdsFailed { verdict }) => {
+            assert!(!verdict.results[0].passed);
+            let aborted = verdict.aborted.unwrap();
+            assert!(aborted.starts_with("error_rate < 20%, abort_on_fail=2s breached for "));
+            assert!(aborted.ends_with("(actual 100.00%)"));
+        }
+        _ => panic!("thresholds should have aborted the load test"),
+    }
+}
+
+#[test]
+/// Invalid thresholds are rejected before the load test starts.
+fn test_thresholds_invalid() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.threshold = vec!["p95 = 300ms".to_string()];
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    // Thresholds require metrics.
+    let mut config = common::build_configuration(&server);
+    config.threshold = vec!["error_rate < 1%".to_string()];
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    // The Manager doesn't evaluate thresholds while a Gaggle runs.
+    let mut config = common::build_configuration(&server);
+    config.no_metrics = false;
+    config.manager = true;
+    config.expect_workers = 1;
+    config.threshold = vec!["error_rate < 20%, abort_on_fail".to_string()];
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    match goose_attack {
+        Err(GooseError::InvalidOption { option, .. }) => assert_eq!(option, "--threshold"),
+        _ => panic!("abort_on_fail thresholds must be rejected in Gaggle mode"),
+    }
+
+    assert!(
+        crate::GooseAttack::initialize_with_config(common::build_configuration(&server))
+            .setup()
+            .unwrap()
+            .set_threshold("rps > 50ms")
+            .is_err()
+    );
+}
@@ -48,7 +48,7 @@ fn test_throttle() {
     config.throttle_requests = Some(throttle_requests);
     config.users = Some(users);
     // Start all users in half a second.
-    config.hatch_rate = users;
+    config.hatch_rate = Some(users);
     // Run for a few seconds to be sure throttle really works.
     config.run_time = run_time.to_string();
     let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
@@ -91,7 +91,7 @@ fn test_throttle() {
     config.throttle_requests = Some(throttle_requests);
     config.users = Some(users);
     // Start all users in half a second.
-    config.hatch_rate = users;
+    config.hatch_rate = Some(users);
     config.run_time = run_time.to_string();
     let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
         .setup()
@@ -125,3 +125,188 @@ fn test_throttle() {
     // Cleanup log file.
     std::fs::remove_file(METRICS_FILE).expect("failed to delete metrics log file");
 }
+
+#[test]
+/// Follow a throttle schedule that increases the allowed requests per second.
+fn test_throttle_schedule() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let first_rate = 10;
+    let second_rate = 50;
+    let run_time = 2;
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(5);
+    // Start all users in one second.
+    config.hatch_rate = Some(5);
+    config.run_time = run_time.to_string();
+    // Allow first_rate requests per second for 1 second, then second_rate.
+    config.throttle_schedule = format!("{},1s;{},1m", first_rate, second_rate);
+    let started = std::time::Instant::now();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute()
+        .unwrap();
+    let elapsed = started.elapsed().as_secs_f64();
+
+    // The schedule allows first_rate requests during its first second, and then
+    // second_rate requests per second until the load test stops, which is no sooner
+    // than run_time seconds later and no later than the load test returns.
+    let called = index.times_called() as f64;
+    assert!(called <= first_rate as f64 + second_rate as f64 * (elapsed - 1.0) + 1.0);
+    assert!(called >= (first_rate + second_rate * (run_time - 1)) as f64);
+    // Confirm the schedule advanced past its first step.
+    assert!(called > first_rate as f64 * elapsed + 1.0);
+}
+
+#[test]
+/// Change the throttle with a GooseController while the load test is running.
+fn test_controller_set_throttle_requests() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let initial_rate = 20;
+    let updated_rate = 40;
+    let run_time = 2;
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(5);
+    config.hatch_rate = Some(5);
+    config.run_time = run_time.to_string();
+    config.throttle_requests = Some(initial_rate);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)));
+
+    // Raise the throttle after the load test has run for 1 second.
+    let controller = goose_attack.controller();
+    let started = std::time::Instant::now();
+    let throttler = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        controller.set_throttle_requests(updated_rate)
+    });
+
+    let _goose_metrics = goose_attack.execute().unwrap();
+    let elapsed = started.elapsed().as_secs_f64();
+    assert!(throttler.join().unwrap());
+
+    // The throttle allows initial_rate requests during the first second, and then
+    // updated_rate requests per second until the load test stops, which is no sooner
+    // than run_time seconds later and no later than the load test returns.
+    let called = index.times_called() as f64;
+    assert!(called <= initial_rate as f64 + updated_rate as f64 * (elapsed - 1.0) + 1.0);
+    assert!(called >= (initial_rate + updated_rate * (run_time - 1)) as f64);
+    // Confirm the controller raised the throttle.
+    assert!(called > initial_rate as f64 * elapsed + 1.0);
+}
+
+#[test]
+/// The --throttle-schedule and --throttle-burst options are validated.
+fn test_throttle_invalid_options() {
+    let server = MockServer::start();
+
+    // A throttle schedule can't be combined with --throttle-requests.
+    let mut config = common::build_configuration(&server);
+    config.throttle_requests = Some(10);
+    config.throttle_schedule = "10,1s;50,1m".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    // Each step of a throttle schedule must allow at least 1 request per second.
+    let mut config = common::build_configuration(&server);
+    config.throttle_schedule = "10,1s;0,1m".to_string();
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+
+    // A burst requires the throttle to be enabled.
+    let mut config = common::build_configuration(&server);
+    config.throttle_burst = Some(10);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}
+
+#[test]
+/// Limit the requests made by one task set, and by each user of another task set.
+fn test_task_set_throttles() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+    let about = Mock::new()
+        .expect_method(GET)
+        .expect_path(ABOUT_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(4);
+    // Start all users in one second.
+    config.hatch_rate = Some(4);
+    config.run_time = "2".to_string();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("TaskSetThrottle")
+                .register_task(task!(get_index))
+                .set_throttle_requests(5)
+                .unwrap(),
+        )
+        .register_taskset(
+            taskset!("UserThrottle")
+                .register_task(task!(get_about))
+                .set_user_throttle_requests(5)
+                .unwrap(),
+        )
+        .execute()
+        .unwrap();
+
+    // The load test runs for about 3 seconds. The 2 users running the first task set
+    // share 5 requests per second, while each of the 2 users running the second task
+    // set makes 5 requests per second.
+    let index_called = index.times_called();
+    let about_called = about.times_called();
+    assert!(index_called > 5);
+    assert!(index_called <= 18);
+    assert!(about_called > 18);
+    assert!(about_called <= 34);
+}
+
+#[test]
+/// Task set throttles must allow from 1 to 1,000,000 requests per second.
+fn test_task_set_throttles_invalid() {
+    assert!(taskset!("LoadTest").set_throttle_requests(0).is_err());
+    assert!(taskset!("LoadTest")
+        .set_throttle_requests(1_000_001)
+        .is_err());
+    assert!(taskset!("LoadTest").set_user_throttle_requests(0).is_err());
+}
@@ -0,0 +1,109 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+#[test]
+/// Start a task every half second with constant pacing.
+fn test_constant_pacing() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.run_time = "2".to_string();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .set_wait_strategy(GooseWaitTime::ConstantPacing(Duration::from_millis(500)))
+                .unwrap(),
+        )
+        .execute()
+        .unwrap();
+
+    // The single user is launched and then runs for 2 more seconds, loading the
+    // index about twice a second for a total of 3 seconds.
+    let called = index.times_called();
+    assert!(called >= 5);
+    assert!(called <= 8);
+}
+
+#[test]
+/// Wait between 100 and 300 milliseconds between tasks.
+fn test_millisecond_wait_time() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.run_time = "2".to_string();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .set_wait_time(Duration::from_millis(100), Duration::from_millis(300))
+                .unwrap(),
+        )
+        .execute()
+        .unwrap();
+
+    // The single user runs for about 3 seconds, pausing at most 300 milliseconds
+    // and at least 100 milliseconds between each request.
+    let called = index.times_called();
+    assert!(called >= 9);
+    assert!(called <= 31);
+}
+
+#[test]
+/// Users exit promptly when the load test ends, even in the middle of a long wait.
+fn test_exit_while_waiting() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let mut config = common::build_configuration(&server);
+    config.run_time = "1".to_string();
+    let started = std::time::Instant::now();
+    let _goose_metrics = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .register_task(task!(get_index))
+                .set_wait_time(Duration::from_secs(30), Duration::from_secs(30))
+                .unwrap(),
+        )
+        .execute()
+        .unwrap();
+
+    // The user loaded the index once, then was told to exit while sleeping.
+    assert!(index.times_called() == 1);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
@@ -0,0 +1,146 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use serde_json::{json, Value};
+use std::net::{TcpListener, TcpStream};
+use std::{thread, time};
+use tokio_tungstenite::tungstenite::{client, Message};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// Read the next JSON message of the specified type from the WebSocket.
+fn read_message(
+    websocket: &mut tokio_tungstenite::tungstenite::WebSocket<TcpStream>,
+    message_type: &str,
+) -> Value {
+    loop {
+        if let Message::Text(text) = websocket.read_message().unwrap() {
+            let message: Value = serde_json::from_str(&text).unwrap();
+            if message["type"] == message_type {
+                return message;
+            }
+        }
+    }
+}
+
+// Send a command to the WebSocket and read the reply, skipping regular snapshots.
+fn send_command(
+    websocket: &mut tokio_tungstenite::tungstenite::WebSocket<TcpStream>,
+    request: Value,
+) -> Value {
+    let command = request["command"].clone();
+    websocket
+        .write_message(Message::Text(request.to_string()))
+        .unwrap();
+    loop {
+        if let Message::Text(text) = websocket.read_message().unwrap() {
+            let message: Value = serde_json::from_str(&text).unwrap();
+            if message["command"] == command {
+                return message;
+            }
+        }
+    }
+}
+
+#[test]
+/// Stream metrics from, and control, a running load test over a WebSocket.
+fn test_websocket() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    // Find an available port for the WebSocket controller.
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(1);
+    config.hatch_rate = Some(10);
+    // Run until stopped by the WebSocket controller.
+    config.run_time = "".to_string();
+    config.no_metrics = false;
+    config.no_reset_metrics = true;
+    config.websocket_port = Some(port);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .set_wait_time(
+                    time::Duration::from_millis(100),
+                    time::Duration::from_millis(100),
+                )
+                .unwrap()
+                .register_task(task!(get_index)),
+        );
+    let load_test = thread::spawn(move || goose_attack.execute().unwrap());
+
+    // Connect to the WebSocket controller once it's listening.
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = TcpStream::connect(("127.0.0.1", port)) {
+            stream = Some(s);
+            break;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    let url = format!("ws://127.0.0.1:{}/", port);
+    let (mut websocket, _) = client(url.as_str(), stream.unwrap()).unwrap();
+
+    // Metrics snapshots are streamed without being requested.
+    let snapshot = read_message(&mut websocket, "metrics");
+    assert!(snapshot["metrics"]["users"].as_u64().is_some());
+
+    // Regular snapshots don't include a command.
+    assert!(snapshot.get("command").is_none());
+
+    // Invalid commands are rejected.
+    let response = send_command(&mut websocket, json!({"command": "launch"}));
+    assert!(response["type"] == "response");
+    assert!(response["success"] == false);
+    let response = send_command(&mut websocket, json!({"command": "users"}));
+    assert!(response["type"] == "response");
+    assert!(response["success"] == false);
+
+    // Launch 2 more users.
+    let response = send_command(&mut websocket, json!({"command": "users", "value": 3}));
+    assert!(response["type"] == "response");
+    assert!(response["success"] == true);
+
+    // Request snapshots of the metrics, which reply with the command, until the new
+    // users are running.
+    let mut snapshot = Value::Null;
+    for _ in 0..50 {
+        snapshot = send_command(&mut websocket, json!({"command": "metrics"}));
+        assert!(snapshot["type"] == "metrics");
+        if snapshot["metrics"]["running_users"] == 3 {
+            break;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    assert!(snapshot["metrics"]["running_users"] == 3);
+    assert!(snapshot["metrics"]["requests"]["GET /"]["success_count"].as_u64() > Some(0));
+
+    let response = send_command(&mut websocket, json!({"command": "stop"}));
+    assert!(response["success"] == true);
+
+    let goose_metrics = load_test.join().unwrap();
+    assert!(goose_metrics.users == 3);
+    assert!(goose_metrics.running_users == 0);
+    assert!(index.times_called() > 0);
+}
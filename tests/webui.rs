@@ -0,0 +1,166 @@
+use httpmock::Method::GET;
+use httpmock::{Mock, MockServer};
+use serde_json::{json, Value};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::{thread, time};
+use tokio_tungstenite::tungstenite::{client, Error, Message, WebSocket};
+
+mod common;
+
+use goose::prelude::*;
+
+const INDEX_PATH: &str = "/";
+
+pub async fn get_index(user: &GooseUser) -> GooseTaskResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// Find an available port.
+fn available_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// Connect to a port once something is listening on it.
+fn connect(port: u16) -> TcpStream {
+    for _ in 0..50 {
+        if let Ok(stream) = TcpStream::connect(("127.0.0.1", port)) {
+            return stream;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    panic!("failed to connect to port {}", port);
+}
+
+// Read metrics snapshots from the WebSocket until one has the expected running users,
+// failing the test if none arrives within 10 seconds.
+fn wait_for_running_users(websocket: &mut WebSocket<TcpStream>, running_users: u64) -> Value {
+    let deadline = time::Instant::now() + time::Duration::from_secs(10);
+    websocket
+        .get_ref()
+        .set_read_timeout(Some(time::Duration::from_millis(500)))
+        .unwrap();
+    loop {
+        assert!(
+            time::Instant::now() < deadline,
+            "timed out waiting for {} running users",
+            running_users
+        );
+        match websocket.read_message() {
+            Ok(Message::Text(text)) => {
+                let message: Value = serde_json::from_str(&text).unwrap();
+                if message["type"] == "metrics"
+                    && message["metrics"]["running_users"] == running_users
+                {
+                    return message;
+                }
+            }
+            Ok(_) => (),
+            // The read timed out, check the deadline and try again.
+            Err(Error::Io(ref e))
+                if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {}
+            Err(e) => panic!("failed to read from websocket: {}", e),
+        }
+    }
+}
+
+#[test]
+#[cfg_attr(not(feature = "webui"), ignore)]
+/// Serve the web UI, and start and stop the load test from it.
+fn test_webui() {
+    let server = MockServer::start();
+
+    let index = Mock::new()
+        .expect_method(GET)
+        .expect_path(INDEX_PATH)
+        .return_status(200)
+        .create_on(&server);
+
+    let webui_port = available_port();
+    let websocket_port = available_port();
+
+    let mut config = common::build_configuration(&server);
+    config.users = Some(1);
+    config.hatch_rate = Some(10);
+    config.run_time = "".to_string();
+    config.webui_port = Some(webui_port);
+    config.websocket_port = Some(websocket_port);
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(
+            taskset!("LoadTest")
+                .set_wait_time(
+                    time::Duration::from_millis(100),
+                    time::Duration::from_millis(100),
+                )
+                .unwrap()
+                .register_task(task!(get_index)),
+        );
+    let load_test = thread::spawn(move || goose_attack.execute().unwrap());
+
+    // The web UI page connects to the WebSocket controller.
+    let mut stream = connect(webui_port);
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut page = String::new();
+    stream.read_to_string(&mut page).unwrap();
+    assert!(page.starts_with("HTTP/1.1 200 OK"));
+    assert!(page.contains(&format!(":{}/", websocket_port)));
+
+    let mut stream = connect(webui_port);
+    stream
+        .write_all(b"GET /missing HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut page = String::new();
+    stream.read_to_string(&mut page).unwrap();
+    assert!(page.starts_with("HTTP/1.1 404 Not Found"));
+
+    // The load test waits to be started from the web UI.
+    let url = format!("ws://127.0.0.1:{}/", websocket_port);
+    let (mut websocket, _) = client(url.as_str(), connect(websocket_port)).unwrap();
+    wait_for_running_users(&mut websocket, 0);
+    assert!(index.times_called() == 0);
+
+    // Start the load test with 2 users, as the web UI's start button does.
+    for command in &[
+        json!({"command": "hatch-rate", "value": 20}),
+        json!({"command": "users", "value": 2}),
+        json!({"command": "start"}),
+    ] {
+        websocket
+            .write_message(Message::Text(command.to_string()))
+            .unwrap();
+    }
+    wait_for_running_users(&mut websocket, 2);
+
+    websocket
+        .write_message(Message::Text(json!({"command": "stop"}).to_string()))
+        .unwrap();
+
+    let goose_metrics = load_test.join().unwrap();
+    assert!(goose_metrics.users == 2);
+    assert!(index.times_called() > 0);
+}
+
+#[test]
+#[cfg_attr(feature = "webui", ignore)]
+/// The web UI requires the webui feature.
+fn test_webui_not_enabled() {
+    let server = MockServer::start();
+
+    let mut config = common::build_configuration(&server);
+    config.webui_port = Some(available_port());
+    let goose_attack = crate::GooseAttack::initialize_with_config(config)
+        .setup()
+        .unwrap()
+        .register_taskset(taskset!("LoadTest").register_task(task!(get_index)))
+        .execute();
+    assert!(goose_attack.is_err());
+}